@@ -1,19 +1,26 @@
 use anchor_lang::prelude::*;
 
+/// Anchor numbers these from 6000 in declaration order, and clients branch
+/// on the numbers, so variants are only ever appended. Variants no check
+/// raises any more are kept, marked retired, to hold their codes.
 #[error_code]
 pub enum DiceError {
+    /// Retired.
     #[msg("Bump error")]
     BumpError,
     #[msg("Overflow")]
     Overflow,
-    #[msg("Minimum bet is 0.01 Sol")]
+    #[msg("Bet is below the house minimum")]
     MinimumBet,
     #[msg("Maximum bet exceeded")]
     MaximumBet,
+    /// Retired.
     #[msg("Minimum roll is 2")]
     MinimumRoll,
+    /// Retired.
     #[msg("Maximum roll is 96")]
     MaximumRoll,
+    /// Retired.
     #[msg("Timeout not yet reached")]
     TimeoutNotReached,
     #[msg("Ed25519 Header Error")]
@@ -22,12 +29,249 @@ pub enum DiceError {
     Ed25519Pubkey,
     #[msg("Ed25519 Message Error")]
     Ed25519Message,
+    /// Retired.
     #[msg("Ed25519 Signature Error")]
     Ed25519Signature,
-    #[msg("Ed25119 Program Error")]
+    /// Retired.
+    #[msg("Ed25519 Program Error")]
     Ed25519Program,
-    #[msg("Ed25119 Accounts Error")]
+    #[msg("Ed25519 Accounts Error")]
     Ed25519Accounts,
-    #[msg("Ed25119 Data Length Error")]
-    Ed25519DataLength
-}
\ No newline at end of file
+    /// Retired.
+    #[msg("Ed25519 Data Length Error")]
+    Ed25519DataLength,
+    #[msg("Fee cannot exceed 10000 basis points")]
+    FeeTooHigh,
+    #[msg("Token mint does not match the bet")]
+    MintMismatch,
+    #[msg("Refund not yet available")]
+    RefundNotYetAvailable,
+    #[msg("Roll must be between 2 and 96 inclusive")]
+    RollOutOfRange,
+    #[msg("Potential payout exceeds vault capacity")]
+    BetExceedsVaultCapacity,
+    #[msg("Minimum bet cannot exceed maximum bet")]
+    InvalidBetLimits,
+    #[msg("Ed25519 signature must be 64 bytes")]
+    Ed25519SignatureLength,
+    #[msg("Player balance is too low for this bet")]
+    InsufficientPlayerBalance,
+    #[msg("Withdrawal would dip into funds reserved for open bets")]
+    InsufficientUnreservedFunds,
+    #[msg("Vault cannot cover the payout")]
+    VaultInsufficientFunds,
+    #[msg("Dice count must be between 1 and 8")]
+    InvalidDiceCount,
+    #[msg("Revealed secret does not match the bet commitment")]
+    CommitMismatch,
+    #[msg("Referrer account missing from remaining accounts")]
+    ReferrerAccountMissing,
+    #[msg("Game is paused")]
+    GamePaused,
+    #[msg("Unsupported bet message version")]
+    UnsupportedBetVersion,
+    #[msg("Cannot close the game while bets are still open")]
+    OpenBetsRemain,
+    #[msg("Batch exceeds the maximum number of bets")]
+    BatchTooLarge,
+    #[msg("Remaining accounts do not match the signature batch")]
+    BatchAccountsMismatch,
+    #[msg("Bet was placed for a different randomness source")]
+    RandomnessSourceMismatch,
+    #[msg("Invalid Switchboard randomness account")]
+    InvalidRandomnessAccount,
+    #[msg("Randomness commitment is not from the previous slot")]
+    RandomnessExpired,
+    #[msg("Randomness has already been revealed")]
+    RandomnessAlreadyRevealed,
+    #[msg("Randomness has not been revealed yet")]
+    RandomnessNotResolved,
+    #[msg("Bet has expired and can only be refunded")]
+    BetExpired,
+    #[msg("No ed25519 instruction precedes this one")]
+    Ed25519InstructionNotFound,
+    #[msg("Potential payout exceeds the house payout cap")]
+    PotentialPayoutExceedsCap,
+    #[msg("A bet with this seed is already open")]
+    SeedAlreadyUsed,
+    #[msg("Fee vault balance is too low for this withdrawal")]
+    InsufficientFeeBalance,
+    #[msg("Cancel window has closed")]
+    CancelWindowClosed,
+    #[msg("Potential payout exceeds the house's maximum exposure")]
+    ExceedsMaxExposure,
+    #[msg("Risk-free bets are not enabled")]
+    RiskFreeDisabled,
+    #[msg("Only a player's first bet can be risk-free")]
+    RiskFreeAlreadyUsed,
+    #[msg("Wait for the bet cooldown to pass before betting again")]
+    BetCooldownActive,
+    #[msg("Only available while the game is paused")]
+    GameNotPaused,
+    #[msg("Released more than the house has reserved")]
+    ReservedAccountingUnderflow,
+    #[msg("Range bets need roll_low <= roll_high")]
+    InvalidRange,
+    #[msg("A winning bet this small would pay nothing")]
+    PayoutTooSmall,
+    #[msg("Maximum bet cannot exceed the house payout cap")]
+    MaxBetExceedsPayoutCap,
+    #[msg("A win this large must be escrowed in a pending payout account")]
+    PendingPayoutRequired,
+    #[msg("A pending payout account was passed for a bet that does not need one")]
+    PendingPayoutNotRequired,
+    #[msg("The challenge window for this payout is still open")]
+    ChallengeWindowOpen,
+    #[msg("The challenge window for this payout has closed")]
+    ChallengeWindowClosed,
+    #[msg("The price feed is older than the configured maximum age")]
+    StalePriceFeed,
+    #[msg("Price account is not the configured SOL/USD feed")]
+    PriceFeedMismatch,
+    #[msg("USD-denominated bets need the SOL/USD price account")]
+    PriceFeedRequired,
+    #[msg("Price feed reported a non-positive price")]
+    InvalidPrice,
+    #[msg("USD-denominated bets are only supported in SOL")]
+    UsdDenominationUnsupported,
+    #[msg("Fee tiers must be ascending and charge no more than the tier below")]
+    InvalidFeeTiers,
+    #[msg("Player account must be a system-owned wallet")]
+    InvalidPlayerAccount,
+    #[msg("This house's config is already initialized")]
+    ConfigAlreadyInitialized,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Player already has the maximum number of open bets")]
+    TooManyOpenBets,
+    #[msg("Vault would fall below its rent-exempt minimum")]
+    VaultWouldBecomeRentExempt,
+    #[msg("Bet account is not in a layout migrate_bet recognises")]
+    UnknownBetLayout,
+    #[msg("Bet account already uses the current layout")]
+    BetAlreadyMigrated,
+    #[msg("Cashout offer exceeds the bet's potential payout")]
+    CashoutExceedsPayout,
+    #[msg("Config, vault and bet belong to different houses")]
+    AccountHouseMismatch,
+    #[msg("Vault balance is not below the rebalance threshold")]
+    VaultAboveRebalanceThreshold,
+    #[msg("Treasury has nothing above rent to move")]
+    InsufficientTreasuryBalance,
+    #[msg("Ed25519 signature entry carries no public key")]
+    Ed25519PubkeyMissing,
+    #[msg("Ed25519 signature entry carries no message")]
+    Ed25519MessageMissing,
+    #[msg("Cashout amount does not match the signed offer")]
+    CashoutOfferMismatch,
+    #[msg("Player cannot be the house or its vault")]
+    PlayerIsHouseAccount,
+    #[msg("Signer is not the house's CPI resolver")]
+    UnauthorizedCpiResolver,
+    #[msg("Signature was submitted after the bet's signature window")]
+    SignatureExpired,
+    #[msg("Bet has not expired yet")]
+    BetNotExpired,
+    #[msg("Rewards are enabled but the reward mint accounts are missing")]
+    RewardAccountsRequired,
+    #[msg("Reward mint or token account does not match the house")]
+    RewardAccountMismatch,
+    #[msg("SlotHashes sysvar has no recent entry")]
+    SlotHashUnavailable,
+    #[msg("Jackpot tier triggers must be at most 100 and fall with each enabled tier")]
+    InvalidJackpotTiers,
+    #[msg("Resolver did not authorize this bet for keeper resolution")]
+    KeeperAuthorizationMismatch,
+    #[msg("Vault did not receive exactly the bet amount")]
+    DepositMismatch,
+    #[msg("Rent recipient is not the account that funded the bet")]
+    RentPayerMismatch,
+    #[msg("Bet would push the payouts reserved for open bets past the house's total exposure cap")]
+    GlobalExposureExceeded,
+    #[msg("Signed placement message does not match the bet")]
+    PlacementMessageMismatch,
+    #[msg("Deposited balance is too low")]
+    InsufficientDepositBalance,
+    #[msg("Player balance must be passed exactly for bets staked from it")]
+    PlayerBalanceMismatch,
+    #[msg("A bet burn is configured but the burn mint accounts are missing")]
+    BurnAccountsRequired,
+    #[msg("Burn mint or token account does not match the house")]
+    BurnAccountMismatch,
+    #[msg("Player holds too few burn tokens to place a bet")]
+    InsufficientBurnBalance,
+    #[msg("Parlay has more legs than MAX_LEGS allows")]
+    TooManyLegs,
+    #[msg("Parlay legs must be d100 Under, Over or Exact predictions on a lamport bet")]
+    InvalidParlayLeg,
+    #[msg("Vault balance is below the house's minimum for accepting bets")]
+    VaultUnderCapitalized,
+    #[msg("The SlotHashes sysvar is required to resolve this bet")]
+    SlotHashesRequired,
+    #[msg("The slot this bet's outcome is drawn from has not been produced yet")]
+    ResolutionTooEarly,
+    #[msg("Reveal delay must exceed the cancel window and be shorter than the refund delay")]
+    InvalidRevealDelay,
+    #[msg("Basis-precision bets must be d100 Under or Over lamport bets without parlay legs")]
+    InvalidBasisBet,
+    #[msg("Too few members of the resolver quorum approved resolving this bet")]
+    InsufficientResolverSignatures,
+    #[msg("Resolver set has duplicate keys or a threshold it cannot meet")]
+    InvalidResolverSet,
+    #[msg("Withdrawal is still within the house's timelock")]
+    WithdrawalStillLocked,
+    #[msg("There is no withdrawal request to claim")]
+    NoPendingWithdrawal,
+    #[msg("Stake is below the house's minimum fee")]
+    FeeExceedsStake,
+    #[msg("Player has excluded themselves from betting")]
+    PlayerSelfExcluded,
+    #[msg("A self-exclusion can only be extended, not shortened")]
+    SelfExclusionShortened,
+    #[msg("Free credit balance is too low")]
+    InsufficientFreeCredits,
+    #[msg("Free credits must be passed exactly for bets staked from them, which can't be risk-free or use a balance")]
+    FreeCreditsMismatch,
+    #[msg("Instructions account is not the instructions sysvar")]
+    InvalidInstructionsSysvar,
+    #[msg("No resolver-signed slot window covering the current slot")]
+    ResolutionStale,
+    #[msg("Charity account missing from remaining accounts")]
+    CharityAccountMissing,
+    #[msg("Used-seed marker must be passed exactly while the house consumes seeds")]
+    UsedSeedMismatch,
+    #[msg("Seed was already used by an earlier bet")]
+    SeedAlreadyConsumed,
+    #[msg("Dice must have 2 to 1000 faces, and dice other than a d100 only take single-die Under, Over or Exact bets")]
+    InvalidFaces,
+    #[msg("Cancellation fee cannot exceed the stake")]
+    CancelFeeExceedsStake,
+    #[msg("House has not enabled auto-resolution")]
+    AutoResolveDisabled,
+    #[msg("Bet has no presigned signature")]
+    BetNotPresigned,
+    #[msg("Bet is already presigned")]
+    BetAlreadyPresigned,
+    #[msg("Randomness source has no backend yet")]
+    RandomnessBackendUnsupported,
+    #[msg("Volume rebates must be ascending and rebate no less than the tier below")]
+    InvalidVolumeRebates,
+    #[msg("Signed message is not the length of the bet message")]
+    Ed25519MessageLength,
+    #[msg("Pass the player's wSOL account to be paid in wSOL, or the unwrap account to be paid in SOL")]
+    WsolAccountsMismatch,
+    #[msg("Bets must be passed in ascending address order, each once")]
+    BetsNotAscending,
+    #[msg("Bet was already settled and only awaits closing")]
+    BetAlreadySettled,
+    #[msg("Bet has not been settled")]
+    BetNotSettled,
+    #[msg("The slot this bet's outcome is drawn from has been produced, so it can no longer be cancelled")]
+    OutcomeRevealed,
+    #[msg("No ed25519 instruction carries a signature")]
+    Ed25519SignatureCount,
+    #[msg("No ed25519 instruction carries the supplied signature")]
+    Ed25519SignatureValue,
+    #[msg("A bet staked from free credits cannot be cashed out")]
+    FreeCreditCashout,
+}
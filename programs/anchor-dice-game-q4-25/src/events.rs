@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+
+use crate::state::JackpotTierKind;
+
+#[event]
+pub struct BetResolved {
+    pub player: Pubkey,
+    pub seed: u128,
+    pub roll: u8,
+    pub result: u8,
+    pub won: bool,
+    pub payout: u64,
+    pub memo: [u8; 32],
+    /// `bonus_roll_offset` in the bet's terms, fixed when it was placed.
+    pub roll_offset: i8,
+    /// Near-miss consolation paid on a loss; 0 otherwise.
+    pub consolation: u64,
+    /// Share of a lost stake given to the house's charity; 0 otherwise.
+    pub charity: u64,
+}
+
+#[event]
+pub struct JackpotWon {
+    pub player: Pubkey,
+    pub seed: u128,
+    pub amount: u64,
+}
+
+#[event]
+pub struct JackpotTierWon {
+    pub player: Pubkey,
+    pub seed: u128,
+    pub tier: JackpotTierKind,
+    pub amount: u64,
+}
+
+#[event]
+pub struct KeeperPaid {
+    pub keeper: Pubkey,
+    pub player: Pubkey,
+    pub seed: u128,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VaultFunded {
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub new_balance: u64,
+}
+
+#[event]
+pub struct EmergencyRefund {
+    pub player: Pubkey,
+    pub seed: u128,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BetCashedOut {
+    pub player: Pubkey,
+    pub seed: u128,
+    pub amount: u64,
+    pub memo: [u8; 32],
+}
+
+#[event]
+pub struct TreasuryRebalanced {
+    pub amount: u64,
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct LimitsUpdated {
+    pub min_bet: u64,
+    pub max_bet: u64,
+}
+
+#[event]
+pub struct ReservedReconciled {
+    pub before: u64,
+    pub after: u64,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+/// Everything needed to re-derive a bet's outcome offline: `input_hash` is
+/// the sha256 of `ROLL_DOMAIN`, the randomness input (the player's
+/// signature, or the reveal followed by the house seed) and `slot_hash`,
+/// the one `Bet::outcome_slot_hash` picked, which
+/// `compute_outcome` reduces to `roll` —
+/// the whole hash as a little-endian integer mod 100, plus one, for a d100
+/// bet, or one d6 per 4-byte chunk for a dice bet.
+#[event]
+pub struct OutcomeProof {
+    pub player: Pubkey,
+    pub seed: u128,
+    pub input_hash: [u8; 32],
+    pub slot_hash: [u8; 32],
+    pub dice_count: u8,
+    pub roll: u8,
+}
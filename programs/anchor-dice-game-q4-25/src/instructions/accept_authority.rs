@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, events::AuthorityTransferred, state::HouseConfig};
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub pending_authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> AcceptAuthority<'info> {
+    pub fn accept_authority(&mut self) -> Result<()> {
+        require!(
+            self.config.pending_authority == Some(self.pending_authority.key()),
+            DiceError::NotPendingAuthority
+        );
+        let previous_authority = self.config.authority;
+        self.config.authority = self.pending_authority.key();
+        self.config.pending_authority = None;
+
+        emit!(AuthorityTransferred {
+            previous_authority,
+            new_authority: self.config.authority,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Allowlisted, HouseConfig};
+
+#[derive(Accounts)]
+pub struct AddToAllowlist<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    /// CHECK: only the address is recorded
+    pub player: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Allowlisted::INIT_SPACE,
+        seeds = [b"allowlist", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump
+    )]
+    pub allowlisted: Account<'info, Allowlisted>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddToAllowlist<'info> {
+    pub fn add_to_allowlist(&mut self, bumps: &AddToAllowlistBumps) -> Result<()> {
+        self.allowlisted.set_inner(Allowlisted {
+            player: self.player.key(),
+            bump: bumps.allowlisted,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{errors::DiceError, state::{Bet, FeeVault, FreeCredits, HouseConfig, PlayerBalance, PlayerStats}, utils::require_vault_rent_exempt};
+
+#[constant]
+pub const CANCEL_WINDOW_SLOTS: u64 = 10;
+
+#[derive(Accounts)]
+pub struct CancelBet<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// The player's free credits, which a free-credit bet's stake is returned
+    /// to instead of the wallet.
+    #[account(
+        mut,
+        seeds = [b"free_credits", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = free_credits.bump
+    )]
+    pub free_credits: Option<Account<'info, FreeCredits>>,
+    /// The player's `PlayerBalance`, which a bet staked from it is returned
+    /// to instead of the wallet.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    /// Receives the `cancel_fee_bps` cut, unless the house reinvests fees.
+    #[account(
+        mut,
+        seeds = [b"fee_vault", house.key().as_ref(), &[config.game_id]],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelBet<'info> {
+    pub fn cancel_bet(&mut self) -> Result<()> {
+        require!(self.bet.cached_outcome.is_none(), DiceError::BetAlreadySettled);
+        require!(self.bet.mint.is_none(), DiceError::MintMismatch);
+        require!(
+            self.bet.free_credit == self.free_credits.is_some(),
+            DiceError::FreeCreditsMismatch
+        );
+        require!(
+            self.bet.from_balance == self.player_balance.is_some(),
+            DiceError::PlayerBalanceMismatch
+        );
+        let slot = Clock::get()?.slot;
+        // Checked on its own so no reveal delay, however configured, can
+        // leave a player cancelling a bet whose outcome they can compute.
+        require!(
+            slot < self.config.reveal_slot(self.bet.slot)?,
+            DiceError::OutcomeRevealed
+        );
+        let cancel_deadline = self
+            .bet
+            .slot
+            .checked_add(CANCEL_WINDOW_SLOTS)
+            .ok_or(DiceError::Overflow)?;
+        require!(slot <= cancel_deadline, DiceError::CancelWindowClosed);
+        self.config.release_reservation(self.bet.potential_payout)?;
+        self.stats.release_open_bet();
+        let fee = self
+            .bet
+            .amount
+            .checked_mul(self.config.cancel_fee_bps as u64)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        let refund = self
+            .bet
+            .amount
+            .checked_sub(fee)
+            .ok_or(DiceError::CancelFeeExceedsStake)?;
+        let to = match (&self.free_credits, &self.player_balance) {
+            (Some(free_credits), _) => free_credits.to_account_info(),
+            (None, Some(player_balance)) => player_balance.to_account_info(),
+            (None, None) => self.player.to_account_info(),
+        };
+        // A reinvested fee simply stays in the vault.
+        let paid_out = if self.config.reinvest_fees { refund } else { self.bet.amount };
+        require_vault_rent_exempt(self.vault.lamports(), paid_out)?;
+        if fee > 0 && !self.config.reinvest_fees {
+            self.transfer_from_vault(self.fee_vault.to_account_info(), fee)?;
+        }
+        self.transfer_from_vault(to, refund)
+    }
+
+    fn transfer_from_vault(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to,
+        };
+
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds,
+        );
+
+        transfer(ctx, amount)
+    }
+}
@@ -0,0 +1,123 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use solana_program::sysvar::instructions::ID as InstructionSysvarId;
+
+use crate::{
+    errors::DiceError,
+    events::BetCashedOut,
+    instructions::verify_cashout_signature,
+    state::{Bet, HouseConfig, PlayerBalance, PlayerStats, RandomnessSource},
+    utils::require_vault_rent_exempt,
+};
+
+#[derive(Accounts)]
+pub struct Cashout<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// The player's `PlayerBalance`, which a bet staked from it is cashed
+    /// out into instead of the wallet.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    /// CHECK: instructions sysvar
+    #[account(
+        address = InstructionSysvarId
+    )]
+    pub instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Cashout<'info> {
+    /// Closes an open bet for `amount`, a price the resolver signed over
+    /// `Bet::cashout_message` and the player accepts by sending the
+    /// transaction. The offer can't top the bet's potential payout.
+    ///
+    /// Signature-resolved bets are excluded: the player can compute their
+    /// own outcome from the signature before accepting any offer. So are
+    /// free-credit bets, whose grant could otherwise be cashed out without
+    /// winning.
+    pub fn cashout(&mut self, amount: u64, sig: &[u8]) -> Result<()> {
+        require!(self.bet.mint.is_none(), DiceError::MintMismatch);
+        require!(!self.bet.free_credit, DiceError::FreeCreditCashout);
+        require!(
+            self.bet.from_balance == self.player_balance.is_some(),
+            DiceError::PlayerBalanceMismatch
+        );
+        require!(
+            self.bet.randomness_source != RandomnessSource::Signature,
+            DiceError::RandomnessSourceMismatch
+        );
+        require!(
+            Clock::get()?.slot <= self.bet.expiry_slot,
+            DiceError::BetExpired
+        );
+        verify_cashout_signature(&self.instructions, &self.config.resolver, &self.bet, amount, sig)?;
+        require!(
+            amount <= self.bet.potential_payout,
+            DiceError::CashoutExceedsPayout
+        );
+        self.config.release_reservation(self.bet.potential_payout)?;
+        self.stats.release_open_bet();
+        require_vault_rent_exempt(self.vault.lamports(), amount)?;
+
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.vault.to_account_info(),
+                to: match &self.player_balance {
+                    Some(player_balance) => player_balance.to_account_info(),
+                    None => self.player.to_account_info(),
+                },
+            },
+            signer_seeds,
+        );
+        transfer(ctx, amount)?;
+
+        emit!(BetCashedOut {
+            player: self.bet.player,
+            seed: self.bet.seed,
+            amount,
+            memo: self.bet.memo,
+        });
+        Ok(())
+    }
+}
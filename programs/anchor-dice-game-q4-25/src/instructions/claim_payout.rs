@@ -0,0 +1,68 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{
+    errors::DiceError,
+    state::{HouseConfig, PendingPayout},
+    utils::require_vault_rent_exempt,
+};
+
+#[derive(Accounts)]
+pub struct ClaimPayout<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = player,
+        seeds = [b"pending_payout", vault.key().as_ref(), player.key().as_ref(), pending_payout.seed.to_le_bytes().as_ref()],
+        bump = pending_payout.bump
+    )]
+    pub pending_payout: Account<'info, PendingPayout>,
+    /// CHECK: gets the pending payout's rent back; must be whoever paid it
+    #[account(mut, address = pending_payout.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimPayout<'info> {
+    pub fn claim_payout(&mut self) -> Result<()> {
+        require!(
+            Clock::get()?.slot >= self.pending_payout.release_slot,
+            DiceError::ChallengeWindowOpen
+        );
+        self.config.release_reservation(self.pending_payout.amount)?;
+        require_vault_rent_exempt(self.vault.lamports(), self.pending_payout.amount)?;
+        let accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.player.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds,
+        );
+
+        transfer(ctx, self.pending_payout.amount)
+    }
+}
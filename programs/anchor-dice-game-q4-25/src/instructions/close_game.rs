@@ -0,0 +1,97 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{errors::DiceError, state::{FeeVault, HouseConfig, HouseLedger, Jackpot, Jackpots, RecentOutcomes, Treasury}};
+
+#[derive(Accounts)]
+pub struct CloseGame<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"jackpot", house.key().as_ref(), &[config.game_id]],
+        bump = jackpot.bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"jackpots", house.key().as_ref(), &[config.game_id]],
+        bump = jackpots.bump
+    )]
+    pub jackpots: Account<'info, Jackpots>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"fee_vault", house.key().as_ref(), &[config.game_id]],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"treasury", house.key().as_ref(), &[config.game_id]],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"recent_outcomes", house.key().as_ref(), &[config.game_id]],
+        bump = recent_outcomes.bump
+    )]
+    pub recent_outcomes: Account<'info, RecentOutcomes>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"ledger", house.key().as_ref(), &[config.game_id]],
+        bump = ledger.bump
+    )]
+    pub ledger: Account<'info, HouseLedger>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseGame<'info> {
+    /// Sweeps the whole vault to the authority, like the closed accounts'
+    /// rent, which leaves the system-owned vault PDA empty so the runtime
+    /// reclaims it.
+    pub fn close_game(&mut self) -> Result<()> {
+        require_eq!(self.config.reserved_lamports, 0, DiceError::OpenBetsRemain);
+        require_eq!(self.config.open_token_bets, 0, DiceError::OpenBetsRemain);
+
+        let accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.authority.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds,
+        );
+
+        transfer(ctx, self.vault.lamports())
+    }
+}
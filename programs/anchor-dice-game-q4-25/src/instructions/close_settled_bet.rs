@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::DiceError,
+    state::{Bet, HouseConfig},
+};
+
+/// Closes a bet `resolve_bet_cpi` left open under `cache_outcomes`, once
+/// its caller no longer needs to retry. Either party to the CPI resolution
+/// may close it; the rent goes back to whoever funded the bet as usual.
+#[derive(Accounts)]
+pub struct CloseSettledBet<'info> {
+    #[account(
+        constraint = Some(closer.key()) == config.cpi_resolver || closer.key() == bet.player @ DiceError::UnauthorizedCpiResolver
+    )]
+    pub closer: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [b"bet", vault.key().as_ref(), bet.player.as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+}
+
+impl<'info> CloseSettledBet<'info> {
+    pub fn close_settled_bet(&self) -> Result<()> {
+        require!(self.bet.cached_outcome.is_some(), DiceError::BetNotSettled);
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::DiceError,
+    instructions::Settlement,
+    randomness::{RandomnessBackend, SwitchboardVrf},
+    Bet, FeeVault, HouseConfig, Jackpot, Jackpots, PendingPayout, PlayerBalance, PlayerStats, RandomnessSource, Treasury,
+};
+
+#[derive(Accounts)]
+pub struct ConsumeRandomness<'info> {
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = resolver,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        seeds = [b"jackpot", house.key().as_ref(), &[config.game_id]],
+        bump = jackpot.bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+    #[account(
+        mut,
+        seeds = [b"jackpots", house.key().as_ref(), &[config.game_id]],
+        bump = jackpots.bump
+    )]
+    pub jackpots: Account<'info, Jackpots>,
+    #[account(
+        mut,
+        seeds = [b"fee_vault", house.key().as_ref(), &[config.game_id]],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    #[account(
+        mut,
+        seeds = [b"treasury", house.key().as_ref(), &[config.game_id]],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    /// CHECK: the player
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// Only for bets staked from a `PlayerBalance`, which their winnings are
+    /// credited back to.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    /// Only for a win at or above `large_win_threshold`: receives the escrowed
+    /// payout, with the resolver paying its rent.
+    #[account(
+        init,
+        payer = resolver,
+        space = 8 + PendingPayout::INIT_SPACE,
+        seeds = [b"pending_payout", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pending_payout: Option<Account<'info, PendingPayout>>,
+    /// CHECK: checked against the account bound by `request_randomness`
+    pub randomness_account_data: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ConsumeRandomness<'info> {
+    pub fn consume_randomness(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(
+            self.bet.randomness_source == RandomnessSource::Switchboard,
+            DiceError::RandomnessSourceMismatch
+        );
+        require!(self.config.resolver_threshold == 0, DiceError::InsufficientResolverSignatures);
+        require!(self.config.resolution_window_slots == 0, DiceError::ResolutionStale);
+        let entropy = SwitchboardVrf { randomness_account: &self.randomness_account_data }
+            .entropy(&self.bet, &self.config)?;
+
+        Settlement {
+            house: &self.house,
+            vault: &self.vault,
+            config: &mut self.config,
+            jackpot: &mut self.jackpot,
+            jackpots: &mut self.jackpots,
+            fee_vault: &self.fee_vault,
+            treasury: &self.treasury,
+            price_update: None,
+            keeper: None,
+            player_balance: self.player_balance.as_ref().map(|balance| balance.as_ref()),
+            pending_rent_payer: self.resolver.key(),
+            system_program: &self.system_program,
+        }
+        .settle(
+            &self.bet,
+            self.player.to_account_info(),
+            &mut self.stats,
+            self.pending_payout.as_mut(),
+            self.bet.rolls(&entropy.input, &entropy.slot_hash),
+            remaining_accounts,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{create_account, CreateAccount},
+};
+
+use crate::state::HouseConfig;
+
+/// Test-only: writes `data` verbatim into a fresh bet PDA, so `migrate_bet`
+/// can be exercised against bets in layouts the program no longer writes.
+#[derive(Accounts)]
+#[instruction(seed: u128)]
+pub struct CreateLegacyBet<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    /// CHECK: only used as a seed
+    pub player: UncheckedAccount<'info>,
+    /// CHECK: created here with whatever bytes the test supplies
+    #[account(
+        mut,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bet: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateLegacyBet<'info> {
+    pub fn create_legacy_bet(&mut self, bumps: &CreateLegacyBetBumps, seed: u128, data: &[u8]) -> Result<()> {
+        let vault = self.vault.key();
+        let player = self.player.key();
+        let seed_bytes = seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"bet", vault.as_ref(), player.as_ref(), seed_bytes.as_ref(), &[bumps.bet]]];
+        create_account(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                CreateAccount {
+                    from: self.payer.to_account_info(),
+                    to: self.bet.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            Rent::get()?.minimum_balance(data.len()),
+            data.len() as u64,
+            &crate::ID,
+        )?;
+        self.bet.try_borrow_mut_data()?.copy_from_slice(data);
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::state::{HouseConfig, PlayerBalance};
+
+#[derive(Accounts)]
+pub struct DepositBalance<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerBalance::INIT_SPACE,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump
+    )]
+    pub player_balance: Account<'info, PlayerBalance>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> DepositBalance<'info> {
+    pub fn deposit_balance(&mut self, bumps: &DepositBalanceBumps, amount: u64) -> Result<()> {
+        self.player_balance.player = self.player.key();
+        self.player_balance.bump = bumps.player_balance;
+
+        let accounts = Transfer {
+            from: self.player.to_account_info(),
+            to: self.player_balance.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            accounts
+        );
+        transfer(ctx, amount)
+    }
+}
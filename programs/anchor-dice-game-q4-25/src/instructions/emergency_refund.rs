@@ -0,0 +1,116 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{
+    errors::DiceError,
+    events::EmergencyRefund,
+    state::{Bet, FreeCredits, HouseConfig, PlayerBalance, PlayerStats},
+    utils::require_vault_rent_exempt,
+};
+
+#[derive(Accounts)]
+pub struct EmergencyRefundBet<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(mut)]
+    pub player: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// The player's free credits, which a free-credit bet's stake is returned
+    /// to instead of the wallet.
+    #[account(
+        mut,
+        seeds = [b"free_credits", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = free_credits.bump
+    )]
+    pub free_credits: Option<Account<'info, FreeCredits>>,
+    /// The player's `PlayerBalance`, which a bet staked from it is returned
+    /// to instead of the wallet.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> EmergencyRefundBet<'info> {
+    /// Refunds a bet without waiting for it to expire. Only available while
+    /// the game is paused, so it can't be used to cherry-pick open bets in
+    /// normal operation.
+    pub fn emergency_refund(&mut self) -> Result<()> {
+        require!(self.config.paused, DiceError::GameNotPaused);
+        require!(self.bet.cached_outcome.is_none(), DiceError::BetAlreadySettled);
+        require!(self.bet.mint.is_none(), DiceError::MintMismatch);
+        require!(
+            self.bet.free_credit == self.free_credits.is_some(),
+            DiceError::FreeCreditsMismatch
+        );
+        require!(
+            self.bet.from_balance == self.player_balance.is_some(),
+            DiceError::PlayerBalanceMismatch
+        );
+        self.config.release_reservation(self.bet.potential_payout)?;
+        self.stats.release_open_bet();
+        require_vault_rent_exempt(self.vault.lamports(), self.bet.amount)?;
+        let to = match (&self.free_credits, &self.player_balance) {
+            (Some(free_credits), _) => free_credits.to_account_info(),
+            (None, Some(player_balance)) => player_balance.to_account_info(),
+            (None, None) => self.player.to_account_info(),
+        };
+        let accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to,
+        };
+
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds,
+        );
+        transfer(ctx, self.bet.amount)?;
+
+        emit!(EmergencyRefund {
+            player: self.bet.player,
+            seed: self.bet.seed,
+            amount: self.bet.amount,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{events::VaultFunded, utils::require_vault_rent_exempt};
+
+#[derive(Accounts)]
+#[instruction(game_id: u8)]
+pub struct FundVault<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", house.key().as_ref(), &[game_id]],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> FundVault<'info> {
+    pub fn fund_vault(&mut self, amount: u64) -> Result<()> {
+        let accounts = Transfer {
+            from: self.funder.to_account_info(),
+            to: self.vault.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            accounts
+        );
+
+        transfer(ctx, amount)?;
+        require_vault_rent_exempt(self.vault.lamports(), 0)?;
+
+        emit!(VaultFunded {
+            funder: self.funder.key(),
+            amount,
+            new_balance: self.vault.lamports(),
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{BetDirection, HouseConfig}, utils::house_edge_bps};
+
+#[derive(Accounts)]
+pub struct GetHouseEdge<'info> {
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> GetHouseEdge<'info> {
+    /// Read-only: returns the house edge in basis points on a bet with these
+    /// parameters at the current config, via return data. Computed from the
+    /// live config so a displayed edge can't drift from what settlement pays.
+    pub fn get_house_edge(&self, amount: u64, roll: u8, direction: BetDirection, dice_count: u8, range: (u8, u8)) -> Result<i64> {
+        house_edge_bps(&self.config, amount, direction, roll, range, dice_count)
+    }
+}
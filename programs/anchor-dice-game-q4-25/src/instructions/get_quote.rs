@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{BetDirection, HouseConfig}, utils::{apply_fee_floor, bet_odds, payout_for}};
+
+#[derive(Accounts)]
+pub struct GetQuote<'info> {
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> GetQuote<'info> {
+    /// Read-only: returns what a winning bet with these parameters would pay
+    /// at the current fee, via return data.
+    pub fn get_quote(&self, amount: u64, roll: u8, direction: BetDirection, dice_count: u8, range: (u8, u8)) -> Result<u64> {
+        let (winning_outcomes, total_outcomes) = bet_odds(direction, roll, range, dice_count)?;
+        let payout = payout_for(amount, winning_outcomes, total_outcomes, self.config.fee_bps_for(amount), self.config.round_up_payouts)?;
+        apply_fee_floor(payout, amount, winning_outcomes, total_outcomes, self.config.min_fee_lamports)
+    }
+}
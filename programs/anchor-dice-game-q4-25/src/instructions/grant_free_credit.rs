@@ -0,0 +1,47 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::state::{FreeCredits, HouseConfig};
+
+#[derive(Accounts)]
+pub struct GrantFreeCredit<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    /// CHECK: only the address is recorded
+    pub player: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + FreeCredits::INIT_SPACE,
+        seeds = [b"free_credits", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump
+    )]
+    pub free_credits: Account<'info, FreeCredits>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> GrantFreeCredit<'info> {
+    /// Adds `amount` lamports of credit, paid for by the authority.
+    pub fn grant_free_credit(&mut self, bumps: &GrantFreeCreditBumps, amount: u64) -> Result<()> {
+        self.free_credits.player = self.player.key();
+        self.free_credits.bump = bumps.free_credits;
+
+        let accounts = Transfer {
+            from: self.authority.to_account_info(),
+            to: self.free_credits.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            accounts
+        );
+        transfer(ctx, amount)
+    }
+}
@@ -1,12 +1,13 @@
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
 
 #[derive(Accounts)]
+#[instruction(game_id: u8)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub house: Signer<'info>,
     #[account(
         mut,
-        seeds = [b"vault", house.key().as_ref()],
+        seeds = [b"vault", house.key().as_ref(), &[game_id]],
         bump
     )]
     pub vault: SystemAccount<'info>,
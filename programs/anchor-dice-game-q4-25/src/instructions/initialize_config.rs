@@ -0,0 +1,193 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, instructions::{CANCEL_WINDOW_SLOTS, REFUND_DELAY_SLOTS}, state::{FeeVault, HouseConfig, HouseConfigArgs, HouseLedger, Jackpot, Jackpots, RecentOutcomes, Treasury, MAX_RESOLVERS}};
+
+#[derive(Accounts)]
+#[instruction(args: HouseConfigArgs)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub house: Signer<'info>,
+    #[account(
+        seeds = [b"vault", house.key().as_ref(), &[args.game_id]],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    /// `init_if_needed` on the config and every account created with it so a
+    /// repeated call reaches `init_config` and fails with
+    /// `ConfigAlreadyInitialized` instead of the system program's generic
+    /// error.
+    #[account(
+        init_if_needed,
+        payer = house,
+        space = 8 + HouseConfig::INIT_SPACE,
+        seeds = [b"config", house.key().as_ref(), &[args.game_id]],
+        bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        init_if_needed,
+        payer = house,
+        space = 8 + Jackpot::INIT_SPACE,
+        seeds = [b"jackpot", house.key().as_ref(), &[args.game_id]],
+        bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+    #[account(
+        init_if_needed,
+        payer = house,
+        space = 8 + Jackpots::INIT_SPACE,
+        seeds = [b"jackpots", house.key().as_ref(), &[args.game_id]],
+        bump
+    )]
+    pub jackpots: Account<'info, Jackpots>,
+    #[account(
+        init_if_needed,
+        payer = house,
+        space = 8 + FeeVault::INIT_SPACE,
+        seeds = [b"fee_vault", house.key().as_ref(), &[args.game_id]],
+        bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    #[account(
+        init_if_needed,
+        payer = house,
+        space = 8 + Treasury::INIT_SPACE,
+        seeds = [b"treasury", house.key().as_ref(), &[args.game_id]],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(
+        init_if_needed,
+        payer = house,
+        space = 8 + RecentOutcomes::INIT_SPACE,
+        seeds = [b"recent_outcomes", house.key().as_ref(), &[args.game_id]],
+        bump
+    )]
+    pub recent_outcomes: Account<'info, RecentOutcomes>,
+    #[account(
+        init_if_needed,
+        payer = house,
+        space = 8 + HouseLedger::INIT_SPACE,
+        seeds = [b"ledger", house.key().as_ref(), &[args.game_id]],
+        bump
+    )]
+    pub ledger: Account<'info, HouseLedger>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> InitializeConfig<'info> {
+    pub fn init_config(&mut self, bumps: &InitializeConfigBumps, args: HouseConfigArgs) -> Result<()> {
+        let HouseConfigArgs { game_id, resolver, fee_bps, fee_tiers, min_bet, max_bet, referral_bps, jackpot_contribution_bps, insurance_bps, rebalance_threshold, max_payout, max_exposure_bps, streak_bonus_bps, max_streak_bonus_bps, risk_free_enabled, round_up_payouts, bonus_roll_offset, player_wins_ties, bet_cooldown_slots, large_win_threshold, challenge_slots, max_open_bets_per_player, sol_usd_feed_id, max_price_age_secs, near_miss_window, near_miss_bps, signature_window_slots, rewards_enabled, reward_mint, reward_rate, keeper_fee_bps, max_total_exposure_bps, bet_burn_amount, burn_mint, reinvest_fees, min_vault_balance, reveal_delay_slots, withdrawal_timelock_slots, min_fee_lamports, min_jackpot, resolution_window_slots, charity, charity_bps, consume_seeds, cancel_fee_bps, auto_resolve, volume_rebates, cache_outcomes } = args;
+        require!(self.config.authority == Pubkey::default(), DiceError::ConfigAlreadyInitialized);
+        HouseConfig::validate_fee_tiers(fee_bps, &fee_tiers)?;
+        HouseConfig::validate_volume_rebates(&volume_rebates)?;
+        require!(min_bet <= max_bet, DiceError::InvalidBetLimits);
+        require!(referral_bps <= 10_000, DiceError::FeeTooHigh);
+        require!(jackpot_contribution_bps <= 10_000, DiceError::FeeTooHigh);
+        require!(insurance_bps <= 10_000, DiceError::FeeTooHigh);
+        require!(near_miss_bps <= 10_000, DiceError::FeeTooHigh);
+        require!(keeper_fee_bps <= 10_000, DiceError::FeeTooHigh);
+        require!(charity_bps <= 10_000, DiceError::FeeTooHigh);
+        require!(cancel_fee_bps <= 10_000, DiceError::CancelFeeExceedsStake);
+        require!(!rewards_enabled || reward_mint.is_some(), DiceError::RewardAccountsRequired);
+        require!(bet_burn_amount == 0 || burn_mint.is_some(), DiceError::BurnAccountsRequired);
+        require!(max_exposure_bps <= 10_000, DiceError::FeeTooHigh);
+        require!(max_total_exposure_bps <= 10_000, DiceError::FeeTooHigh);
+        require!(max_streak_bonus_bps <= 10_000, DiceError::FeeTooHigh);
+        require!(
+            reveal_delay_slots > CANCEL_WINDOW_SLOTS && reveal_delay_slots < REFUND_DELAY_SLOTS,
+            DiceError::InvalidRevealDelay
+        );
+        self.config.set_inner(HouseConfig {
+            house_id: self.house.key(),
+            game_id,
+            authority: self.house.key(),
+            pending_authority: None,
+            resolver,
+            cpi_resolver: None,
+            fee_bps,
+            fee_tiers,
+            min_bet,
+            max_bet,
+            reserved_lamports: 0,
+            referral_bps,
+            jackpot_contribution_bps,
+            insurance_bps,
+            rebalance_threshold,
+            paused: false,
+            max_payout,
+            max_exposure_bps,
+            bet_count: 0,
+            streak_bonus_bps,
+            max_streak_bonus_bps,
+            risk_free_enabled,
+            round_up_payouts,
+            bonus_roll_offset,
+            player_wins_ties,
+            bet_cooldown_slots,
+            large_win_threshold,
+            challenge_slots,
+            max_open_bets_per_player,
+            sol_usd_feed_id,
+            max_price_age_secs,
+            near_miss_window,
+            near_miss_bps,
+            signature_window_slots,
+            rewards_enabled,
+            reward_mint,
+            reward_rate,
+            keeper_fee_bps,
+            max_total_exposure_bps,
+            bet_burn_amount,
+            burn_mint,
+            reinvest_fees,
+            min_vault_balance,
+            reveal_delay_slots,
+            resolver_set: [Pubkey::default(); MAX_RESOLVERS],
+            resolver_threshold: 0,
+            withdrawal_timelock_slots,
+            pending_withdrawal: 0,
+            withdrawal_unlock_slot: 0,
+            min_fee_lamports,
+            min_jackpot,
+            resolution_window_slots,
+            charity,
+            charity_bps,
+            consume_seeds,
+            cancel_fee_bps,
+            auto_resolve,
+            volume_rebates,
+            cache_outcomes,
+            open_token_bets: 0,
+            vault_bump: bumps.vault,
+            bump: bumps.config,
+        });
+        self.jackpot.set_inner(Jackpot {
+            balance: 0,
+            bump: bumps.jackpot,
+        });
+        self.jackpots.set_inner(Jackpots {
+            tiers: Default::default(),
+            bump: bumps.jackpots,
+        });
+        self.fee_vault.set_inner(FeeVault {
+            bump: bumps.fee_vault,
+        });
+        self.treasury.set_inner(Treasury {
+            bump: bumps.treasury,
+        });
+        self.recent_outcomes.set_inner(RecentOutcomes {
+            outcomes: Default::default(),
+            position: 0,
+            bump: bumps.recent_outcomes,
+        });
+        self.ledger.set_inner(HouseLedger {
+            lifetime_bets: 0,
+            lifetime_volume: 0,
+            lifetime_payouts: 0,
+            lifetime_fees: 0,
+            bump: bumps.ledger,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{
+    errors::DiceError,
+    instructions::{ResolveOutcome, REFUND_DELAY_SLOTS},
+    state::{Bet, BetDirection, BetTerms, HouseConfig, PrecisionMode, RandomnessSource, BET_LAYOUT_VERSION, MAX_LEGS},
+    utils::{apply_fee_floor, bet_odds, payout_for, D100_FACES},
+};
+
+/// The original `Bet` layout, before any of the later fields were added.
+#[derive(AnchorDeserialize)]
+struct BetV0 {
+    player: Pubkey,
+    seed: u128,
+    slot: u64,
+    amount: u64,
+    roll: u8,
+    bump: u8,
+}
+
+const BET_V0_LEN: usize = 8 + 32 + 16 + 8 + 8 + 1 + 1;
+
+/// The fields every versioned layout starts with, up to `memo`. Only read
+/// for its length: the `Option`s in it are stored compactly when `None`,
+/// so where a stored bet's later fields begin varies from bet to bet.
+#[derive(AnchorDeserialize)]
+#[allow(dead_code)]
+struct BetPrefix {
+    player: Pubkey,
+    seed: u128,
+    nonce: u64,
+    slot: u64,
+    expiry_slot: u64,
+    amount: u64,
+    roll: u8,
+    bump: u8,
+    mint: Option<Pubkey>,
+    direction: BetDirection,
+    potential_payout: u64,
+    dice_count: u8,
+    player_commit: [u8; 32],
+    referrer: Option<Pubkey>,
+    randomness_source: RandomnessSource,
+    randomness_account: Option<Pubkey>,
+    risk_free: bool,
+    roll_low: u8,
+    roll_high: u8,
+    usd_denominated: bool,
+    entry_price: i64,
+    entry_price_expo: i32,
+    memo: [u8; 32],
+}
+
+/// The fields each versioned layout lacks, in the order they were added:
+/// layout 1 predates `signature_window_slots`, layout 2 `slot_hash`, layout
+/// 3 `rent_payer`, layout 4 `from_balance`, layout 5 the parlay `legs` and
+/// `leg_count`, layout 6 `precision` and `roll_bps`, layout 7
+/// `free_credit`, layout 8 `faces`, layout 9 `terms`, layout 10
+/// `presigned_sig`, layout 11 `client_seed`, layout 12 `cached_outcome`.
+/// Each entry is the room the field takes in the account and the length of
+/// its zeroed encoding, which is shorter for an `Option`.
+const ADDED_FIELDS: [(u8, usize, usize); 12] = [
+    (1, 8, 8),
+    (2, 32, 32),
+    (3, 32, 32),
+    (4, 1, 1),
+    (5, 2 * MAX_LEGS + 1, 2 * MAX_LEGS + 1),
+    (6, 3, 3),
+    (7, 1, 1),
+    (8, 2, 2),
+    (9, BetTerms::INIT_SPACE, BetTerms::INIT_SPACE),
+    (10, 64, 64),
+    (11, 32, 32),
+    (12, 1 + ResolveOutcome::INIT_SPACE, 1),
+];
+
+#[derive(Accounts)]
+pub struct MigrateBet<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    /// CHECK: may not deserialize as the current `Bet`; ownership,
+    /// discriminator and address are checked in `migrate_bet`.
+    #[account(mut, owner = crate::ID)]
+    pub bet: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateBet<'info> {
+    /// Rewrites a bet stored in an older layout as the current `Bet`,
+    /// growing the account (the authority covers the extra rent).
+    pub fn migrate_bet(&mut self) -> Result<()> {
+        let bet = self.read_bet()?;
+        require_keys_eq!(
+            Pubkey::create_program_address(
+                &[
+                    b"bet",
+                    self.vault.key().as_ref(),
+                    bet.player.as_ref(),
+                    bet.seed.to_le_bytes().as_ref(),
+                    &[bet.bump],
+                ],
+                &crate::ID,
+            )
+            .map_err(|_| DiceError::UnknownBetLayout)?,
+            self.bet.key(),
+            DiceError::UnknownBetLayout
+        );
+
+        let space = Bet::LEN;
+        let top_up = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(self.bet.lamports());
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.authority.to_account_info(),
+                        to: self.bet.to_account_info(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        self.bet.resize(space)?;
+
+        let mut data = self.bet.try_borrow_mut_data()?;
+        bet.try_serialize(&mut &mut data[..])?;
+        Ok(())
+    }
+
+    fn read_bet(&mut self) -> Result<Bet> {
+        let data = self.bet.try_borrow_data()?;
+        require!(data.starts_with(Bet::DISCRIMINATOR), DiceError::UnknownBetLayout);
+        if data.len() == Bet::LEN {
+            let bet = Bet::deserialize(&mut &data[8..])?;
+            require!(
+                bet.layout_version != BET_LAYOUT_VERSION,
+                DiceError::BetAlreadyMigrated
+            );
+            return err!(DiceError::UnknownBetLayout);
+        }
+        // Accounts are sized for their layout with every `Option` set, so the
+        // size alone says which layout a bet was written in.
+        let version = ADDED_FIELDS.iter().map(|(layout, ..)| *layout).find(|version| {
+            let missing: usize = ADDED_FIELDS
+                .iter()
+                .filter(|(layout, ..)| layout >= version)
+                .map(|(_, space, _)| space)
+                .sum();
+            data.len() == Bet::LEN - missing
+        });
+        if let Some(version) = version {
+            // Fields are only ever added just before `layout_version`, so the
+            // stored bytes are the prefix, then the fields older than the
+            // layout, then the version, then padding for the prefix's unset
+            // `Option`s.
+            let mut prefix = &data[8..];
+            BetPrefix::deserialize(&mut prefix)?;
+            let present: usize = ADDED_FIELDS
+                .iter()
+                .filter(|(layout, ..)| *layout < version)
+                .map(|(_, space, _)| space)
+                .sum();
+            let end = data.len() - prefix.len() + present;
+            require!(data.get(end) == Some(&version), DiceError::UnknownBetLayout);
+
+            // Zeroes read as a window of 0, which leaves the signature valid
+            // until the bet expires, an all-zero slot hash, a bet staked from
+            // the wallet rather than free credits, no parlay legs, `Percent`
+            // precision, no presigned signature, no client seed and no
+            // cached outcome. Older bets were always funded by the player
+            // and rolled a d100.
+            let missing: usize = ADDED_FIELDS
+                .iter()
+                .filter(|(layout, ..)| *layout >= version)
+                .map(|(.., zeroed)| zeroed)
+                .sum();
+            let mut bytes = data[8..end].to_vec();
+            bytes.resize(bytes.len() + missing, 0);
+            bytes.push(BET_LAYOUT_VERSION);
+            let mut bet = Bet::deserialize(&mut &bytes[..])?;
+            if version <= 3 {
+                bet.rent_payer = bet.player;
+            }
+            if version <= 8 {
+                bet.faces = D100_FACES;
+            }
+            if version <= 9 {
+                // Bets from before the snapshot were always judged on
+                // the live config, so they keep today's terms from now,
+                // less any volume rebate, which needs the player's stats.
+                bet.terms = self.config.bet_terms(bet.amount, 0);
+            }
+            return Ok(bet);
+        }
+        require_eq!(data.len(), BET_V0_LEN, DiceError::UnknownBetLayout);
+        let old = BetV0::deserialize(&mut &data[8..])?;
+        drop(data);
+        self.from_v0(old)
+    }
+
+    /// Maps a v0 bet onto the current layout: an Under bet on a d100,
+    /// resolved by the player's signature, with none of the optional
+    /// features and a fresh nonce and refund window. v0 never reserved
+    /// payouts, so its payout is reserved now.
+    fn from_v0(&mut self, old: BetV0) -> Result<Bet> {
+        let (winning_outcomes, total_outcomes) =
+            bet_odds(BetDirection::Under, old.roll, (0, 0), 0)?;
+        let potential_payout = payout_for(
+            old.amount,
+            winning_outcomes,
+            total_outcomes,
+            self.config.fee_bps_for(old.amount),
+            self.config.round_up_payouts,
+        )?;
+        let potential_payout = apply_fee_floor(
+            potential_payout,
+            old.amount,
+            winning_outcomes,
+            total_outcomes,
+            self.config.min_fee_lamports,
+        )?;
+        self.config.reserved_lamports = self
+            .config
+            .reserved_lamports
+            .checked_add(potential_payout)
+            .ok_or(DiceError::Overflow)?;
+        self.config.bet_count = self.config.bet_count.checked_add(1).ok_or(DiceError::Overflow)?;
+        let now = Clock::get()?.slot;
+        Ok(Bet {
+            player: old.player,
+            seed: old.seed,
+            nonce: self.config.bet_count,
+            slot: old.slot,
+            expiry_slot: now.checked_add(REFUND_DELAY_SLOTS).ok_or(DiceError::Overflow)?,
+            amount: old.amount,
+            roll: old.roll,
+            bump: old.bump,
+            mint: None,
+            direction: BetDirection::Under,
+            potential_payout,
+            dice_count: 0,
+            player_commit: [0; 32],
+            referrer: None,
+            randomness_source: RandomnessSource::Signature,
+            randomness_account: None,
+            risk_free: false,
+            roll_low: 0,
+            roll_high: 0,
+            usd_denominated: false,
+            entry_price: 0,
+            entry_price_expo: 0,
+            memo: [0; 32],
+            signature_window_slots: 0,
+            slot_hash: [0; 32],
+            rent_payer: old.player,
+            from_balance: false,
+            legs: Default::default(),
+            leg_count: 0,
+            precision: PrecisionMode::Percent,
+            roll_bps: 0,
+            free_credit: false,
+            faces: D100_FACES,
+            terms: self.config.bet_terms(old.amount, 0),
+            presigned_sig: [0; 64],
+            client_seed: [0; 32],
+            cached_outcome: None,
+            layout_version: BET_LAYOUT_VERSION,
+        })
+    }
+}
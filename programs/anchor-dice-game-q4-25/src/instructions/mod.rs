@@ -8,4 +8,153 @@ pub mod resolve_bet;
 pub use resolve_bet::*;
 
 pub mod refund_bet;
-pub use refund_bet::*;
\ No newline at end of file
+pub use refund_bet::*;
+
+pub mod place_bet_spl;
+pub use place_bet_spl::*;
+
+pub mod resolve_bet_spl;
+pub use resolve_bet_spl::*;
+
+pub mod resolve_bet_wsol;
+pub use resolve_bet_wsol::*;
+
+pub mod refund_bet_spl;
+pub use refund_bet_spl::*;
+
+pub mod initialize_config;
+pub use initialize_config::*;
+
+pub mod update_fee;
+pub use update_fee::*;
+
+pub mod withdraw_house;
+pub use withdraw_house::*;
+
+pub mod set_paused;
+pub use set_paused::*;
+
+pub mod close_game;
+pub use close_game::*;
+
+pub mod resolve_bets_batch;
+pub use resolve_bets_batch::*;
+
+pub mod request_randomness;
+pub use request_randomness::*;
+
+pub mod consume_randomness;
+pub use consume_randomness::*;
+
+pub mod withdraw_fees;
+pub use withdraw_fees::*;
+
+pub mod cancel_bet;
+pub use cancel_bet::*;
+
+pub mod get_quote;
+pub use get_quote::*;
+
+pub mod rotate_resolver;
+pub use rotate_resolver::*;
+
+pub mod fund_vault;
+pub use fund_vault::*;
+
+pub mod emergency_refund;
+pub use emergency_refund::*;
+
+pub mod place_next_bet;
+pub use place_next_bet::*;
+
+pub mod update_limits;
+pub use update_limits::*;
+
+pub mod claim_payout;
+pub use claim_payout::*;
+
+pub mod void_payout;
+pub use void_payout::*;
+
+pub mod set_bonus_roll_offset;
+pub use set_bonus_roll_offset::*;
+
+pub mod propose_authority;
+pub use propose_authority::*;
+
+pub mod accept_authority;
+pub use accept_authority::*;
+
+pub mod request_withdrawal;
+pub use request_withdrawal::*;
+
+pub mod migrate_bet;
+pub use migrate_bet::*;
+
+#[cfg(feature = "test-randomness")]
+pub mod create_legacy_bet;
+#[cfg(feature = "test-randomness")]
+pub use create_legacy_bet::*;
+
+pub mod cashout;
+pub use cashout::*;
+
+pub mod rebalance_from_treasury;
+pub use rebalance_from_treasury::*;
+
+pub mod resolve_bet_cpi;
+pub use resolve_bet_cpi::*;
+
+pub mod set_cpi_resolver;
+pub use set_cpi_resolver::*;
+
+pub mod set_resolver_set;
+pub use set_resolver_set::*;
+
+pub mod self_exclude;
+pub use self_exclude::*;
+
+pub mod grant_free_credit;
+pub use grant_free_credit::*;
+
+pub mod seed_jackpot;
+pub use seed_jackpot::*;
+
+pub mod sweep_expired;
+pub use sweep_expired::*;
+
+pub mod reconcile_reserved;
+pub use reconcile_reserved::*;
+
+pub mod close_settled_bet;
+pub use close_settled_bet::*;
+
+pub mod set_jackpot_tiers;
+pub use set_jackpot_tiers::*;
+
+pub mod get_house_edge;
+pub use get_house_edge::*;
+
+pub mod keeper_resolve_bet;
+pub use keeper_resolve_bet::*;
+
+pub mod add_to_allowlist;
+pub use add_to_allowlist::*;
+
+pub mod remove_from_allowlist;
+pub use remove_from_allowlist::*;
+
+pub mod deposit_balance;
+pub use deposit_balance::*;
+
+pub mod withdraw_balance;
+pub use withdraw_balance::*;
+
+pub mod place_bet_signed;
+pub use place_bet_signed::*;
+
+pub mod presign_bet;
+pub use presign_bet::*;
+
+pub mod resolve_bet_auto;
+pub use resolve_bet_auto::*;
@@ -1,54 +1,365 @@
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount};
+use solana_program::sysvar::slot_hashes::ID as SlotHashesId;
 
-use crate::state::Bet;
+use crate::{errors::DiceError, instructions::REFUND_DELAY_SLOTS, state::{Allowlisted, Bet, BetArgs, FreeCredits, HouseConfig, PlayerBalance, PlayerStats, PrecisionMode, RandomnessSource, SelfExclusion, UsedSeed, BET_LAYOUT_VERSION}, utils::{apply_fee_floor, basis_odds, bet_odds, face_odds, parlay_odds, payout_for, read_sol_usd_price, recent_slot_hash, D100_FACES}};
 
 #[derive(Accounts)]
 #[instruction(seed:u128)]
 pub struct PlaceBet<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
+    /// Funds the bet account and gets its rent back when the bet closes: the
+    /// player for a self-funded bet, or a house or relayer sponsoring it.
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
     ///CHECK: This is safe
     pub house: UncheckedAccount<'info>,
     #[account(
         mut,
-        seeds = [b"vault", house.key().as_ref()],
-        bump
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
     )]
     pub vault: SystemAccount<'info>,
     #[account(
-        init,
+        mut,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        init_if_needed,
         payer = player,
-        space = 8 + Bet::INIT_SPACE,
-        seeds = [b"bet", vault.key().as_ref(), seed.to_le_bytes().as_ref()],
+        space = 8 + PlayerStats::INIT_SPACE,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// `init_if_needed` so a reused seed reaches `create_bet` and fails with
+    /// `SeedAlreadyUsed` instead of the system program's generic error.
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = Bet::LEN,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), seed.to_le_bytes().as_ref()],
         bump
     )]
     pub bet: Account<'info, Bet>,
+    /// CHECK: Pyth SOL/USD `PriceUpdateV2`, validated by `read_sol_usd_price`;
+    /// only needed for USD-denominated bets.
+    pub price_update: Option<UncheckedAccount<'info>>,
+    /// CHECK: SlotHashes sysvar, read by `recent_slot_hash`
+    #[account(address = SlotHashesId)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    /// Only for allowlisted players, whose bets skip the cooldown.
+    #[account(
+        seeds = [b"allowlist", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = allowlisted.bump
+    )]
+    pub allowlisted: Option<Account<'info, Allowlisted>>,
+    /// CHECK: the player's self-exclusion PDA, read by
+    /// `SelfExclusion::is_active`; it usually doesn't exist.
+    #[account(
+        seeds = [b"self_exclusion", player.key().as_ref()],
+        bump
+    )]
+    pub self_exclusion: UncheckedAccount<'info>,
+    /// Only while `consume_seeds` is set: marks the seed as spent for good.
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = 8 + UsedSeed::INIT_SPACE,
+        seeds = [b"used_seed", vault.key().as_ref(), player.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub used_seed: Option<Account<'info, UsedSeed>>,
+    /// Stakes the bet from the player's deposited balance instead of their
+    /// wallet; its payouts are credited back there.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    /// Stakes the bet from credit the house granted the player instead.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"free_credits", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = free_credits.bump
+    )]
+    pub free_credits: Option<Account<'info, FreeCredits>>,
+    /// Only while `bet_burn_amount` is set: the house's burn mint, the
+    /// player's token account for it, and the token program; see
+    /// `BetPlacement::burn`.
+    #[account(mut)]
+    pub burn_mint: Option<Account<'info, Mint>>,
+    #[account(mut)]
+    pub player_burn_ata: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
     pub system_program: Program<'info, System>
 }
 
 impl<'info> PlaceBet<'info> {
-    pub fn create_bet(&mut self, bumps: &PlaceBetBumps, seed: u128, roll: u8, amount: u64) -> Result<()> {
+    pub fn create_bet(&mut self, bumps: &PlaceBetBumps, seed: u128, args: BetArgs) -> Result<()> {
+        self.placement().create_bet(bumps.bet, bumps.stats, seed, args)
+    }
+
+    pub fn deposit(&mut self) -> Result<()> {
+        self.placement().deposit()
+    }
+
+    fn placement(&mut self) -> BetPlacement<'_, 'info> {
+        BetPlacement {
+            player: self.player.as_ref(),
+            player_balance: self.player_balance.as_mut(),
+            free_credits: self.free_credits.as_mut(),
+            rent_payer: &self.rent_payer,
+            house: &self.house,
+            vault: &self.vault,
+            config: &mut self.config,
+            stats: &mut self.stats,
+            bet: &mut self.bet,
+            price_update: self.price_update.as_ref(),
+            slot_hashes: &self.slot_hashes,
+            allowlisted: self.allowlisted.is_some(),
+            self_exclusion: &self.self_exclusion,
+            used_seed: self.used_seed.as_mut(),
+            burn_mint: self.burn_mint.as_ref(),
+            player_burn_ata: self.player_burn_ata.as_ref(),
+            token_program: self.token_program.as_ref(),
+            system_program: &self.system_program,
+        }
+    }
+}
+
+/// Accounts shared by the explicit-seed, nonce-seeded and relayed placement
+/// paths, so all of them validate and record a bet identically.
+pub struct BetPlacement<'a, 'info> {
+    pub player: &'a AccountInfo<'info>,
+    /// Where the stake comes from when the player isn't signing: their
+    /// deposited balance. `None` takes it from the player's wallet.
+    pub player_balance: Option<&'a mut Account<'info, PlayerBalance>>,
+    /// House-granted credit the stake comes from instead, if any.
+    pub free_credits: Option<&'a mut Account<'info, FreeCredits>>,
+    pub rent_payer: &'a Signer<'info>,
+    pub house: &'a UncheckedAccount<'info>,
+    pub vault: &'a SystemAccount<'info>,
+    pub config: &'a mut Account<'info, HouseConfig>,
+    pub stats: &'a mut Account<'info, PlayerStats>,
+    pub bet: &'a mut Account<'info, Bet>,
+    pub price_update: Option<&'a UncheckedAccount<'info>>,
+    pub slot_hashes: &'a UncheckedAccount<'info>,
+    /// Whether the player has an `Allowlisted` entry, exempting them from
+    /// `bet_cooldown_slots`.
+    pub allowlisted: bool,
+    pub self_exclusion: &'a UncheckedAccount<'info>,
+    pub used_seed: Option<&'a mut Account<'info, UsedSeed>>,
+    pub burn_mint: Option<&'a Account<'info, Mint>>,
+    pub player_burn_ata: Option<&'a Account<'info, TokenAccount>>,
+    pub token_program: Option<&'a Program<'info, Token>>,
+    pub system_program: &'a Program<'info, System>,
+}
+
+impl<'info> BetPlacement<'_, 'info> {
+    pub fn create_bet(&mut self, bet_bump: u8, stats_bump: u8, seed: u128, args: BetArgs) -> Result<()> {
+        let BetArgs { roll, amount, direction, dice_count, player_commit, referrer, risk_free, roll_low, roll_high, usd_denominated, memo, legs, precision, roll_bps, faces, randomness_source, client_seed } = args;
+        require!(self.bet.player == Pubkey::default(), DiceError::SeedAlreadyUsed);
+        UsedSeed::consume(self.used_seed.as_deref_mut(), self.config.consume_seeds, self.player.key(), seed)?;
+        require!(
+            self.player.key() != self.house.key() && self.player.key() != self.vault.key(),
+            DiceError::PlayerIsHouseAccount
+        );
+        require!(!self.config.paused, DiceError::GamePaused);
+        require!(!SelfExclusion::is_active(self.self_exclusion)?, DiceError::PlayerSelfExcluded);
+        require!(
+            self.vault.lamports() >= self.config.min_vault_balance,
+            DiceError::VaultUnderCapitalized
+        );
+        let slot = Clock::get()?.slot;
+        self.stats.require_can_bet(self.config, slot, self.allowlisted)?;
+        if risk_free {
+            require!(self.config.risk_free_enabled, DiceError::RiskFreeDisabled);
+            require!(self.stats.total_bets == 0, DiceError::RiskFreeAlreadyUsed);
+        }
+        let odds = match precision {
+            PrecisionMode::Percent if faces == D100_FACES => bet_odds(direction, roll, (roll_low, roll_high), dice_count)?,
+            PrecisionMode::Percent => {
+                require!(legs.is_empty(), DiceError::InvalidFaces);
+                face_odds(direction, roll, dice_count, faces)?
+            }
+            PrecisionMode::Basis => {
+                require!(legs.is_empty() && faces == D100_FACES, DiceError::InvalidBasisBet);
+                basis_odds(direction, roll_bps, dice_count)?
+            }
+        };
+        let (winning_outcomes, total_outcomes) = parlay_odds(odds, &legs)?;
+        require!(amount >= self.config.min_bet, DiceError::MinimumBet);
+        require!(amount <= self.config.max_bet, DiceError::MaximumBet);
+        require!(amount >= self.config.min_fee_lamports, DiceError::FeeExceedsStake);
+        if let Some(free_credits) = &self.free_credits {
+            // A risk-free loss or a balance payout would hand the credit
+            // back as withdrawable lamports.
+            require!(
+                !risk_free && self.player_balance.is_none(),
+                DiceError::FreeCreditsMismatch
+            );
+            require!(
+                FreeCredits::available(&free_credits.to_account_info())? >= amount,
+                DiceError::InsufficientFreeCredits
+            );
+        } else {
+            let available = match &self.player_balance {
+                Some(player_balance) => PlayerBalance::available(&player_balance.to_account_info())?,
+                None => self.player.lamports(),
+            };
+            require!(available >= amount, DiceError::InsufficientPlayerBalance);
+        }
+        let terms = self.config.bet_terms(amount, self.stats.total_wagered);
+        let potential_payout = payout_for(amount, winning_outcomes, total_outcomes, terms.fee_bps, self.config.round_up_payouts)?;
+        let potential_payout = apply_fee_floor(potential_payout, amount, winning_outcomes, total_outcomes, self.config.min_fee_lamports)?;
+        require!(potential_payout > 0, DiceError::PayoutTooSmall);
+        require!(
+            potential_payout <= self.config.max_payout,
+            DiceError::PotentialPayoutExceedsCap
+        );
+        require!(
+            potential_payout <= self.vault.lamports(),
+            DiceError::BetExceedsVaultCapacity
+        );
+        let max_exposure = (self.vault.lamports() as u128)
+            .checked_mul(self.config.max_exposure_bps as u128)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        require!(
+            potential_payout as u128 <= max_exposure,
+            DiceError::ExceedsMaxExposure
+        );
+        self.config.reserved_lamports = self
+            .config
+            .reserved_lamports
+            .checked_add(potential_payout)
+            .ok_or(DiceError::Overflow)?;
+        let max_total_exposure = (self.vault.lamports() as u128)
+            .checked_mul(self.config.max_total_exposure_bps as u128)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        require!(
+            self.config.reserved_lamports as u128 <= max_total_exposure,
+            DiceError::GlobalExposureExceeded
+        );
+        let (entry_price, entry_price_expo) = if usd_denominated {
+            let price_update = self.price_update.ok_or(DiceError::PriceFeedRequired)?;
+            read_sol_usd_price(price_update, self.config)?
+        } else {
+            (0, 0)
+        };
+        self.stats.record_bet(self.player.key(), stats_bump, amount, slot)?;
+        self.config.bet_count = self.config.bet_count.checked_add(1).ok_or(DiceError::Overflow)?;
         self.bet.set_inner(Bet{
-            slot : Clock::get()?.slot,
+            nonce: self.config.bet_count,
+            slot,
+            expiry_slot: slot.checked_add(REFUND_DELAY_SLOTS).ok_or(DiceError::Overflow)?,
             player: self.player.key(),
             seed,
             roll,
             amount,
-            bump : bumps.bet,
+            bump : bet_bump,
+            mint: None,
+            direction,
+            potential_payout,
+            dice_count,
+            player_commit,
+            referrer,
+            randomness_source: RandomnessSource::for_placement(randomness_source, &player_commit)?,
+            randomness_account: None,
+            risk_free,
+            roll_low,
+            roll_high,
+            usd_denominated,
+            entry_price,
+            entry_price_expo,
+            memo,
+            signature_window_slots: self.config.signature_window_slots,
+            slot_hash: recent_slot_hash(self.slot_hashes)?,
+            rent_payer: self.rent_payer.key(),
+            from_balance: self.player_balance.is_some(),
+            legs: Bet::pack_legs(&legs),
+            leg_count: legs.len() as u8,
+            precision,
+            roll_bps,
+            free_credit: self.free_credits.is_some(),
+            faces,
+            terms,
+            presigned_sig: [0; 64],
+            client_seed,
+            cached_outcome: None,
+            layout_version: BET_LAYOUT_VERSION,
         });
-        Ok(())
+        self.burn()
     }
 
-    pub fn deposit(&mut self, amount: u64) -> Result<()> {
-        let accounts = Transfer {
-            from: self.player.to_account_info(),
-            to: self.vault.to_account_info()
+    /// Burns `bet_burn_amount` of the house's burn mint from the player's
+    /// token account; does nothing while the amount is 0. The player must
+    /// sign, so relayed placements can't be used while a burn is configured.
+    fn burn(&self) -> Result<()> {
+        let amount = self.config.bet_burn_amount;
+        if amount == 0 {
+            return Ok(());
+        }
+        let (Some(mint), Some(player_ata), Some(token_program)) =
+            (self.burn_mint, self.player_burn_ata, self.token_program)
+        else {
+            return err!(DiceError::BurnAccountsRequired);
         };
+        require!(self.config.burn_mint == Some(mint.key()), DiceError::BurnAccountMismatch);
+        require!(
+            player_ata.mint == mint.key() && player_ata.owner == self.player.key(),
+            DiceError::BurnAccountMismatch
+        );
+        require!(player_ata.amount >= amount, DiceError::InsufficientBurnBalance);
+        burn(
+            CpiContext::new(
+                token_program.to_account_info(),
+                Burn {
+                    mint: mint.to_account_info(),
+                    from: player_ata.to_account_info(),
+                    authority: self.player.clone(),
+                },
+            ),
+            amount,
+        )
+    }
 
-        let ctx = CpiContext::new(
-            self.system_program.to_account_info(),
-            accounts
+    /// Locks the recorded `bet.amount` rather than a separately passed
+    /// value, and checks the vault grew by exactly that much, so every payout
+    /// computed from `bet.amount` is backed by what was deposited.
+    pub fn deposit(&mut self) -> Result<()> {
+        let amount = self.bet.amount;
+        let before = self.vault.lamports();
+        if let Some(free_credits) = &self.free_credits {
+            free_credits.sub_lamports(amount)?;
+            self.vault.add_lamports(amount)?;
+        } else if let Some(player_balance) = &self.player_balance {
+            player_balance.sub_lamports(amount)?;
+            self.vault.add_lamports(amount)?;
+        } else {
+            let accounts = Transfer {
+                from: self.player.to_account_info(),
+                to: self.vault.to_account_info()
+            };
+
+            let ctx = CpiContext::new(
+                self.system_program.to_account_info(),
+                accounts
+            );
+            transfer(ctx, amount)?;
+        }
+        require!(
+            self.vault.lamports() == before.checked_add(amount).ok_or(DiceError::Overflow)?,
+            DiceError::DepositMismatch
         );
-        transfer(ctx, amount)
+        Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use solana_program::sysvar::{instructions::ID as InstructionSysvarId, slot_hashes::ID as SlotHashesId};
+
+use crate::{
+    errors::DiceError,
+    instructions::{verify_placement_signature, BetPlacement},
+    state::{Allowlisted, Bet, BetArgs, HouseConfig, PlayerBalance, PlayerStats, UsedSeed},
+};
+
+/// `place_bet` submitted by a relayer: the player only signs
+/// `PlayerBalance::placement_message` off-chain, verified through an ed25519
+/// instruction, and the stake comes out of their deposited balance. The
+/// relayer pays the transaction fee and the rent, which it gets back when the
+/// bet closes. Unavailable while the house requires a `bet_burn_amount`, which
+/// only a signing player can burn.
+#[derive(Accounts)]
+#[instruction(seed:u128)]
+pub struct PlaceBetSigned<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    /// CHECK: authorizes the bet through the signed placement message
+    pub player: UncheckedAccount<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + PlayerStats::INIT_SPACE,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = Bet::LEN,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Account<'info, PlayerBalance>,
+    /// CHECK: Pyth SOL/USD `PriceUpdateV2`, validated by `read_sol_usd_price`;
+    /// only needed for USD-denominated bets.
+    pub price_update: Option<UncheckedAccount<'info>>,
+    /// CHECK: SlotHashes sysvar, read by `recent_slot_hash`
+    #[account(address = SlotHashesId)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    /// Only for allowlisted players, whose bets skip the cooldown.
+    #[account(
+        seeds = [b"allowlist", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = allowlisted.bump
+    )]
+    pub allowlisted: Option<Account<'info, Allowlisted>>,
+    /// CHECK: the player's self-exclusion PDA, read by
+    /// `SelfExclusion::is_active`; it usually doesn't exist.
+    #[account(
+        seeds = [b"self_exclusion", player.key().as_ref()],
+        bump
+    )]
+    pub self_exclusion: UncheckedAccount<'info>,
+    /// Only while `consume_seeds` is set: marks the seed as spent for good.
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + UsedSeed::INIT_SPACE,
+        seeds = [b"used_seed", vault.key().as_ref(), player.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub used_seed: Option<Account<'info, UsedSeed>>,
+    /// CHECK: instructions sysvar
+    #[account(
+        address = InstructionSysvarId
+    )]
+    pub instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> PlaceBetSigned<'info> {
+    /// Checks the player signed exactly these arguments against the current
+    /// nonce, then spends the nonce so the signature can't place a second bet.
+    pub fn verify_placement(&mut self, seed: u128, args: &BetArgs, sig: &[u8]) -> Result<()> {
+        let message = self.player_balance.placement_message(&self.house.key(), seed, args)?;
+        verify_placement_signature(&self.instructions, &self.player.key(), &message, sig)?;
+        self.player_balance.nonce = self
+            .player_balance
+            .nonce
+            .checked_add(1)
+            .ok_or(DiceError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn create_bet(&mut self, bumps: &PlaceBetSignedBumps, seed: u128, args: BetArgs) -> Result<()> {
+        self.placement().create_bet(bumps.bet, bumps.stats, seed, args)
+    }
+
+    pub fn deposit(&mut self) -> Result<()> {
+        self.placement().deposit()
+    }
+
+    fn placement(&mut self) -> BetPlacement<'_, 'info> {
+        BetPlacement {
+            player: self.player.as_ref(),
+            player_balance: Some(&mut self.player_balance),
+            free_credits: None,
+            rent_payer: &self.relayer,
+            house: &self.house,
+            vault: &self.vault,
+            config: &mut self.config,
+            stats: &mut self.stats,
+            bet: &mut self.bet,
+            price_update: self.price_update.as_ref(),
+            slot_hashes: &self.slot_hashes,
+            allowlisted: self.allowlisted.is_some(),
+            self_exclusion: &self.self_exclusion,
+            used_seed: self.used_seed.as_mut(),
+            burn_mint: None,
+            player_burn_ata: None,
+            token_program: None,
+            system_program: &self.system_program,
+        }
+    }
+}
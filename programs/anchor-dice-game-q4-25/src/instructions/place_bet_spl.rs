@@ -0,0 +1,242 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+use solana_program::sysvar::slot_hashes::ID as SlotHashesId;
+
+use crate::{errors::DiceError, instructions::REFUND_DELAY_SLOTS, state::{Allowlisted, Bet, BetArgs, BetTerms, HouseConfig, PlayerStats, PrecisionMode, RandomnessSource, SelfExclusion, TokenReserve, UsedSeed, BET_LAYOUT_VERSION}, utils::{bet_odds, payout_for, recent_slot_hash, D100_FACES}};
+
+/// Token bets count towards the player's cooldown and open-bet cap like
+/// lamport bets, but not towards the lamport-denominated `total_wagered`.
+#[derive(Accounts)]
+#[instruction(seed:u128)]
+pub struct PlaceBetSpl<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    /// Funds the bet account and gets its rent back when the bet closes: the
+    /// player for a self-funded bet, or a house or relayer sponsoring it.
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerStats::INIT_SPACE,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = player
+    )]
+    pub player_ata: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = player,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+    /// Created by the game's first bet in this mint.
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = 8 + TokenReserve::INIT_SPACE,
+        seeds = [b"token_reserve", vault.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub token_reserve: Account<'info, TokenReserve>,
+    /// `init_if_needed` so a reused seed reaches `create_bet` and fails with
+    /// `SeedAlreadyUsed` instead of the system program's generic error.
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = Bet::LEN,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: SlotHashes sysvar, read by `recent_slot_hash`
+    #[account(address = SlotHashesId)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    /// Only for allowlisted players, whose bets skip the cooldown.
+    #[account(
+        seeds = [b"allowlist", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = allowlisted.bump
+    )]
+    pub allowlisted: Option<Account<'info, Allowlisted>>,
+    /// CHECK: the player's self-exclusion PDA, read by
+    /// `SelfExclusion::is_active`; it usually doesn't exist.
+    #[account(
+        seeds = [b"self_exclusion", player.key().as_ref()],
+        bump
+    )]
+    pub self_exclusion: UncheckedAccount<'info>,
+    /// Only while `consume_seeds` is set: marks the seed as spent for good.
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = 8 + UsedSeed::INIT_SPACE,
+        seeds = [b"used_seed", vault.key().as_ref(), player.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub used_seed: Option<Account<'info, UsedSeed>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> PlaceBetSpl<'info> {
+    pub fn create_bet(&mut self, bumps: &PlaceBetSplBumps, seed: u128, args: BetArgs) -> Result<()> {
+        let BetArgs { roll, amount, direction, dice_count, player_commit, referrer, risk_free, roll_low, roll_high, usd_denominated, memo, legs, precision, roll_bps: _, faces, randomness_source, client_seed } = args;
+        require!(self.bet.player == Pubkey::default(), DiceError::SeedAlreadyUsed);
+        UsedSeed::consume(self.used_seed.as_mut(), self.config.consume_seeds, self.player.key(), seed)?;
+        require!(
+            self.player.key() != self.house.key() && self.player.key() != self.vault.key(),
+            DiceError::PlayerIsHouseAccount
+        );
+        require!(!self.config.paused, DiceError::GamePaused);
+        require!(!SelfExclusion::is_active(&self.self_exclusion)?, DiceError::PlayerSelfExcluded);
+        let slot = Clock::get()?.slot;
+        self.stats.require_can_bet(&self.config, slot, self.allowlisted.is_some())?;
+        require!(!risk_free, DiceError::RiskFreeDisabled);
+        require!(!usd_denominated, DiceError::UsdDenominationUnsupported);
+        require!(legs.is_empty(), DiceError::InvalidParlayLeg);
+        require!(precision == PrecisionMode::Percent, DiceError::InvalidBasisBet);
+        require!(faces == D100_FACES, DiceError::InvalidFaces);
+        require!(randomness_source == RandomnessSource::Signature, DiceError::RandomnessSourceMismatch);
+        let (winning_outcomes, total_outcomes) = bet_odds(direction, roll, (roll_low, roll_high), dice_count)?;
+        require!(amount >= self.config.min_bet, DiceError::MinimumBet);
+        require!(amount <= self.config.max_bet, DiceError::MaximumBet);
+        require!(self.player_ata.amount >= amount, DiceError::InsufficientPlayerBalance);
+        let potential_payout = payout_for(amount, winning_outcomes, total_outcomes, self.config.fee_bps_for(amount), self.config.round_up_payouts)?;
+        require!(potential_payout > 0, DiceError::PayoutTooSmall);
+        require!(
+            potential_payout <= self.config.max_payout,
+            DiceError::PotentialPayoutExceedsCap
+        );
+        self.reserve(bumps.token_reserve, potential_payout)?;
+        self.stats.record_bet(self.player.key(), bumps.stats, 0, slot)?;
+        self.config.bet_count = self.config.bet_count.checked_add(1).ok_or(DiceError::Overflow)?;
+        self.config.open_token_bets = self
+            .config
+            .open_token_bets
+            .checked_add(1)
+            .ok_or(DiceError::Overflow)?;
+        self.bet.set_inner(Bet{
+            nonce: self.config.bet_count,
+            slot,
+            expiry_slot: slot.checked_add(REFUND_DELAY_SLOTS).ok_or(DiceError::Overflow)?,
+            player: self.player.key(),
+            seed,
+            roll,
+            amount,
+            bump : bumps.bet,
+            mint: Some(self.mint.key()),
+            direction,
+            potential_payout,
+            dice_count,
+            player_commit,
+            referrer,
+            randomness_source: RandomnessSource::Signature,
+            randomness_account: None,
+            risk_free: false,
+            roll_low,
+            roll_high,
+            usd_denominated: false,
+            entry_price: 0,
+            entry_price_expo: 0,
+            memo,
+            signature_window_slots: self.config.signature_window_slots,
+            slot_hash: recent_slot_hash(&self.slot_hashes)?,
+            rent_payer: self.rent_payer.key(),
+            from_balance: false,
+            legs: Default::default(),
+            leg_count: 0,
+            precision: PrecisionMode::Percent,
+            roll_bps: 0,
+            free_credit: false,
+            faces: D100_FACES,
+            // The fee floor is lamport-denominated, so token bets skip it.
+            terms: BetTerms { min_fee_lamports: 0, ..self.config.bet_terms(amount, 0) },
+            presigned_sig: [0; 64],
+            client_seed,
+            cached_outcome: None,
+            layout_version: BET_LAYOUT_VERSION,
+        });
+        Ok(())
+    }
+
+    /// The lamport vault's capacity and exposure checks from `create_bet` in
+    /// `place_bet`, against the vault's token account and this mint's
+    /// `TokenReserve`.
+    fn reserve(&mut self, reserve_bump: u8, potential_payout: u64) -> Result<()> {
+        let vault_amount = self.vault_ata.amount;
+        require!(
+            potential_payout <= vault_amount,
+            DiceError::BetExceedsVaultCapacity
+        );
+        let max_exposure = (vault_amount as u128)
+            .checked_mul(self.config.max_exposure_bps as u128)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        require!(
+            potential_payout as u128 <= max_exposure,
+            DiceError::ExceedsMaxExposure
+        );
+        if self.token_reserve.mint == Pubkey::default() {
+            self.token_reserve.mint = self.mint.key();
+            self.token_reserve.bump = reserve_bump;
+        }
+        self.token_reserve.reserve(potential_payout)?;
+        let max_total_exposure = (vault_amount as u128)
+            .checked_mul(self.config.max_total_exposure_bps as u128)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        require!(
+            self.token_reserve.reserved as u128 <= max_total_exposure,
+            DiceError::GlobalExposureExceeded
+        );
+        Ok(())
+    }
+
+    /// Like `BetPlacement::deposit`: transfers `bet.amount` and checks the
+    /// vault's token account received exactly that.
+    pub fn deposit(&mut self) -> Result<()> {
+        let amount = self.bet.amount;
+        let before = self.vault_ata.amount;
+        let accounts = Transfer {
+            from: self.player_ata.to_account_info(),
+            to: self.vault_ata.to_account_info(),
+            authority: self.player.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            accounts
+        );
+        transfer(ctx, amount)?;
+        self.vault_ata.reload()?;
+        require!(
+            self.vault_ata.amount == before.checked_add(amount).ok_or(DiceError::Overflow)?,
+            DiceError::DepositMismatch
+        );
+        Ok(())
+    }
+}
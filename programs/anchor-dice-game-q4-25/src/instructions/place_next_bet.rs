@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use solana_program::sysvar::slot_hashes::ID as SlotHashesId;
+
+use crate::{
+    instructions::BetPlacement,
+    state::{Allowlisted, Bet, BetArgs, HouseConfig, PlayerBalance, PlayerStats, UsedSeed},
+};
+
+/// Like `PlaceBet`, but the bet is seeded by the player's on-chain
+/// `bet_nonce`, so clients never have to pick a unique seed and the next
+/// bet's address is always derivable from `PlayerStats`.
+#[derive(Accounts)]
+pub struct PlaceNextBet<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    /// Funds the bet account and gets its rent back when the bet closes: the
+    /// player for a self-funded bet, or a house or relayer sponsoring it.
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerStats::INIT_SPACE,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// `init_if_needed` so a nonce already taken by an explicit-seed bet
+    /// fails with `SeedAlreadyUsed`.
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = Bet::LEN,
+        seeds = [
+            b"bet",
+            vault.key().as_ref(),
+            player.key().as_ref(),
+            (stats.bet_nonce as u128).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: Pyth SOL/USD `PriceUpdateV2`, validated by `read_sol_usd_price`;
+    /// only needed for USD-denominated bets.
+    pub price_update: Option<UncheckedAccount<'info>>,
+    /// CHECK: SlotHashes sysvar, read by `recent_slot_hash`
+    #[account(address = SlotHashesId)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    /// Only for allowlisted players, whose bets skip the cooldown.
+    #[account(
+        seeds = [b"allowlist", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = allowlisted.bump
+    )]
+    pub allowlisted: Option<Account<'info, Allowlisted>>,
+    /// CHECK: the player's self-exclusion PDA, read by
+    /// `SelfExclusion::is_active`; it usually doesn't exist.
+    #[account(
+        seeds = [b"self_exclusion", player.key().as_ref()],
+        bump
+    )]
+    pub self_exclusion: UncheckedAccount<'info>,
+    /// Only while `consume_seeds` is set: marks the seed as spent for good.
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = 8 + UsedSeed::INIT_SPACE,
+        seeds = [b"used_seed", vault.key().as_ref(), player.key().as_ref(), (stats.bet_nonce as u128).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub used_seed: Option<Account<'info, UsedSeed>>,
+    /// Stakes the bet from the player's deposited balance instead of their
+    /// wallet; its payouts are credited back there.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    /// Only while `bet_burn_amount` is set: the house's burn mint, the
+    /// player's token account for it, and the token program; see
+    /// `BetPlacement::burn`.
+    #[account(mut)]
+    pub burn_mint: Option<Account<'info, Mint>>,
+    #[account(mut)]
+    pub player_burn_ata: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> PlaceNextBet<'info> {
+    pub fn create_bet(&mut self, bumps: &PlaceNextBetBumps, args: BetArgs) -> Result<()> {
+        let seed = self.stats.bet_nonce as u128;
+        self.placement().create_bet(bumps.bet, bumps.stats, seed, args)
+    }
+
+    pub fn deposit(&mut self) -> Result<()> {
+        self.placement().deposit()
+    }
+
+    fn placement(&mut self) -> BetPlacement<'_, 'info> {
+        BetPlacement {
+            player: self.player.as_ref(),
+            player_balance: self.player_balance.as_mut(),
+            free_credits: None,
+            rent_payer: &self.rent_payer,
+            house: &self.house,
+            vault: &self.vault,
+            config: &mut self.config,
+            stats: &mut self.stats,
+            bet: &mut self.bet,
+            price_update: self.price_update.as_ref(),
+            slot_hashes: &self.slot_hashes,
+            allowlisted: self.allowlisted.is_some(),
+            self_exclusion: &self.self_exclusion,
+            used_seed: self.used_seed.as_mut(),
+            burn_mint: self.burn_mint.as_ref(),
+            player_burn_ata: self.player_burn_ata.as_ref(),
+            token_program: self.token_program.as_ref(),
+            system_program: &self.system_program,
+        }
+    }
+}
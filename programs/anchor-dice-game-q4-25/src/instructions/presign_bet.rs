@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use solana_program::sysvar::instructions::ID as InstructionSysvarId;
+
+use crate::{
+    errors::DiceError,
+    instructions::verify_ed25519_signature,
+    state::{Bet, HouseConfig, RandomnessSource},
+};
+
+/// Stores the player's signature over their bet so `resolve_bet_auto` can
+/// settle it later without anyone having to submit it again. The signature
+/// is checked here exactly as `resolve_bet` would check it.
+#[derive(Accounts)]
+pub struct PresignBet<'info> {
+    pub player: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: instructions sysvar
+    #[account(
+        address = InstructionSysvarId
+    )]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+impl<'info> PresignBet<'info> {
+    pub fn presign_bet(&mut self, sig: &[u8]) -> Result<()> {
+        require!(self.config.auto_resolve, DiceError::AutoResolveDisabled);
+        require!(
+            self.bet.randomness_source == RandomnessSource::Signature,
+            DiceError::RandomnessSourceMismatch
+        );
+        require!(self.bet.presigned_sig == [0; 64], DiceError::BetAlreadyPresigned);
+        require_eq!(sig.len(), 64, DiceError::Ed25519SignatureLength);
+        verify_ed25519_signature(&self.instructions, &self.player.key(), &self.bet, sig)?;
+        self.bet.presigned_sig.copy_from_slice(sig);
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::state::HouseConfig;
+
+/// First half of an authority transfer: the new key gains nothing until it
+/// signs `accept_authority`, so a mistyped key can't lock the house out.
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> ProposeAuthority<'info> {
+    pub fn propose_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        self.config.pending_authority = Some(new_authority);
+        Ok(())
+    }
+}
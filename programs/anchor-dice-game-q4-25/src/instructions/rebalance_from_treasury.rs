@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, events::TreasuryRebalanced, state::{HouseConfig, Treasury}};
+
+#[derive(Accounts)]
+pub struct RebalanceFromTreasury<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        seeds = [b"treasury", house.key().as_ref(), &[config.game_id]],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+impl<'info> RebalanceFromTreasury<'info> {
+    /// Tops the vault back up to `rebalance_threshold` from the treasury's
+    /// balance above rent, moving less if the treasury can't cover it all.
+    pub fn rebalance_from_treasury(&mut self) -> Result<()> {
+        let shortfall = self
+            .config
+            .rebalance_threshold
+            .saturating_sub(self.vault.lamports());
+        require!(shortfall > 0, DiceError::VaultAboveRebalanceThreshold);
+
+        let treasury = self.treasury.to_account_info();
+        let rent = Rent::get()?.minimum_balance(treasury.data_len());
+        let amount = treasury.lamports().saturating_sub(rent).min(shortfall);
+        require!(amount > 0, DiceError::InsufficientTreasuryBalance);
+
+        treasury.sub_lamports(amount)?;
+        self.vault.add_lamports(amount)?;
+
+        emit!(TreasuryRebalanced {
+            amount,
+            vault_balance: self.vault.lamports(),
+        });
+        Ok(())
+    }
+}
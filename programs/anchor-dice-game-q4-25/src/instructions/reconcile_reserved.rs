@@ -0,0 +1,111 @@
+use anchor_lang::{error::ErrorCode, prelude::*, Discriminator};
+
+use crate::{
+    errors::DiceError,
+    events::ReservedReconciled,
+    state::{Bet, HouseConfig, PendingPayout},
+};
+
+#[derive(Accounts)]
+pub struct ReconcileReserved<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> ReconcileReserved<'info> {
+    /// Resets `reserved_lamports` after a manual intervention left it out of
+    /// step with the open bets: to `reserved` if given, otherwise to the sum
+    /// of the potential payouts of the lamport bets and the amounts of the
+    /// pending payouts passed as remaining accounts, in ascending address
+    /// order so none is counted twice. Nothing
+    /// here can tell whether the list is complete, which is why it's only
+    /// available while the game is paused.
+    pub fn reconcile_reserved(&mut self, reserved: Option<u64>, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(self.config.paused, DiceError::GameNotPaused);
+        let after = match reserved {
+            Some(reserved) => {
+                require!(remaining_accounts.is_empty(), DiceError::BatchAccountsMismatch);
+                reserved
+            }
+            None => self.sum_open_bets(remaining_accounts)?,
+        };
+        let before = self.config.reserved_lamports;
+        self.config.reserved_lamports = after;
+
+        emit!(ReservedReconciled { before, after });
+        Ok(())
+    }
+
+    fn sum_open_bets(&self, accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        let mut total: u64 = 0;
+        let mut previous: Option<Pubkey> = None;
+        for info in accounts {
+            require!(
+                previous.is_none_or(|previous| previous < info.key()),
+                DiceError::BetsNotAscending
+            );
+            previous = Some(info.key());
+
+            // An escrowed large win stays reserved until it's claimed or
+            // voided, after its bet is closed.
+            let reserved = if info.try_borrow_data()?.starts_with(PendingPayout::DISCRIMINATOR) {
+                self.pending_payout_amount(info)?
+            } else {
+                self.open_bet_payout(info)?
+            };
+            total = total.checked_add(reserved).ok_or(DiceError::Overflow)?;
+        }
+        Ok(total)
+    }
+
+    fn open_bet_payout(&self, bet_info: &'info AccountInfo<'info>) -> Result<u64> {
+        let bet = Account::<Bet>::try_from(bet_info)?;
+        let bet_address = Pubkey::create_program_address(
+            &[
+                b"bet",
+                self.vault.key().as_ref(),
+                bet.player.as_ref(),
+                bet.seed.to_le_bytes().as_ref(),
+                &[bet.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(bet_address, bet_info.key(), ErrorCode::ConstraintSeeds);
+        // Token bets are never reserved against the lamport vault, and a
+        // settled bet's reservation is already released.
+        require!(bet.mint.is_none(), DiceError::MintMismatch);
+        require!(bet.cached_outcome.is_none(), DiceError::BetAlreadySettled);
+        Ok(bet.potential_payout)
+    }
+
+    fn pending_payout_amount(&self, pending_info: &'info AccountInfo<'info>) -> Result<u64> {
+        let pending = Account::<PendingPayout>::try_from(pending_info)?;
+        let pending_address = Pubkey::create_program_address(
+            &[
+                b"pending_payout",
+                self.vault.key().as_ref(),
+                pending.player.as_ref(),
+                pending.seed.to_le_bytes().as_ref(),
+                &[pending.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(pending_address, pending_info.key(), ErrorCode::ConstraintSeeds);
+        Ok(pending.amount)
+    }
+}
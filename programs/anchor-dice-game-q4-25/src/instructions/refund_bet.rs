@@ -3,7 +3,10 @@ use anchor_lang::{
     system_program::{transfer, Transfer},
 };
 
-use crate::{errors::DiceError, state::Bet};
+use crate::{errors::DiceError, state::{Bet, FreeCredits, HouseConfig, PlayerBalance, PlayerStats}, utils::require_vault_rent_exempt};
+
+#[constant]
+pub const REFUND_DELAY_SLOTS: u64 = 1000;
 
 #[derive(Accounts)]
 pub struct RefundBet<'info> {
@@ -13,32 +16,82 @@ pub struct RefundBet<'info> {
     pub house: UncheckedAccount<'info>,
     #[account(
         mut,
-        seeds = [b"vault", house.key().as_ref()],
-        bump
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
     )]
     pub vault: SystemAccount<'info>,
     #[account(
         mut,
-        close = player,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        close = rent_payer,
          has_one = player,
-        seeds = [b"bet", vault.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
         bump = bet.bump
     )]
     pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// The player's free credits, which a free-credit bet's stake is returned
+    /// to instead of the wallet.
+    #[account(
+        mut,
+        seeds = [b"free_credits", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = free_credits.bump
+    )]
+    pub free_credits: Option<Account<'info, FreeCredits>>,
+    /// The player's `PlayerBalance`, which a bet staked from it is returned
+    /// to instead of the wallet.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> RefundBet<'info> {
-    pub fn refund_bet(&mut self, bumps: &RefundBetBumps) -> Result<()> {
+    pub fn refund_bet(&mut self) -> Result<()> {
+        require!(self.bet.cached_outcome.is_none(), DiceError::BetAlreadySettled);
+        require!(self.bet.mint.is_none(), DiceError::MintMismatch);
+        require!(
+            self.bet.free_credit == self.free_credits.is_some(),
+            DiceError::FreeCreditsMismatch
+        );
+        require!(
+            self.bet.from_balance == self.player_balance.is_some(),
+            DiceError::PlayerBalanceMismatch
+        );
         let slot = Clock::get()?.slot;
-        require!((self.bet.slot - slot) > 1000, DiceError::TimeoutNotReached);
+        require!(slot > self.bet.expiry_slot, DiceError::RefundNotYetAvailable);
+        self.config.release_reservation(self.bet.potential_payout)?;
+        self.stats.release_open_bet();
+        require_vault_rent_exempt(self.vault.lamports(), self.bet.amount)?;
+        let to = match (&self.free_credits, &self.player_balance) {
+            (Some(free_credits), _) => free_credits.to_account_info(),
+            (None, Some(player_balance)) => player_balance.to_account_info(),
+            (None, None) => self.player.to_account_info(),
+        };
         let accounts = Transfer {
             from: self.vault.to_account_info(),
-            to: self.player.to_account_info(),
+            to,
         };
 
         let signer_seeds: &[&[&[u8]]] =
-            &[&[b"vault", &self.house.key().to_bytes(), &[bumps.vault]]];
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
 
         let ctx = CpiContext::new_with_signer(
             self.system_program.to_account_info(),
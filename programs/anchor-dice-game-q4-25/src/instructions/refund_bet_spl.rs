@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::{
+    errors::DiceError,
+    instructions::CANCEL_WINDOW_SLOTS,
+    state::{Bet, HouseConfig, PlayerStats, TokenReserve},
+};
+
+/// `cancel_bet` and `refund_bet` for bets placed with `place_bet_spl`: the
+/// stake goes back from the vault's token account to the player's, which
+/// is recreated at the player's expense if they've closed it since placing.
+#[derive(Accounts)]
+pub struct RefundBetSpl<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = player,
+        associated_token::mint = mint,
+        associated_token::authority = player
+    )]
+    pub player_ata: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"token_reserve", vault.key().as_ref(), mint.key().as_ref()],
+        bump = token_reserve.bump
+    )]
+    pub token_reserve: Account<'info, TokenReserve>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundBetSpl<'info> {
+    pub fn cancel_bet(&mut self) -> Result<()> {
+        self.require_open_token_bet()?;
+        let slot = Clock::get()?.slot;
+        require!(
+            slot < self.config.reveal_slot(self.bet.slot)?,
+            DiceError::OutcomeRevealed
+        );
+        let cancel_deadline = self
+            .bet
+            .slot
+            .checked_add(CANCEL_WINDOW_SLOTS)
+            .ok_or(DiceError::Overflow)?;
+        require!(slot <= cancel_deadline, DiceError::CancelWindowClosed);
+        // There's no token fee vault, so the fee always stays in the vault's
+        // token account, as a reinvested one does in `cancel_bet`.
+        let fee = self
+            .bet
+            .amount
+            .checked_mul(self.config.cancel_fee_bps as u64)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        let refund = self
+            .bet
+            .amount
+            .checked_sub(fee)
+            .ok_or(DiceError::CancelFeeExceedsStake)?;
+        self.token_reserve.release(self.bet.potential_payout)?;
+        self.stats.release_open_bet();
+        self.config.release_token_bet();
+        self.transfer_from_vault(refund)
+    }
+
+    pub fn refund_bet(&mut self) -> Result<()> {
+        self.require_open_token_bet()?;
+        require!(
+            Clock::get()?.slot > self.bet.expiry_slot,
+            DiceError::RefundNotYetAvailable
+        );
+        self.token_reserve.release(self.bet.potential_payout)?;
+        self.stats.release_open_bet();
+        self.config.release_token_bet();
+        self.transfer_from_vault(self.bet.amount)
+    }
+
+    fn require_open_token_bet(&self) -> Result<()> {
+        require!(self.bet.cached_outcome.is_none(), DiceError::BetAlreadySettled);
+        require_keys_eq!(
+            self.bet.mint.ok_or(DiceError::MintMismatch)?,
+            self.mint.key(),
+            DiceError::MintMismatch
+        );
+        Ok(())
+    }
+
+    fn transfer_from_vault(&self, amount: u64) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+
+        let cpi_context = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.vault_ata.to_account_info(),
+                to: self.player_ata.to_account_info(),
+                authority: self.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        transfer(cpi_context, amount)
+    }
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Allowlisted, HouseConfig};
+
+#[derive(Accounts)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"allowlist", house.key().as_ref(), &[config.game_id], allowlisted.player.as_ref()],
+        bump = allowlisted.bump
+    )]
+    pub allowlisted: Account<'info, Allowlisted>,
+}
+
+impl<'info> RemoveFromAllowlist<'info> {
+    /// Closing the account is the whole removal; its rent returns to the
+    /// authority.
+    pub fn remove_from_allowlist(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use switchboard_on_demand::accounts::RandomnessAccountData;
+
+use crate::{errors::DiceError, state::{Bet, HouseConfig, RandomnessSource}};
+
+#[derive(Accounts)]
+pub struct RequestRandomness<'info> {
+    pub player: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: parsed as Switchboard randomness account data
+    pub randomness_account_data: UncheckedAccount<'info>,
+}
+
+impl<'info> RequestRandomness<'info> {
+    /// Binds a freshly committed Switchboard randomness account to the bet.
+    /// The commitment must be from the previous slot and still unrevealed,
+    /// so neither side can know the value when the bet is tied to it.
+    pub fn request_randomness(&mut self) -> Result<()> {
+        require!(
+            self.bet.randomness_source == RandomnessSource::Signature,
+            DiceError::RandomnessSourceMismatch
+        );
+        require!(self.bet.mint.is_none(), DiceError::MintMismatch);
+
+        let clock = Clock::get()?;
+        let randomness_data =
+            RandomnessAccountData::parse(self.randomness_account_data.data.borrow())
+                .map_err(|_| DiceError::InvalidRandomnessAccount)?;
+        require_eq!(
+            randomness_data.seed_slot,
+            clock.slot.saturating_sub(1),
+            DiceError::RandomnessExpired
+        );
+        require!(
+            randomness_data.get_value(&clock).is_err(),
+            DiceError::RandomnessAlreadyRevealed
+        );
+
+        self.bet.randomness_source = RandomnessSource::Switchboard;
+        self.bet.randomness_account = Some(self.randomness_account_data.key());
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, state::HouseConfig};
+
+/// First half of a timelocked withdrawal: records the amount and when it
+/// unlocks. Nothing leaves the vault until `claim_withdrawal`.
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> RequestWithdrawal<'info> {
+    /// Replaces any outstanding request and restarts the timelock; an
+    /// `amount` of 0 cancels it.
+    pub fn request_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.config.pending_withdrawal = amount;
+        self.config.withdrawal_unlock_slot = Clock::get()?
+            .slot
+            .checked_add(self.config.withdrawal_timelock_slots)
+            .ok_or(DiceError::Overflow)?;
+        Ok(())
+    }
+}
@@ -1,40 +1,145 @@
-use crate::{errors::DiceError, Bet};
+use crate::{
+    errors::DiceError,
+    events::{BetResolved, JackpotTierWon, JackpotWon, KeeperPaid, OutcomeProof},
+    randomness::{Ed25519Sig, RandomnessBackend, SlotHash},
+    utils::{belongs_to_house, outcome_hash, read_sol_usd_price, require_vault_rent_exempt, usd_pegged_payout, D100_FACES},
+    Bet, FeeVault, HouseConfig, HouseLedger, Jackpot, Jackpots, PendingPayout, PlayerBalance, PlayerStats, PrecisionMode, RandomnessSource, RecentOutcome, RecentOutcomes, Rolls, Treasury, BET_MESSAGE_VERSION, MAX_RESOLVERS, WINDOW_MESSAGE_TAG,
+};
 use anchor_instruction_sysvar::Ed25519InstructionSignatures;
+use anchor_spl::token::{mint_to, Mint, MintTo, Token, TokenAccount};
 use anchor_lang::{
     prelude::*,
     system_program::{transfer, Transfer},
 };
 use solana_program::{
     ed25519_program,
-    sysvar::instructions::{load_instruction_at_checked, ID as InstructionSysvarId},
-    hash::hash
+    hash::hash,
+    sysvar::{
+        instructions::{load_current_index_checked, load_instruction_at_checked, ID as InstructionSysvarId},
+        slot_hashes::ID as SlotHashesId,
+    },
 };
 
 #[constant]
-const HOUSE_FEE: u64 = 150; //basis
+pub const JACKPOT_ROLL: u8 = 1;
 
 #[derive(Accounts)]
 #[instruction()]
 pub struct ResolveBet<'info> {
     #[account(mut)]
-    pub house: Signer<'info>,
+    pub resolver: Signer<'info>,
+    ///CHECK: only identifies the house, which `config`, `vault` and `bet`
+    /// must all belong to
+    #[account(
+        constraint = belongs_to_house(&house.key(), &config, &vault.key(), &bet, &bet.key()) @ DiceError::AccountHouseMismatch
+    )]
+    pub house: UncheckedAccount<'info>,
     #[account(
         mut,
-        seeds = [b"vault", house.key().as_ref()],
-        bump
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
     )]
     pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = resolver,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        seeds = [b"jackpot", house.key().as_ref(), &[config.game_id]],
+        bump = jackpot.bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+    #[account(
+        mut,
+        seeds = [b"jackpots", house.key().as_ref(), &[config.game_id]],
+        bump = jackpots.bump
+    )]
+    pub jackpots: Account<'info, Jackpots>,
+    #[account(
+        mut,
+        seeds = [b"fee_vault", house.key().as_ref(), &[config.game_id]],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    #[account(
+        mut,
+        seeds = [b"treasury", house.key().as_ref(), &[config.game_id]],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(
+        mut,
+        seeds = [b"recent_outcomes", house.key().as_ref(), &[config.game_id]],
+        bump = recent_outcomes.bump
+    )]
+    pub recent_outcomes: Account<'info, RecentOutcomes>,
+    #[account(
+        mut,
+        seeds = [b"ledger", house.key().as_ref(), &[config.game_id]],
+        bump = ledger.bump
+    )]
+    pub ledger: Account<'info, HouseLedger>,
     /// CHECK: the player
     #[account(mut)]
     pub player: UncheckedAccount<'info>,
+    /// Closed by a successful resolve, so a retry of one that already landed
+    /// fails here with `AccountNotInitialized` before anything is checked or
+    /// paid; clients can treat that error on `bet` as "already settled". A
+    /// bet later re-placed under the same seed has a new nonce, so the old
+    /// signature fails with `Ed25519Message` instead.
     #[account(
         mut,
-        close = player,
+        close = rent_payer,
         has_one = player,
-        seeds = [b"bet", vault.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
         bump = bet.bump
     )]
     pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// Only for bets staked from a `PlayerBalance`, which their winnings are
+    /// credited back to.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    /// Only for a win at or above `large_win_threshold`: receives the escrowed
+    /// payout, with the resolver paying its rent.
+    #[account(
+        init,
+        payer = resolver,
+        space = 8 + PendingPayout::INIT_SPACE,
+        seeds = [b"pending_payout", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pending_payout: Option<Account<'info, PendingPayout>>,
+    /// CHECK: Pyth SOL/USD `PriceUpdateV2`, validated by `read_sol_usd_price`;
+    /// only needed for USD-denominated bets.
+    pub price_update: Option<UncheckedAccount<'info>>,
+    /// Only with `rewards_enabled`: the house's reward mint, the player's
+    /// token account for it, and the token program; see `mint_rewards`.
+    #[account(mut)]
+    pub reward_mint: Option<Account<'info, Mint>>,
+    #[account(mut)]
+    pub player_reward_ata: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+    /// CHECK: SlotHashes sysvar, read by `Bet::outcome_slot_hash`.
+    #[account(address = SlotHashesId)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
     /// CHECK: instructions sysvar
     #[account(
         address = InstructionSysvarId
@@ -45,80 +150,797 @@ pub struct ResolveBet<'info> {
 
 impl<'info> ResolveBet<'info> {
     pub fn verify_ed25519_signature(&self, sig: &[u8]) -> Result<()> {
-        let ed25519_ix = load_instruction_at_checked(0, &self.instructions)?;
-        require_eq!(
-            ed25519_ix.program_id,
-            ed25519_program::ID,
-            DiceError::Ed25519Program
-        );
-        require_eq!(ed25519_ix.accounts.len(), 0, DiceError::Ed25519Accounts);
-
-        let signatures = Ed25519InstructionSignatures::unpack(&ed25519_ix.data)
-            .map_err(|_| DiceError::Ed25519DataLength)?
-            .0;
-        require_eq!(signatures.len(), 1, DiceError::Ed25519Signature);
-
-        let signature = &signatures[0];
-        require!(signature.is_verifiable, DiceError::Ed25519Header);
-        require_keys_eq!(
-            signature.public_key.ok_or(DiceError::Ed25519Pubkey)?,
-            self.player.key(),
-            DiceError::Ed25519Pubkey
-        );
+        verify_ed25519_signature(&self.instructions, &self.player.key(), &self.bet, sig)
+    }
+
+    pub fn verify_resolver_quorum(&self) -> Result<()> {
+        verify_resolver_quorum(&self.instructions, &self.config, &self.bet)
+    }
+
+    pub fn verify_resolution_window(&self) -> Result<()> {
+        verify_resolution_window(&self.instructions, &self.config, &self.bet)
+    }
+
+    pub fn verify_commit(&self, reveal: &[u8; 32]) -> Result<()> {
         require!(
-            &signature
-                .signature
-                .ok_or(DiceError::Ed25519Signature)?
-                .eq(sig),
-            DiceError::Ed25519Signature
+            hash(reveal).to_bytes() == self.bet.player_commit,
+            DiceError::CommitMismatch
         );
+        Ok(())
+    }
+
+    pub fn resolve_bet(
+        &mut self,
+        sig: &[u8],
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<ResolveOutcome> {
+        require_eq!(sig.len(), 64, DiceError::Ed25519SignatureLength);
         require!(
-            &signature
-                .message
-                .as_ref()
-                .ok_or(DiceError::Ed25519Message)?
-                .eq(&self.bet.to_slice()),
-            DiceError::Ed25519Message
+            self.bet.randomness_source == RandomnessSource::Signature,
+            DiceError::RandomnessSourceMismatch
         );
+        let slot_hash = self.bet.outcome_slot_hash(&self.config, self.slot_hashes.as_deref())?;
+        let rolls = self.bet.rolls(sig, &slot_hash);
+        self.emit_proof(sig, &slot_hash, rolls.roll);
+        self.settle(rolls, remaining_accounts)
+    }
 
-        Ok(())
+    /// Commit-reveal resolution: the outcome is derived from the player's
+    /// revealed secret followed by the house seed, so neither side alone
+    /// controls it.
+    pub fn reveal_and_resolve(
+        &mut self,
+        reveal: [u8; 32],
+        house_seed: [u8; 32],
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<ResolveOutcome> {
+        require!(
+            self.bet.randomness_source == RandomnessSource::CommitReveal,
+            DiceError::RandomnessSourceMismatch
+        );
+        let input = [reveal, house_seed].concat();
+        let slot_hash = self.bet.outcome_slot_hash(&self.config, self.slot_hashes.as_deref())?;
+        let rolls = self.bet.rolls(&input, &slot_hash);
+        self.emit_proof(&input, &slot_hash, rolls.roll);
+        self.settle(rolls, remaining_accounts)
     }
 
-    pub fn resolve_bet(&self, sig: &[u8], bumps: &ResolveBetBumps) -> Result<()> {
-        let hash = hash(sig).to_bytes();
+    /// Resolves a bet through the backend for its `RandomnessSource`; `sig`
+    /// is only read for `Signature` bets. Commit-reveal and Switchboard bets
+    /// bring inputs of their own to `reveal_and_resolve` and
+    /// `consume_randomness`.
+    pub fn resolve_bet_from_source(
+        &mut self,
+        sig: &[u8],
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<ResolveOutcome> {
+        let slot_hashes = self.slot_hashes.as_deref();
+        let entropy = match self.bet.randomness_source {
+            RandomnessSource::Signature => Ed25519Sig { instructions: &self.instructions, slot_hashes, sig }
+                .entropy(&self.bet, &self.config)?,
+            RandomnessSource::SlotHash => SlotHash { bet_key: self.bet.key(), slot_hashes }
+                .entropy(&self.bet, &self.config)?,
+            RandomnessSource::Beacon => return err!(DiceError::RandomnessBackendUnsupported),
+            RandomnessSource::CommitReveal | RandomnessSource::Switchboard => {
+                return err!(DiceError::RandomnessSourceMismatch)
+            }
+        };
+        let rolls = self.bet.rolls(&entropy.input, &entropy.slot_hash);
+        self.emit_proof(&entropy.input, &entropy.slot_hash, rolls.roll);
+        self.settle(rolls, remaining_accounts)
+    }
 
-        let mut buffer = [0u8; 16];
-        buffer.copy_from_slice(&hash[..16]);
+    /// Test-only: settles with a caller-chosen roll so win and loss paths can
+    /// be exercised deterministically. Parlay legs all land on the same roll,
+    /// a `Basis` bet on the lowest outcome of that percentile, and a bet on
+    /// another die on that face.
+    #[cfg(feature = "test-randomness")]
+    pub fn resolve_bet_with_roll(
+        &mut self,
+        roll: u8,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<ResolveOutcome> {
+        let basis = roll.saturating_sub(1) as u16 * 100;
+        self.settle(Rolls { roll, basis, face: roll as u16, legs: [roll; crate::MAX_LEGS] }, remaining_accounts)
+    }
 
-        let lower = u128::from_le_bytes(buffer);
-        buffer.copy_from_slice(&hash[16..]);
+    fn emit_proof(&self, input: &[u8], slot_hash: &[u8; 32], roll: u8) {
+        emit!(OutcomeProof {
+            player: self.bet.player,
+            seed: self.bet.seed,
+            input_hash: outcome_hash(input, slot_hash),
+            slot_hash: *slot_hash,
+            dice_count: self.bet.dice_count,
+            roll,
+        });
+    }
 
-        let upper = u128::from_le_bytes(buffer);
+    fn settle(
+        &mut self,
+        rolls: Rolls,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<ResolveOutcome> {
+        self.mint_rewards()?;
+        let outcome = Settlement {
+            house: &self.house,
+            vault: &self.vault,
+            config: &mut self.config,
+            jackpot: &mut self.jackpot,
+            jackpots: &mut self.jackpots,
+            fee_vault: &self.fee_vault,
+            treasury: &self.treasury,
+            price_update: self.price_update.as_deref(),
+            keeper: None,
+            player_balance: self.player_balance.as_ref().map(|balance| balance.as_ref()),
+            pending_rent_payer: self.resolver.key(),
+            system_program: &self.system_program,
+        }
+        .settle(
+            &self.bet,
+            self.player.to_account_info(),
+            &mut self.stats,
+            self.pending_payout.as_mut(),
+            rolls,
+            remaining_accounts,
+        )?;
+        self.recent_outcomes.record(RecentOutcome {
+            player: self.bet.player,
+            roll: outcome.roll,
+            won: outcome.won,
+            payout: outcome.payout,
+        });
+        self.ledger.record(self.bet.amount, outcome.payout, self.bet.house_fee()?)?;
+        Ok(outcome)
+    }
+}
 
-        let roll = (lower.wrapping_add(upper).wrapping_rem(100) + 1) as u8;
+impl<'info> ResolveBet<'info> {
+    /// Mints `amount * reward_rate` of the house's reward token to the
+    /// player, win or lose, signed by the vault as mint authority. A no-op
+    /// unless `rewards_enabled`.
+    fn mint_rewards(&self) -> Result<()> {
+        if !self.config.rewards_enabled {
+            return Ok(());
+        }
+        let (Some(mint), Some(player_ata), Some(token_program)) =
+            (&self.reward_mint, &self.player_reward_ata, &self.token_program)
+        else {
+            return err!(DiceError::RewardAccountsRequired);
+        };
+        require!(
+            self.config.reward_mint == Some(mint.key()),
+            DiceError::RewardAccountMismatch
+        );
+        require!(
+            player_ata.mint == mint.key() && player_ata.owner == self.player.key(),
+            DiceError::RewardAccountMismatch
+        );
+        let reward = self
+            .bet
+            .amount
+            .checked_mul(self.config.reward_rate)
+            .ok_or(DiceError::Overflow)?;
 
-        if self.bet.roll >= roll {
-            let payout = self
-                .bet
-                .amount
-                .checked_mul(10_000 - HOUSE_FEE)
-                .ok_or(DiceError::Overflow)?
-                .checked_div(10_000)
-                .unwrap();
-            let signer_seeds: &[&[&[u8]]] =
-                &[&[b"vault", &self.house.key().to_bytes(), &[bumps.vault]]];
-
-            let cpi_context = CpiContext::new_with_signer(
-                self.system_program.to_account_info(),
-                Transfer {
-                    from: self.vault.to_account_info(),
-                    to: self.player.to_account_info(),
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+        mint_to(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                MintTo {
+                    mint: mint.to_account_info(),
+                    to: player_ata.to_account_info(),
+                    authority: self.vault.to_account_info(),
                 },
                 signer_seeds,
+            ),
+            reward,
+        )
+    }
+}
+
+/// Result of settling a bet, handed back as return data so callers learn the
+/// outcome without parsing logs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct ResolveOutcome {
+    pub roll: u8,
+    pub won: bool,
+    pub payout: u64,
+}
+
+/// House-side accounts shared by the lamport resolution paths, so single and
+/// batched resolution settle a bet identically.
+pub struct Settlement<'a, 'info> {
+    pub house: &'a UncheckedAccount<'info>,
+    pub vault: &'a SystemAccount<'info>,
+    pub config: &'a mut HouseConfig,
+    pub jackpot: &'a mut Jackpot,
+    pub jackpots: &'a mut Jackpots,
+    pub fee_vault: &'a Account<'info, FeeVault>,
+    pub treasury: &'a Account<'info, Treasury>,
+    pub price_update: Option<&'a AccountInfo<'info>>,
+    /// A third party that submitted the resolution; see `keeper_resolve_bet`.
+    pub keeper: Option<&'a AccountInfo<'info>>,
+    /// The player's `PlayerBalance`, required exactly when the bet was staked
+    /// from it; every payout to the player is credited there instead.
+    pub player_balance: Option<&'a AccountInfo<'info>>,
+    /// Pays for an escrowed large win's `PendingPayout`, and is recorded on it
+    /// to get the rent back.
+    pub pending_rent_payer: Pubkey,
+    pub system_program: &'a Program<'info, System>,
+}
+
+impl<'info> Settlement<'_, 'info> {
+    pub fn settle(
+        &mut self,
+        bet: &Bet,
+        player: AccountInfo<'info>,
+        stats: &mut PlayerStats,
+        mut pending_payout: Option<&mut Account<'info, PendingPayout>>,
+        rolls: Rolls,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<ResolveOutcome> {
+        let roll = rolls.roll;
+        require!(bet.cached_outcome.is_none(), DiceError::BetAlreadySettled);
+        require!(bet.mint.is_none(), DiceError::MintMismatch);
+        require!(
+            bet.from_balance == self.player_balance.is_some(),
+            DiceError::PlayerBalanceMismatch
+        );
+        // Lamports sent to a program-owned account may be stranded there.
+        require_keys_eq!(*player.owner, System::id(), DiceError::InvalidPlayerAccount);
+        let player = self.player_balance.cloned().unwrap_or(player);
+        require!(
+            player.key() != self.house.key() && player.key() != self.vault.key(),
+            DiceError::PlayerIsHouseAccount
+        );
+        require!(
+            Clock::get()?.slot <= bet.expiry_slot,
+            DiceError::BetExpired
+        );
+        self.config.release_reservation(bet.potential_payout)?;
+        stats.release_open_bet();
+
+        let won = bet.is_winner(&rolls) && bet.legs_win(&rolls.legs);
+        let mut payout = 0;
+        let mut consolation = 0;
+        let mut charity = 0;
+
+        if won {
+            let mut base_payout = bet.payout()?;
+            if bet.usd_denominated {
+                base_payout = self.usd_payout(bet, base_payout)?;
+            }
+            payout = base_payout
+                .checked_add(self.streak_bonus(base_payout, stats.current_streak)?)
+                .ok_or(DiceError::Overflow)?;
+            require!(
+                self.vault.lamports() >= payout,
+                DiceError::VaultInsufficientFunds
             );
+            let threshold = self.config.large_win_threshold;
+            if threshold > 0 && payout >= threshold {
+                let pending = pending_payout
+                    .take()
+                    .ok_or(DiceError::PendingPayoutRequired)?;
+                self.escrow_payout(bet, pending, payout)?;
+            } else {
+                self.pay_from_vault(player.clone(), payout)?;
+            }
+
+            stats.win_count = stats.win_count.checked_add(1).ok_or(DiceError::Overflow)?;
+            stats.current_streak = stats.current_streak.saturating_add(1);
+            stats.total_won = stats
+                .total_won
+                .checked_add(payout)
+                .ok_or(DiceError::Overflow)?;
+        } else {
+            stats.current_streak = 0;
+            if bet.risk_free {
+                self.pay_from_vault(player.clone(), bet.amount)?;
+            } else {
+                self.fund_treasury(bet.amount)?;
+                consolation = self.near_miss_consolation(bet, roll)?;
+                if consolation > 0 {
+                    self.pay_from_vault(player.clone(), consolation)?;
+                }
+                charity = self.pay_charity(bet.amount.saturating_sub(consolation), remaining_accounts)?;
+            }
+        }
+        require!(
+            pending_payout.is_none(),
+            DiceError::PendingPayoutNotRequired
+        );
+
+        msg!(
+            "bet {}: rolled {} vs threshold {}, {}, payout {}",
+            bet.seed,
+            roll,
+            bet.effective_roll(bet.terms.bonus_roll_offset),
+            if won { "won" } else { "lost" },
+            payout
+        );
+
+        let house_fee = bet.house_fee()?;
+        let mut referral = 0;
+        if let Some(referrer) = bet.referrer {
+            referral = house_fee
+                .checked_mul(self.config.referral_bps as u64)
+                .ok_or(DiceError::Overflow)?
+                / 10_000;
+            let referrer_account = remaining_accounts
+                .iter()
+                .find(|account| account.key() == referrer)
+                .ok_or(DiceError::ReferrerAccountMissing)?;
+            if referral > 0 {
+                self.pay_from_vault(referrer_account.clone(), referral)?;
+            }
+        }
+
+        let contribution = self.fund_jackpot(house_fee)?;
+        let tier_contribution = self.fund_jackpot_tiers(house_fee)?;
+        let keeper_fee = self.pay_keeper(bet, house_fee)?;
+        let profit = house_fee.saturating_sub(referral + contribution + tier_contribution + keeper_fee);
+        if profit > 0 && !self.config.reinvest_fees {
+            self.pay_from_vault(self.fee_vault.to_account_info(), profit)?;
+        }
+
+        if bet.dice_count == 0 && roll == JACKPOT_ROLL && self.jackpot.balance > 0 {
+            let jackpot = self.jackpot.balance;
+            self.jackpot.balance = 0;
+            self.pay_from_vault(player.clone(), jackpot)?;
+            // Restart from the guaranteed floor, earmarking only what the
+            // vault can spare beyond open bets and the tiered jackpots.
+            self.jackpot.balance = self.config.min_jackpot.min(self.unreserved(0));
+
+            emit!(JackpotWon {
+                player: bet.player,
+                seed: bet.seed,
+                amount: jackpot,
+            });
+        }
+
+        let tier = match bet.dice_count {
+            0 => self.jackpots.triggered(roll),
+            _ => None,
+        };
+        if let Some(index) = tier {
+            let amount = self.jackpots.tiers[index].balance;
+            self.jackpots.tiers[index].balance = 0;
+            self.pay_from_vault(player, amount)?;
+
+            emit!(JackpotTierWon {
+                player: bet.player,
+                seed: bet.seed,
+                tier: Jackpots::KINDS[index],
+                amount,
+            });
+        }
+
+        emit!(BetResolved {
+            player: bet.player,
+            seed: bet.seed,
+            roll: bet.roll,
+            result: roll,
+            won,
+            payout,
+            memo: bet.memo,
+            roll_offset: bet.terms.bonus_roll_offset,
+            consolation,
+            charity,
+        });
+
+        Ok(ResolveOutcome { roll, won, payout })
+    }
+
+    /// The bonus grows with the wins before this one and is only paid from
+    /// funds not reserved for other bets or the jackpot.
+    fn streak_bonus(&self, payout: u64, streak: u32) -> Result<u64> {
+        let bonus_bps = (streak as u64)
+            .saturating_mul(self.config.streak_bonus_bps as u64)
+            .min(self.config.max_streak_bonus_bps as u64);
+        let bonus = payout
+            .checked_mul(bonus_bps)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        Ok(bonus.min(self.unreserved(payout)))
+    }
 
-            transfer(cpi_context, payout)?;
+    /// Returns `near_miss_bps` of the stake for a loss within
+    /// `near_miss_window` of the threshold, paid only from unreserved funds.
+    fn near_miss_consolation(&self, bet: &Bet, roll: u8) -> Result<u64> {
+        let window = self.config.near_miss_window;
+        // A parlay can lose on a leg while its own roll wins, so it has no
+        // near miss, and the window is in whole d100 rolls, which neither a
+        // `Basis` bet nor one on another die is judged on.
+        if window == 0
+            || bet.leg_count > 0
+            || bet.precision == PrecisionMode::Basis
+            || bet.faces != D100_FACES
+            || bet.miss_distance(roll, bet.terms.bonus_roll_offset) > window
+        {
+            return Ok(0);
         }
+        let consolation = bet
+            .amount
+            .checked_mul(self.config.near_miss_bps as u64)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        Ok(consolation.min(self.unreserved(0)))
+    }
+
+    /// Converts a USD-denominated payout at the current SOL/USD price. A price
+    /// drop can push it past what was reserved at placement, so the excess is
+    /// capped by the vault's unreserved balance.
+    fn usd_payout(&self, bet: &Bet, payout: u64) -> Result<u64> {
+        let price_update = self.price_update.ok_or(DiceError::PriceFeedRequired)?;
+        let current = read_sol_usd_price(price_update, self.config)?;
+        let pegged = usd_pegged_payout(payout, (bet.entry_price, bet.entry_price_expo), current)?;
+        Ok(pegged.min(payout.saturating_add(self.unreserved(payout))))
+    }
+
+    /// Vault lamports left once `payout`, other bets' reservations and the
+    /// jackpots are set aside.
+    fn unreserved(&self, payout: u64) -> u64 {
+        self.vault
+            .lamports()
+            .saturating_sub(payout)
+            .saturating_sub(self.config.reserved_lamports)
+            .saturating_sub(self.jackpot.balance)
+            .saturating_sub(self.jackpots.total())
+    }
+
+    /// Holds a large win back for the challenge window. The funds stay in
+    /// the vault and remain reserved until claimed or voided.
+    fn escrow_payout(
+        &mut self,
+        bet: &Bet,
+        pending: &mut Account<'info, PendingPayout>,
+        amount: u64,
+    ) -> Result<()> {
+        let (_, bump) = Pubkey::find_program_address(
+            &[
+                b"pending_payout",
+                self.vault.key().as_ref(),
+                bet.player.as_ref(),
+                bet.seed.to_le_bytes().as_ref(),
+            ],
+            &crate::ID,
+        );
+        pending.set_inner(PendingPayout {
+            player: bet.player,
+            seed: bet.seed,
+            amount,
+            release_slot: Clock::get()?
+                .slot
+                .checked_add(self.config.challenge_slots)
+                .ok_or(DiceError::Overflow)?,
+            rent_payer: self.pending_rent_payer,
+            bump,
+        });
+        self.config.reserved_lamports = self
+            .config
+            .reserved_lamports
+            .checked_add(amount)
+            .ok_or(DiceError::Overflow)?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn fund_jackpot(&mut self, house_fee: u64) -> Result<u64> {
+        let contribution = house_fee
+            .checked_mul(self.config.jackpot_contribution_bps as u64)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        self.jackpot.balance = self
+            .jackpot
+            .balance
+            .checked_add(contribution)
+            .ok_or(DiceError::Overflow)?;
+        Ok(contribution)
+    }
+
+    /// Adds each tier's `contribution_bps` of the house fee to its balance,
+    /// returning the total taken.
+    fn fund_jackpot_tiers(&mut self, house_fee: u64) -> Result<u64> {
+        let mut total = 0u64;
+        for tier in self.jackpots.tiers.iter_mut() {
+            let contribution = house_fee
+                .checked_mul(tier.contribution_bps as u64)
+                .ok_or(DiceError::Overflow)?
+                / 10_000;
+            tier.balance = tier.balance.checked_add(contribution).ok_or(DiceError::Overflow)?;
+            total = total.checked_add(contribution).ok_or(DiceError::Overflow)?;
+        }
+        Ok(total)
+    }
+
+    /// Pays `keeper_fee_bps` of the house fee to the keeper, if one submitted
+    /// the resolution, returning the amount.
+    fn pay_keeper(&self, bet: &Bet, house_fee: u64) -> Result<u64> {
+        let Some(keeper) = self.keeper else {
+            return Ok(0);
+        };
+        let fee = house_fee
+            .checked_mul(self.config.keeper_fee_bps as u64)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        if fee > 0 {
+            self.pay_from_vault(keeper.clone(), fee)?;
+            emit!(KeeperPaid {
+                keeper: keeper.key(),
+                player: bet.player,
+                seed: bet.seed,
+                amount: fee,
+            });
+        }
+        Ok(fee)
+    }
+
+    /// Gives `charity_bps` of what the house keeps from a lost stake to the
+    /// configured charity, returning the amount.
+    fn pay_charity(&self, kept: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+        let Some(charity) = self.config.charity else {
+            return Ok(0);
+        };
+        let amount = kept
+            .checked_mul(self.config.charity_bps as u64)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        if amount > 0 {
+            let charity_account = remaining_accounts
+                .iter()
+                .find(|account| account.key() == charity)
+                .ok_or(DiceError::CharityAccountMissing)?;
+            self.pay_from_vault(charity_account.clone(), amount)?;
+        }
+        Ok(amount)
+    }
+
+    /// Moves `insurance_bps` of a lost stake to the treasury.
+    fn fund_treasury(&self, stake: u64) -> Result<()> {
+        let insurance = stake
+            .checked_mul(self.config.insurance_bps as u64)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        if insurance > 0 {
+            self.pay_from_vault(self.treasury.to_account_info(), insurance)?;
+        }
+        Ok(())
+    }
+
+    fn pay_from_vault(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        require_vault_rent_exempt(self.vault.lamports(), amount)?;
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+
+        let cpi_context = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.vault.to_account_info(),
+                to,
+            },
+            signer_seeds,
+        );
+
+        transfer(cpi_context, amount)
+    }
+}
+
+/// Scans the instructions preceding the current one for the ed25519
+/// instruction carrying `sig`, so compute-budget or relayer instructions may
+/// be placed anywhere in the transaction. Returns its already-unpacked
+/// signatures and the index of the entry carrying `sig` (relayers may bundle
+/// unrelated verifications into one instruction), so the instruction is only
+/// parsed once. Fails with `Ed25519InstructionNotFound` if there's no ed25519
+/// instruction at all, `Ed25519SignatureCount` if none carries a signature,
+/// and `Ed25519SignatureValue` if none carries `sig`.
+fn find_ed25519_signature(
+    instructions: &AccountInfo,
+    sig: &[u8],
+) -> Result<(Ed25519InstructionSignatures, usize)> {
+    require_instructions_sysvar(instructions)?;
+    let current_index = load_current_index_checked(instructions)?;
+    let mut found_instruction = false;
+    let mut found_signature = false;
+    for index in 0..current_index {
+        let ix = load_instruction_at_checked(index as usize, instructions)?;
+        if ix.program_id != ed25519_program::ID {
+            continue;
+        }
+        found_instruction = true;
+        let Ok(signatures) = Ed25519InstructionSignatures::unpack(&ix.data) else {
+            continue;
+        };
+        found_signature |= !signatures.0.is_empty();
+        let position = signatures
+            .0
+            .iter()
+            .position(|signature| signature.signature.is_some_and(|s| s.eq(sig)));
+        if let Some(position) = position {
+            require_eq!(ix.accounts.len(), 0, DiceError::Ed25519Accounts);
+            return Ok((signatures, position));
+        }
+    }
+    require!(found_instruction, DiceError::Ed25519InstructionNotFound);
+    require!(found_signature, DiceError::Ed25519SignatureCount);
+    err!(DiceError::Ed25519SignatureValue)
+}
+
+/// Every account struct pins `instructions` to the sysvar with an `address`
+/// constraint; checking again here keeps the signature checks sound even if
+/// one of those constraints is ever loosened.
+fn require_instructions_sysvar(instructions: &AccountInfo) -> Result<()> {
+    require_keys_eq!(
+        instructions.key(),
+        InstructionSysvarId,
+        DiceError::InvalidInstructionsSysvar
+    );
+    Ok(())
+}
+
+/// Finds the verified ed25519 entry carrying `sig` and returns the message
+/// `signer` signed with it.
+fn signed_message(instructions: &AccountInfo, signer: &Pubkey, sig: &[u8]) -> Result<Vec<u8>> {
+    require_eq!(sig.len(), 64, DiceError::Ed25519SignatureLength);
+    let (mut signatures, position) = find_ed25519_signature(instructions, sig)?;
+    let signature = signatures.0.swap_remove(position);
+    require!(signature.is_verifiable, DiceError::Ed25519Header);
+    require_keys_eq!(
+        signature.public_key.ok_or(DiceError::Ed25519PubkeyMissing)?,
+        *signer,
+        DiceError::Ed25519Pubkey
+    );
+    signature.message.ok_or(error!(DiceError::Ed25519MessageMissing))
+}
+
+pub fn verify_ed25519_signature(
+    instructions: &AccountInfo,
+    player: &Pubkey,
+    bet: &Bet,
+    sig: &[u8],
+) -> Result<()> {
+    let message = signed_message(instructions, player, sig)?;
+    require!(
+        message.first() == Some(&BET_MESSAGE_VERSION),
+        DiceError::UnsupportedBetVersion
+    );
+    // Checked on its own first so a message that's a prefix or extension
+    // of the bet's fails as such, whatever the comparison below does.
+    require_eq!(message.len(), bet.message_len(), DiceError::Ed25519MessageLength);
+    require!(bet.message_matches(&message), DiceError::Ed25519Message);
+    if bet.signature_window_slots > 0 {
+        let deadline = bet
+            .slot
+            .checked_add(bet.signature_window_slots)
+            .ok_or(DiceError::Overflow)?;
+        require!(Clock::get()?.slot <= deadline, DiceError::SignatureExpired);
+    }
+
+    Ok(())
+}
+
+/// Checks that `resolver` signed off on `bet` being resolved by a keeper.
+pub fn verify_keeper_authorization(
+    instructions: &AccountInfo,
+    resolver: &Pubkey,
+    bet: &Bet,
+    sig: &[u8],
+) -> Result<()> {
+    let message = signed_message(instructions, resolver, sig)?;
+    require!(message == bet.keeper_message(), DiceError::KeeperAuthorizationMismatch);
+    Ok(())
+}
+
+/// Checks that at least `config.resolver_threshold` distinct members of
+/// `config.resolver_set` signed `bet.quorum_message()` in the instructions
+/// preceding the current one, in as many ed25519 instructions as the
+/// resolvers like. Always passes while the threshold is 0.
+pub fn verify_resolver_quorum(instructions: &AccountInfo, config: &HouseConfig, bet: &Bet) -> Result<()> {
+    if config.resolver_threshold == 0 {
+        return Ok(());
+    }
+    require_instructions_sysvar(instructions)?;
+    let message = bet.quorum_message();
+    let mut approved = [false; MAX_RESOLVERS];
+    let current_index = load_current_index_checked(instructions)?;
+    for index in 0..current_index {
+        let ix = load_instruction_at_checked(index as usize, instructions)?;
+        if ix.program_id != ed25519_program::ID || !ix.accounts.is_empty() {
+            continue;
+        }
+        let Ok(signatures) = Ed25519InstructionSignatures::unpack(&ix.data) else {
+            continue;
+        };
+        for signature in signatures.0 {
+            if !signature.is_verifiable || signature.message.as_deref() != Some(message.as_slice()) {
+                continue;
+            }
+            let Some(key) = signature.public_key else {
+                continue;
+            };
+            if key == Pubkey::default() {
+                continue;
+            }
+            if let Some(position) = config.resolver_set.iter().position(|resolver| *resolver == key) {
+                approved[position] = true;
+            }
+        }
+    }
+    let approvals = approved.iter().filter(|approved| **approved).count();
+    require!(
+        approvals >= config.resolver_threshold as usize,
+        DiceError::InsufficientResolverSignatures
+    );
+    Ok(())
+}
+
+/// Checks that the resolver signed `bet.window_message` over a range of at
+/// most `config.resolution_window_slots` slots that includes the current
+/// one, in an ed25519 instruction preceding the current one. Always passes
+/// while the window is 0.
+pub fn verify_resolution_window(instructions: &AccountInfo, config: &HouseConfig, bet: &Bet) -> Result<()> {
+    if config.resolution_window_slots == 0 {
+        return Ok(());
+    }
+    require_instructions_sysvar(instructions)?;
+    let mut prefix = bet.to_slice();
+    prefix.extend_from_slice(WINDOW_MESSAGE_TAG);
+    let slot = Clock::get()?.slot;
+    let current_index = load_current_index_checked(instructions)?;
+    for index in 0..current_index {
+        let ix = load_instruction_at_checked(index as usize, instructions)?;
+        if ix.program_id != ed25519_program::ID || !ix.accounts.is_empty() {
+            continue;
+        }
+        let Ok(signatures) = Ed25519InstructionSignatures::unpack(&ix.data) else {
+            continue;
+        };
+        for signature in signatures.0 {
+            if !signature.is_verifiable || signature.public_key != Some(config.resolver) {
+                continue;
+            }
+            let Some(message) = signature.message else {
+                continue;
+            };
+            let Some(slots) = message.strip_prefix(prefix.as_slice()) else {
+                continue;
+            };
+            if slots.len() != 16 {
+                continue;
+            }
+            let valid_from = u64::from_le_bytes(slots[..8].try_into().unwrap());
+            let valid_until = u64::from_le_bytes(slots[8..].try_into().unwrap());
+            if valid_from <= slot
+                && slot <= valid_until
+                && valid_until - valid_from <= config.resolution_window_slots
+            {
+                return Ok(());
+            }
+        }
+    }
+    err!(DiceError::ResolutionStale)
+}
+
+/// Checks that `player` signed `message`, authorizing a relayed placement.
+pub fn verify_placement_signature(
+    instructions: &AccountInfo,
+    player: &Pubkey,
+    message: &[u8],
+    sig: &[u8],
+) -> Result<()> {
+    let signed = signed_message(instructions, player, sig)?;
+    require!(signed == message, DiceError::PlacementMessageMismatch);
+    Ok(())
+}
+
+/// Checks that `resolver` signed an offer to buy `bet` back for `amount`.
+pub fn verify_cashout_signature(
+    instructions: &AccountInfo,
+    resolver: &Pubkey,
+    bet: &Bet,
+    amount: u64,
+    sig: &[u8],
+) -> Result<()> {
+    let message = signed_message(instructions, resolver, sig)?;
+    require!(message == bet.cashout_message(amount), DiceError::CashoutOfferMismatch);
+    Ok(())
+}
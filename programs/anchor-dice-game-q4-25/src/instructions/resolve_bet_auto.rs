@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use solana_program::sysvar::slot_hashes::ID as SlotHashesId;
+
+use crate::{
+    errors::DiceError,
+    events::OutcomeProof,
+    instructions::{ResolveOutcome, Settlement},
+    utils::{belongs_to_house, outcome_hash},
+    Bet, FeeVault, HouseConfig, Jackpot, Jackpots, PendingPayout, PlayerBalance, PlayerStats, RandomnessSource, Treasury,
+};
+
+/// Settles a bet from the signature `presign_bet` stored on it, for
+/// schedulers such as Clockwork threads: every account is a PDA of the
+/// config or the bet, or an address the bet records, and the only signer is
+/// whoever runs the thread, who earns `keeper_fee_bps` like a keeper.
+///
+/// Trust model: the randomness is still the player's signature mixed with
+/// the bet's slot hash, but it sits in the bet account from `presign_bet`
+/// on, so anyone can compute the outcome before it settles. The player could
+/// always do that; what changes is that the house no longer gets a say when
+/// a bet settles, since neither the resolver nor the quorum signs anything
+/// here. Houses opt in with `auto_resolve`, and the path stays closed while
+/// a resolver quorum or slot window is required. Setting
+/// `reveal_delay_slots` keeps the outcome unknown to everyone until the
+/// delay passes.
+#[derive(Accounts)]
+pub struct ResolveBetAuto<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    ///CHECK: only identifies the house, which `config`, `vault` and `bet`
+    /// must all belong to
+    #[account(
+        constraint = belongs_to_house(&house.key(), &config, &vault.key(), &bet, &bet.key()) @ DiceError::AccountHouseMismatch
+    )]
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        seeds = [b"jackpot", house.key().as_ref(), &[config.game_id]],
+        bump = jackpot.bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+    #[account(
+        mut,
+        seeds = [b"jackpots", house.key().as_ref(), &[config.game_id]],
+        bump = jackpots.bump
+    )]
+    pub jackpots: Account<'info, Jackpots>,
+    #[account(
+        mut,
+        seeds = [b"fee_vault", house.key().as_ref(), &[config.game_id]],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    #[account(
+        mut,
+        seeds = [b"treasury", house.key().as_ref(), &[config.game_id]],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    /// CHECK: the player
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// Only for bets staked from a `PlayerBalance`, which their winnings are
+    /// credited back to.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    /// Only for a win at or above `large_win_threshold`: receives the escrowed
+    /// payout, with the keeper paying its rent.
+    #[account(
+        init,
+        payer = keeper,
+        space = 8 + PendingPayout::INIT_SPACE,
+        seeds = [b"pending_payout", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pending_payout: Option<Account<'info, PendingPayout>>,
+    /// CHECK: Pyth SOL/USD `PriceUpdateV2`, validated by `read_sol_usd_price`;
+    /// only needed for USD-denominated bets.
+    pub price_update: Option<UncheckedAccount<'info>>,
+    /// CHECK: SlotHashes sysvar, read by `Bet::outcome_slot_hash`.
+    #[account(address = SlotHashesId)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResolveBetAuto<'info> {
+    pub fn resolve_bet_auto(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<ResolveOutcome> {
+        require!(self.config.auto_resolve, DiceError::AutoResolveDisabled);
+        require!(
+            self.bet.randomness_source == RandomnessSource::Signature,
+            DiceError::RandomnessSourceMismatch
+        );
+        require!(self.bet.presigned_sig != [0; 64], DiceError::BetNotPresigned);
+        // As with `resolve_bet_cpi`, nothing here carries the resolvers'
+        // signatures.
+        require!(self.config.resolver_threshold == 0, DiceError::InsufficientResolverSignatures);
+        require!(self.config.resolution_window_slots == 0, DiceError::ResolutionStale);
+        let sig = self.bet.presigned_sig;
+        let slot_hash = self.bet.outcome_slot_hash(&self.config, self.slot_hashes.as_deref())?;
+        let rolls = self.bet.rolls(&sig, &slot_hash);
+        emit!(OutcomeProof {
+            player: self.bet.player,
+            seed: self.bet.seed,
+            input_hash: outcome_hash(&sig, &slot_hash),
+            slot_hash,
+            dice_count: self.bet.dice_count,
+            roll: rolls.roll,
+        });
+
+        let keeper = self.keeper.key();
+        let paid_keeper = ![self.config.resolver, self.config.authority, self.house.key()].contains(&keeper);
+        Settlement {
+            house: &self.house,
+            vault: &self.vault,
+            config: &mut self.config,
+            jackpot: &mut self.jackpot,
+            jackpots: &mut self.jackpots,
+            fee_vault: &self.fee_vault,
+            treasury: &self.treasury,
+            price_update: self.price_update.as_deref(),
+            keeper: paid_keeper.then(|| self.keeper.as_ref()),
+            player_balance: self.player_balance.as_ref().map(|balance| balance.as_ref()),
+            pending_rent_payer: self.keeper.key(),
+            system_program: &self.system_program,
+        }
+        .settle(
+            &self.bet,
+            self.player.to_account_info(),
+            &mut self.stats,
+            self.pending_payout.as_mut(),
+            rolls,
+            remaining_accounts,
+        )
+    }
+}
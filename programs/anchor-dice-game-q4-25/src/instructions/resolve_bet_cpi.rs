@@ -0,0 +1,182 @@
+use anchor_lang::prelude::*;
+use solana_program::sysvar::slot_hashes::ID as SlotHashesId;
+
+use crate::{
+    errors::DiceError,
+    events::OutcomeProof,
+    instructions::{ResolveOutcome, Settlement},
+    utils::outcome_hash,
+    Bet, FeeVault, HouseConfig, Jackpot, Jackpots, PendingPayout, PlayerBalance, PlayerStats, RandomnessSource, Treasury,
+};
+
+/// Resolution for bets driven by another program, which cannot rely on the
+/// ed25519 instruction since the instructions sysvar only sees top-level
+/// instructions.
+///
+/// Trust assumptions: the outcome is derived from whatever `randomness` the
+/// caller passes, and this program does not verify where it came from. The
+/// house trusts `config.cpi_resolver` (typically a PDA of the calling
+/// program, signing through `invoke_signed`) to have verified it; the player
+/// accepts it by co-signing. Neither signature alone resolves a bet, and only
+/// signature-sourced bets qualify, so a commit-reveal or Switchboard bet's
+/// committed randomness can't be bypassed.
+///
+/// Retries: a caller with at-least-once delivery may land the same
+/// resolution twice. By default the second attempt fails on the closed bet.
+/// Under the house's `cache_outcomes` the settled bet instead stays open
+/// with its outcome in `Bet::cached_outcome`, and a repeat call returns that
+/// outcome without paying anything; the outcome is only written by the
+/// instruction that made the transfers, so the two can't disagree. The
+/// caller closes the bet with `close_settled_bet` once it's done retrying.
+/// A retry of an escrowed large win must leave out `pending_payout`, which
+/// already exists.
+#[derive(Accounts)]
+pub struct ResolveBetCpi<'info> {
+    #[account(mut)]
+    pub cpi_resolver: Signer<'info>,
+    #[account(mut)]
+    pub player: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        constraint = config.cpi_resolver == Some(cpi_resolver.key()) @ DiceError::UnauthorizedCpiResolver,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        seeds = [b"jackpot", house.key().as_ref(), &[config.game_id]],
+        bump = jackpot.bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+    #[account(
+        mut,
+        seeds = [b"jackpots", house.key().as_ref(), &[config.game_id]],
+        bump = jackpots.bump
+    )]
+    pub jackpots: Account<'info, Jackpots>,
+    #[account(
+        mut,
+        seeds = [b"fee_vault", house.key().as_ref(), &[config.game_id]],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    #[account(
+        mut,
+        seeds = [b"treasury", house.key().as_ref(), &[config.game_id]],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    /// Closed once settled unless the house caches outcomes.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// Only for bets staked from a `PlayerBalance`, which their winnings are
+    /// credited back to.
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    /// Only for a win at or above `large_win_threshold`: receives the escrowed
+    /// payout, with the CPI resolver paying its rent.
+    #[account(
+        init,
+        payer = cpi_resolver,
+        space = 8 + PendingPayout::INIT_SPACE,
+        seeds = [b"pending_payout", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pending_payout: Option<Account<'info, PendingPayout>>,
+    /// CHECK: Pyth SOL/USD `PriceUpdateV2`, validated by `read_sol_usd_price`;
+    /// only needed for USD-denominated bets.
+    pub price_update: Option<UncheckedAccount<'info>>,
+    /// CHECK: SlotHashes sysvar, read by `Bet::outcome_slot_hash`.
+    #[account(address = SlotHashesId)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResolveBetCpi<'info> {
+    pub fn resolve_bet_cpi(
+        &mut self,
+        randomness: [u8; 32],
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<ResolveOutcome> {
+        if let Some(outcome) = self.bet.cached_outcome {
+            msg!("bet {} already settled, returning cached outcome", self.bet.seed);
+            return Ok(outcome);
+        }
+        require!(
+            self.bet.randomness_source == RandomnessSource::Signature,
+            DiceError::RandomnessSourceMismatch
+        );
+        // Without the instructions sysvar there is no way to see the
+        // quorum's approvals or the resolver's slot window, so this path is
+        // closed while either is required.
+        require!(self.config.resolver_threshold == 0, DiceError::InsufficientResolverSignatures);
+        require!(self.config.resolution_window_slots == 0, DiceError::ResolutionStale);
+        let slot_hash = self.bet.outcome_slot_hash(&self.config, self.slot_hashes.as_deref())?;
+        let rolls = self.bet.rolls(&randomness, &slot_hash);
+        emit!(OutcomeProof {
+            player: self.bet.player,
+            seed: self.bet.seed,
+            input_hash: outcome_hash(&randomness, &slot_hash),
+            slot_hash,
+            dice_count: self.bet.dice_count,
+            roll: rolls.roll,
+        });
+
+        let outcome = Settlement {
+            house: &self.house,
+            vault: &self.vault,
+            config: &mut self.config,
+            jackpot: &mut self.jackpot,
+            jackpots: &mut self.jackpots,
+            fee_vault: &self.fee_vault,
+            treasury: &self.treasury,
+            price_update: self.price_update.as_deref(),
+            keeper: None,
+            player_balance: self.player_balance.as_ref().map(|balance| balance.as_ref()),
+            pending_rent_payer: self.cpi_resolver.key(),
+            system_program: &self.system_program,
+        }
+        .settle(
+            &self.bet,
+            self.player.to_account_info(),
+            &mut self.stats,
+            self.pending_payout.as_mut(),
+            rolls,
+            remaining_accounts,
+        )?;
+        if self.config.cache_outcomes {
+            self.bet.cached_outcome = Some(outcome);
+        } else {
+            self.bet.close(self.rent_payer.to_account_info())?;
+        }
+        Ok(outcome)
+    }
+}
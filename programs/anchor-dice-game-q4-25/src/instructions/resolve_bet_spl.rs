@@ -0,0 +1,191 @@
+use crate::{
+    errors::DiceError,
+    events::BetResolved,
+    instructions::{verify_ed25519_signature, verify_resolution_window, verify_resolver_quorum},
+    Bet, HouseConfig, PlayerStats, TokenReserve,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+use solana_program::sysvar::{instructions::ID as InstructionSysvarId, slot_hashes::ID as SlotHashesId};
+
+#[derive(Accounts)]
+pub struct ResolveBetSpl<'info> {
+    pub resolver: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = resolver,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    /// CHECK: the player
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = player
+    )]
+    pub player_ata: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"token_reserve", vault.key().as_ref(), mint.key().as_ref()],
+        bump = token_reserve.bump
+    )]
+    pub token_reserve: Account<'info, TokenReserve>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// CHECK: SlotHashes sysvar, read by `Bet::outcome_slot_hash`.
+    #[account(address = SlotHashesId)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar
+    #[account(
+        address = InstructionSysvarId
+    )]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ResolveBetSpl<'info> {
+    pub fn verify_ed25519_signature(&self, sig: &[u8]) -> Result<()> {
+        verify_ed25519_signature(&self.instructions, &self.player.key(), &self.bet, sig)
+    }
+
+    pub fn verify_resolver_quorum(&self) -> Result<()> {
+        verify_resolver_quorum(&self.instructions, &self.config, &self.bet)
+    }
+
+    pub fn verify_resolution_window(&self) -> Result<()> {
+        verify_resolution_window(&self.instructions, &self.config, &self.bet)
+    }
+
+    pub fn resolve_bet(&mut self, sig: &[u8]) -> Result<()> {
+        TokenSettlement {
+            vault: &self.vault,
+            config: &mut self.config,
+            mint: &self.mint,
+            vault_ata: &self.vault_ata,
+            token_reserve: &mut self.token_reserve,
+            stats: &mut self.stats,
+            slot_hashes: self.slot_hashes.as_deref(),
+            token_program: &self.token_program,
+        }
+        .settle(&self.bet, sig, &self.player_ata)?;
+        Ok(())
+    }
+}
+
+/// House-side accounts shared by the token resolution paths, so a wSOL bet
+/// settles exactly like any other token bet before it's unwrapped.
+pub struct TokenSettlement<'a, 'info> {
+    pub vault: &'a SystemAccount<'info>,
+    pub config: &'a mut HouseConfig,
+    pub mint: &'a Account<'info, Mint>,
+    pub vault_ata: &'a Account<'info, TokenAccount>,
+    pub token_reserve: &'a mut TokenReserve,
+    pub stats: &'a mut PlayerStats,
+    pub slot_hashes: Option<&'a AccountInfo<'info>>,
+    pub token_program: &'a Program<'info, Token>,
+}
+
+impl<'info> TokenSettlement<'_, 'info> {
+    /// Settles `bet` and pays a win from the vault's token account into `to`,
+    /// returning the payout.
+    pub fn settle(&mut self, bet: &Bet, sig: &[u8], to: &Account<'info, TokenAccount>) -> Result<u64> {
+        require_eq!(sig.len(), 64, DiceError::Ed25519SignatureLength);
+        require_keys_eq!(
+            bet.mint.ok_or(DiceError::MintMismatch)?,
+            self.mint.key(),
+            DiceError::MintMismatch
+        );
+        require!(
+            Clock::get()?.slot <= bet.expiry_slot,
+            DiceError::BetExpired
+        );
+
+        let slot_hash = bet.outcome_slot_hash(self.config, self.slot_hashes)?;
+        let rolls = bet.rolls(sig, &slot_hash);
+        let roll = rolls.roll;
+
+        let won = bet.is_winner(&rolls);
+        self.token_reserve.release(bet.potential_payout)?;
+        self.stats.release_open_bet();
+        self.config.release_token_bet();
+        let mut payout = 0;
+
+        if won {
+            payout = bet.payout()?;
+            require!(
+                self.vault_ata.amount >= payout,
+                DiceError::VaultInsufficientFunds
+            );
+            let signer_seeds: &[&[&[u8]]] =
+                &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+
+            let cpi_context = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault_ata.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            );
+
+            transfer(cpi_context, payout)?;
+        }
+
+        msg!(
+            "bet {}: rolled {} vs threshold {}, {}, payout {}",
+            bet.seed,
+            roll,
+            bet.effective_roll(bet.terms.bonus_roll_offset),
+            if won { "won" } else { "lost" },
+            payout
+        );
+
+        emit!(BetResolved {
+            player: bet.player,
+            seed: bet.seed,
+            roll: bet.roll,
+            result: roll,
+            won,
+            payout,
+            memo: bet.memo,
+            roll_offset: bet.terms.bonus_roll_offset,
+            consolation: 0,
+            charity: 0,
+        });
+
+        Ok(payout)
+    }
+}
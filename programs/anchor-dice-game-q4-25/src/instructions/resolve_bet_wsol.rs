@@ -0,0 +1,172 @@
+use crate::{
+    errors::DiceError,
+    instructions::{verify_ed25519_signature, verify_resolution_window, verify_resolver_quorum, TokenSettlement},
+    Bet, HouseConfig, PlayerStats, TokenReserve,
+};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{close_account, spl_token::native_mint, CloseAccount, Mint, Token, TokenAccount},
+};
+use solana_program::sysvar::{instructions::ID as InstructionSysvarId, slot_hashes::ID as SlotHashesId};
+
+/// `resolve_bet_spl` for bets placed in wrapped SOL. A win is paid either
+/// into the player's wSOL account, created at the resolver's expense if the
+/// player has closed it since placing, or with `unwrap` as native SOL: the
+/// payout goes through `unwrap_account`, a token account only this bet uses,
+/// which is closed into the resolver, who forwards the payout to the player
+/// and keeps the account's rent back. Exactly one of the two accounts must
+/// be passed, matching `unwrap`.
+#[derive(Accounts)]
+pub struct ResolveBetWsol<'info> {
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = resolver,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    /// CHECK: the player
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
+    #[account(address = native_mint::ID @ DiceError::MintMismatch)]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        associated_token::mint = mint,
+        associated_token::authority = player
+    )]
+    pub player_ata: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), player.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    #[account(
+        init,
+        payer = resolver,
+        token::mint = mint,
+        token::authority = vault,
+        seeds = [b"unwrap", bet.key().as_ref()],
+        bump
+    )]
+    pub unwrap_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: gets the bet's rent back; must be whoever funded it
+    #[account(mut, address = bet.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"token_reserve", vault.key().as_ref(), mint.key().as_ref()],
+        bump = token_reserve.bump
+    )]
+    pub token_reserve: Account<'info, TokenReserve>,
+    #[account(
+        mut,
+        seeds = [b"stats", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, PlayerStats>,
+    /// CHECK: SlotHashes sysvar, read by `Bet::outcome_slot_hash`.
+    #[account(address = SlotHashesId)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar
+    #[account(
+        address = InstructionSysvarId
+    )]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResolveBetWsol<'info> {
+    pub fn verify_ed25519_signature(&self, sig: &[u8]) -> Result<()> {
+        verify_ed25519_signature(&self.instructions, &self.player.key(), &self.bet, sig)
+    }
+
+    pub fn verify_resolver_quorum(&self) -> Result<()> {
+        verify_resolver_quorum(&self.instructions, &self.config, &self.bet)
+    }
+
+    pub fn verify_resolution_window(&self) -> Result<()> {
+        verify_resolution_window(&self.instructions, &self.config, &self.bet)
+    }
+
+    pub fn resolve_bet(&mut self, sig: &[u8], unwrap: bool) -> Result<()> {
+        require!(
+            unwrap == self.unwrap_account.is_some() && unwrap != self.player_ata.is_some(),
+            DiceError::WsolAccountsMismatch
+        );
+        let to = self
+            .unwrap_account
+            .as_ref()
+            .or(self.player_ata.as_ref())
+            .ok_or(DiceError::WsolAccountsMismatch)?;
+        let payout = TokenSettlement {
+            vault: &self.vault,
+            config: &mut self.config,
+            mint: &self.mint,
+            vault_ata: &self.vault_ata,
+            token_reserve: &mut self.token_reserve,
+            stats: &mut self.stats,
+            slot_hashes: self.slot_hashes.as_deref(),
+            token_program: &self.token_program,
+        }
+        .settle(&self.bet, sig, to)?;
+
+        if let Some(unwrap_account) = &self.unwrap_account {
+            // Closed on a loss too, so the resolver gets its rent back.
+            self.unwrap(unwrap_account, payout)?;
+        }
+        Ok(())
+    }
+
+    /// Closes `unwrap_account`, which turns its wSOL back into lamports, into
+    /// the resolver, and has the resolver pass the payout on: closing it
+    /// straight into the player would hand them its rent as well.
+    fn unwrap(&self, unwrap_account: &Account<'info, TokenAccount>, payout: u64) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: unwrap_account.to_account_info(),
+                destination: self.resolver.to_account_info(),
+                authority: self.vault.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        if payout > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: self.resolver.to_account_info(),
+                        to: self.player.to_account_info(),
+                    },
+                ),
+                payout,
+            )?;
+        }
+        Ok(())
+    }
+}
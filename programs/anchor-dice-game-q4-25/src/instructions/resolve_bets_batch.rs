@@ -0,0 +1,149 @@
+use crate::{
+    errors::DiceError,
+    instructions::{verify_ed25519_signature, verify_resolution_window, verify_resolver_quorum, Settlement},
+    Bet, FeeVault, HouseConfig, Jackpot, Jackpots, PlayerStats, RandomnessSource, Treasury,
+};
+use anchor_lang::{error::ErrorCode, prelude::*};
+use solana_program::sysvar::{instructions::ID as InstructionSysvarId, slot_hashes::ID as SlotHashesId};
+
+#[constant]
+pub const MAX_BATCH_SIZE: u8 = 4;
+
+#[derive(Accounts)]
+pub struct ResolveBetsBatch<'info> {
+    pub resolver: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = resolver,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        seeds = [b"jackpot", house.key().as_ref(), &[config.game_id]],
+        bump = jackpot.bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+    #[account(
+        mut,
+        seeds = [b"jackpots", house.key().as_ref(), &[config.game_id]],
+        bump = jackpots.bump
+    )]
+    pub jackpots: Account<'info, Jackpots>,
+    #[account(
+        mut,
+        seeds = [b"fee_vault", house.key().as_ref(), &[config.game_id]],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+    #[account(
+        mut,
+        seeds = [b"treasury", house.key().as_ref(), &[config.game_id]],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    /// CHECK: SlotHashes sysvar, read by `Bet::outcome_slot_hash`.
+    #[account(address = SlotHashesId)]
+    pub slot_hashes: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar
+    #[account(
+        address = InstructionSysvarId
+    )]
+    pub instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResolveBetsBatch<'info> {
+    /// Remaining accounts start with one `[bet, player, stats, rent_payer]`
+    /// group per signature, in the same order as `sigs`, followed by any
+    /// referrer accounts. Bets staked from a `PlayerBalance` can't be batched.
+    pub fn resolve_bets_batch(
+        &mut self,
+        sigs: &[Vec<u8>],
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(
+            sigs.len() <= MAX_BATCH_SIZE as usize,
+            DiceError::BatchTooLarge
+        );
+        require!(
+            remaining_accounts.len() >= sigs.len() * 4,
+            DiceError::BatchAccountsMismatch
+        );
+
+        for (sig, accounts) in sigs.iter().zip(remaining_accounts.chunks_exact(4)) {
+            let (bet_info, player, stats_info, rent_payer) =
+                (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+
+            let bet = Account::<Bet>::try_from(bet_info)?;
+            require_keys_eq!(bet.player, player.key(), ErrorCode::ConstraintHasOne);
+            require_keys_eq!(bet.rent_payer, rent_payer.key(), DiceError::RentPayerMismatch);
+            let bet_address = Pubkey::create_program_address(
+                &[
+                    b"bet",
+                    self.vault.key().as_ref(),
+                    player.key().as_ref(),
+                    bet.seed.to_le_bytes().as_ref(),
+                    &[bet.bump],
+                ],
+                &crate::ID,
+            )
+            .map_err(|_| ErrorCode::ConstraintSeeds)?;
+            require_keys_eq!(bet_address, bet_info.key(), ErrorCode::ConstraintSeeds);
+
+            let mut stats = Account::<PlayerStats>::try_from(stats_info)?;
+            let stats_address = Pubkey::create_program_address(
+                &[b"stats", self.house.key().as_ref(), &[self.config.game_id], player.key().as_ref(), &[stats.bump]],
+                &crate::ID,
+            )
+            .map_err(|_| ErrorCode::ConstraintSeeds)?;
+            require_keys_eq!(stats_address, stats_info.key(), ErrorCode::ConstraintSeeds);
+
+            require!(
+                bet.randomness_source == RandomnessSource::Signature,
+                DiceError::RandomnessSourceMismatch
+            );
+            verify_ed25519_signature(&self.instructions, &player.key(), &bet, sig)?;
+            verify_resolver_quorum(&self.instructions, &self.config, &bet)?;
+            verify_resolution_window(&self.instructions, &self.config, &bet)?;
+            let slot_hash = bet.outcome_slot_hash(&self.config, self.slot_hashes.as_deref())?;
+
+            Settlement {
+                house: &self.house,
+                vault: &self.vault,
+                config: &mut self.config,
+                jackpot: &mut self.jackpot,
+                jackpots: &mut self.jackpots,
+                fee_vault: &self.fee_vault,
+                treasury: &self.treasury,
+                price_update: None,
+                keeper: None,
+                player_balance: None,
+                pending_rent_payer: self.resolver.key(),
+                system_program: &self.system_program,
+            }
+            .settle(
+                &bet,
+                player.clone(),
+                &mut stats,
+                None,
+                bet.rolls(sig, &slot_hash),
+                remaining_accounts,
+            )?;
+
+            stats.exit(&crate::ID)?;
+            bet.close(rent_payer.clone())?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+use crate::state::HouseConfig;
+
+#[derive(Accounts)]
+pub struct RotateResolver<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> RotateResolver<'info> {
+    pub fn rotate_resolver(&mut self, resolver: Pubkey) -> Result<()> {
+        self.config.resolver = resolver;
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{errors::DiceError, state::{HouseConfig, Jackpot}};
+
+#[derive(Accounts)]
+pub struct SeedJackpot<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        seeds = [b"jackpot", house.key().as_ref(), &[config.game_id]],
+        bump = jackpot.bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SeedJackpot<'info> {
+    /// Adds `amount` to the jackpot, paid into the vault by the authority so
+    /// the bankroll backing bets is left untouched.
+    pub fn seed_jackpot(&mut self, amount: u64) -> Result<()> {
+        let accounts = Transfer {
+            from: self.authority.to_account_info(),
+            to: self.vault.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            accounts
+        );
+        transfer(ctx, amount)?;
+
+        self.jackpot.balance = self
+            .jackpot
+            .balance
+            .checked_add(amount)
+            .ok_or(DiceError::Overflow)?;
+        Ok(())
+    }
+}
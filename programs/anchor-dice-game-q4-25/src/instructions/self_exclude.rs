@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, state::SelfExclusion};
+
+#[derive(Accounts)]
+pub struct SelfExclude<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + SelfExclusion::INIT_SPACE,
+        seeds = [b"self_exclusion", player.key().as_ref()],
+        bump
+    )]
+    pub self_exclusion: Account<'info, SelfExclusion>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SelfExclude<'info> {
+    /// Bars the player from placing bets with any house until `until_slot`.
+    /// An existing exclusion can be extended but never shortened.
+    pub fn self_exclude(&mut self, bumps: &SelfExcludeBumps, until_slot: u64) -> Result<()> {
+        require!(
+            until_slot >= self.self_exclusion.until_slot,
+            DiceError::SelfExclusionShortened
+        );
+        self.self_exclusion.set_inner(SelfExclusion {
+            player: self.player.key(),
+            until_slot,
+            bump: bumps.self_exclusion,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+use crate::state::HouseConfig;
+
+#[derive(Accounts)]
+pub struct SetBonusRollOffset<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> SetBonusRollOffset<'info> {
+    pub fn set_bonus_roll_offset(&mut self, offset: i8) -> Result<()> {
+        self.config.bonus_roll_offset = offset;
+        Ok(())
+    }
+}
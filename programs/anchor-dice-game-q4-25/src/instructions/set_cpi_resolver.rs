@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::state::HouseConfig;
+
+#[derive(Accounts)]
+pub struct SetCpiResolver<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> SetCpiResolver<'info> {
+    /// `None` turns `resolve_bet_cpi` off for this house.
+    pub fn set_cpi_resolver(&mut self, cpi_resolver: Option<Pubkey>) -> Result<()> {
+        self.config.cpi_resolver = cpi_resolver;
+        Ok(())
+    }
+}
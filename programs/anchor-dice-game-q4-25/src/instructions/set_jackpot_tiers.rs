@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::DiceError,
+    state::{HouseConfig, JackpotTierArgs, Jackpots},
+};
+
+#[derive(Accounts)]
+pub struct SetJackpotTiers<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        seeds = [b"jackpots", house.key().as_ref(), &[config.game_id]],
+        bump = jackpots.bump
+    )]
+    pub jackpots: Account<'info, Jackpots>,
+}
+
+impl<'info> SetJackpotTiers<'info> {
+    /// Sets each tier's trigger and contribution, keeping its balance. Every
+    /// enabled tier must trigger on strictly fewer rolls than the one below
+    /// it, and the tiers and the single jackpot together can take at most
+    /// the whole house fee.
+    pub fn set_jackpot_tiers(&mut self, tiers: [JackpotTierArgs; 3]) -> Result<()> {
+        let mut total_bps = self.config.jackpot_contribution_bps as u32;
+        let mut previous_trigger = u8::MAX;
+        for tier in &tiers {
+            require!(tier.trigger_roll <= 100, DiceError::InvalidJackpotTiers);
+            if tier.trigger_roll > 0 {
+                require!(
+                    tier.trigger_roll < previous_trigger,
+                    DiceError::InvalidJackpotTiers
+                );
+                previous_trigger = tier.trigger_roll;
+            }
+            total_bps += tier.contribution_bps as u32;
+        }
+        require!(total_bps <= 10_000, DiceError::FeeTooHigh);
+
+        for (tier, args) in self.jackpots.tiers.iter_mut().zip(tiers) {
+            tier.trigger_roll = args.trigger_roll;
+            tier.contribution_bps = args.contribution_bps;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, state::{HouseConfig, MAX_RESOLVERS}};
+
+#[derive(Accounts)]
+pub struct SetResolverSet<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> SetResolverSet<'info> {
+    /// Replaces the resolver quorum. Unused slots hold the default key; the
+    /// remaining keys must be distinct and at least `threshold` of them. A
+    /// threshold of 0 turns the quorum off.
+    pub fn set_resolver_set(&mut self, resolvers: [Pubkey; MAX_RESOLVERS], threshold: u8) -> Result<()> {
+        let mut members = 0;
+        for (index, resolver) in resolvers.iter().enumerate() {
+            if *resolver == Pubkey::default() {
+                continue;
+            }
+            require!(!resolvers[..index].contains(resolver), DiceError::InvalidResolverSet);
+            members += 1;
+        }
+        require!(threshold <= members, DiceError::InvalidResolverSet);
+
+        self.config.resolver_set = resolvers;
+        self.config.resolver_threshold = threshold;
+        Ok(())
+    }
+}
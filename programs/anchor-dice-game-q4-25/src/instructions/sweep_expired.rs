@@ -0,0 +1,132 @@
+use anchor_lang::{
+    error::ErrorCode,
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{
+    errors::DiceError,
+    state::{Bet, HouseConfig, PlayerBalance, PlayerStats},
+    utils::require_vault_rent_exempt,
+};
+
+#[constant]
+pub const MAX_SWEEP_SIZE: u8 = 8;
+
+#[derive(Accounts)]
+pub struct SweepExpired<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SweepExpired<'info> {
+    /// Refunds and closes bets nobody refunded after expiry. Remaining
+    /// accounts are one `[bet, player, stats, rent_payer]` group per bet, the
+    /// player and rent payer writable; each player gets back their stake and
+    /// each rent payer the bet's rent. For a bet staked from a
+    /// `PlayerBalance`, that balance takes the player's place and is credited
+    /// instead, as in `refund_bet`.
+    pub fn sweep_expired(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(
+            remaining_accounts.len().is_multiple_of(4),
+            DiceError::BatchAccountsMismatch
+        );
+        require!(
+            remaining_accounts.len() / 4 <= MAX_SWEEP_SIZE as usize,
+            DiceError::BatchTooLarge
+        );
+
+        let slot = Clock::get()?.slot;
+        for accounts in remaining_accounts.chunks_exact(4) {
+            let (bet_info, player, stats_info, rent_payer) =
+                (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+
+            let bet = Account::<Bet>::try_from(bet_info)?;
+            if bet.from_balance {
+                self.require_player_balance(&bet.player, player)?;
+            } else {
+                require_keys_eq!(bet.player, player.key(), ErrorCode::ConstraintHasOne);
+            }
+            require_keys_eq!(bet.rent_payer, rent_payer.key(), DiceError::RentPayerMismatch);
+            let bet_address = Pubkey::create_program_address(
+                &[
+                    b"bet",
+                    self.vault.key().as_ref(),
+                    bet.player.as_ref(),
+                    bet.seed.to_le_bytes().as_ref(),
+                    &[bet.bump],
+                ],
+                &crate::ID,
+            )
+            .map_err(|_| ErrorCode::ConstraintSeeds)?;
+            require_keys_eq!(bet_address, bet_info.key(), ErrorCode::ConstraintSeeds);
+
+            let mut stats = Account::<PlayerStats>::try_from(stats_info)?;
+            let stats_address = Pubkey::create_program_address(
+                &[b"stats", self.house.key().as_ref(), &[self.config.game_id], bet.player.as_ref(), &[stats.bump]],
+                &crate::ID,
+            )
+            .map_err(|_| ErrorCode::ConstraintSeeds)?;
+            require_keys_eq!(stats_address, stats_info.key(), ErrorCode::ConstraintSeeds);
+
+            require!(bet.cached_outcome.is_none(), DiceError::BetAlreadySettled);
+            require!(bet.mint.is_none(), DiceError::MintMismatch);
+            // Their stake goes back to the player's free credits, which
+            // `refund_bet` takes.
+            require!(!bet.free_credit, DiceError::FreeCreditsMismatch);
+            require!(slot > bet.expiry_slot, DiceError::BetNotExpired);
+
+            self.config.release_reservation(bet.potential_payout)?;
+            stats.release_open_bet();
+            self.refund(player.clone(), bet.amount)?;
+
+            stats.exit(&crate::ID)?;
+            bet.close(rent_payer.clone())?;
+        }
+
+        Ok(())
+    }
+
+    fn require_player_balance(&self, player: &Pubkey, balance_info: &'info AccountInfo<'info>) -> Result<()> {
+        let balance = Account::<PlayerBalance>::try_from(balance_info)?;
+        require_keys_eq!(balance.player, *player, ErrorCode::ConstraintHasOne);
+        let balance_address = Pubkey::create_program_address(
+            &[b"balance", self.house.key().as_ref(), &[self.config.game_id], player.as_ref(), &[balance.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(balance_address, balance_info.key(), ErrorCode::ConstraintSeeds);
+        Ok(())
+    }
+
+    fn refund(&self, player: AccountInfo<'info>, amount: u64) -> Result<()> {
+        require_vault_rent_exempt(self.vault.lamports(), amount)?;
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.vault.to_account_info(),
+                to: player,
+            },
+            signer_seeds,
+        );
+        transfer(ctx, amount)
+    }
+}
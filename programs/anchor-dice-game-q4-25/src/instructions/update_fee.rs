@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, state::HouseConfig};
+
+#[derive(Accounts)]
+pub struct UpdateFee<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> UpdateFee<'info> {
+    pub fn update_fee(&mut self, fee_bps: u16) -> Result<()> {
+        HouseConfig::validate_fee_tiers(fee_bps, &self.config.fee_tiers)?;
+        self.config.fee_bps = fee_bps;
+        Ok(())
+    }
+}
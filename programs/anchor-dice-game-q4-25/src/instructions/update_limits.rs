@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, events::LimitsUpdated, state::HouseConfig};
+
+#[derive(Accounts)]
+pub struct UpdateLimits<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+}
+
+impl<'info> UpdateLimits<'info> {
+    pub fn update_limits(&mut self, min_bet: u64, max_bet: u64) -> Result<()> {
+        require!(min_bet <= max_bet, DiceError::InvalidBetLimits);
+        require!(
+            max_bet <= self.config.max_payout,
+            DiceError::MaxBetExceedsPayoutCap
+        );
+        self.config.min_bet = min_bet;
+        self.config.max_bet = max_bet;
+
+        emit!(LimitsUpdated { min_bet, max_bet });
+        Ok(())
+    }
+}
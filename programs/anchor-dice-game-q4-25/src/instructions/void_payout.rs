@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::DiceError,
+    state::{HouseConfig, PendingPayout},
+};
+
+#[derive(Accounts)]
+pub struct VoidPayout<'info> {
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [b"pending_payout", vault.key().as_ref(), pending_payout.player.as_ref(), pending_payout.seed.to_le_bytes().as_ref()],
+        bump = pending_payout.bump
+    )]
+    pub pending_payout: Account<'info, PendingPayout>,
+    /// CHECK: gets the pending payout's rent back; must be whoever paid it
+    #[account(mut, address = pending_payout.rent_payer @ DiceError::RentPayerMismatch)]
+    pub rent_payer: UncheckedAccount<'info>,
+}
+
+impl<'info> VoidPayout<'info> {
+    /// Cancels a suspicious payout during its challenge window. The escrowed
+    /// funds never left the vault, so releasing the reservation returns them
+    /// to the house.
+    pub fn void_payout(&mut self) -> Result<()> {
+        require!(
+            Clock::get()?.slot < self.pending_payout.release_slot,
+            DiceError::ChallengeWindowClosed
+        );
+        self.config.release_reservation(self.pending_payout.amount)
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, state::{HouseConfig, PlayerBalance}};
+
+#[derive(Accounts)]
+pub struct WithdrawBalance<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"balance", house.key().as_ref(), &[config.game_id], player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Account<'info, PlayerBalance>,
+}
+
+impl<'info> WithdrawBalance<'info> {
+    pub fn withdraw_balance(&mut self, amount: u64) -> Result<()> {
+        let player_balance = self.player_balance.to_account_info();
+        require!(
+            amount <= PlayerBalance::available(&player_balance)?,
+            DiceError::InsufficientDepositBalance
+        );
+
+        player_balance.sub_lamports(amount)?;
+        self.player.add_lamports(amount)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, state::{FeeVault, HouseConfig}};
+
+/// Pays the current authority, as `withdraw_house` does.
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        mut,
+        seeds = [b"fee_vault", house.key().as_ref(), &[config.game_id]],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+}
+
+impl<'info> WithdrawFees<'info> {
+    pub fn withdraw_fees(&mut self, amount: u64) -> Result<()> {
+        let fee_vault = self.fee_vault.to_account_info();
+        let rent = Rent::get()?.minimum_balance(fee_vault.data_len());
+        let available = fee_vault.lamports().saturating_sub(rent);
+        require!(amount <= available, DiceError::InsufficientFeeBalance);
+
+        fee_vault.sub_lamports(amount)?;
+        self.authority.add_lamports(amount)?;
+        Ok(())
+    }
+}
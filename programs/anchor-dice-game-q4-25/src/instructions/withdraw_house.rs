@@ -0,0 +1,98 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{errors::DiceError, state::{HouseConfig, Jackpot, Jackpots}, utils::require_vault_rent_exempt};
+
+/// Withdrawals go to the current authority: `house` only seeds the game's
+/// accounts and stays the original owner after a handover.
+#[derive(Accounts)]
+pub struct WithdrawHouse<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    ///CHECK: This is safe
+    pub house: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", config.house_id.as_ref(), &[config.game_id]],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", house.key().as_ref(), &[config.game_id]],
+        bump = config.bump
+    )]
+    pub config: Account<'info, HouseConfig>,
+    #[account(
+        seeds = [b"jackpot", house.key().as_ref(), &[config.game_id]],
+        bump = jackpot.bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+    #[account(
+        seeds = [b"jackpots", house.key().as_ref(), &[config.game_id]],
+        bump = jackpots.bump
+    )]
+    pub jackpots: Account<'info, Jackpots>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawHouse<'info> {
+    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+        require!(
+            self.config.withdrawal_timelock_slots == 0,
+            DiceError::WithdrawalStillLocked
+        );
+        self.transfer_unreserved(amount)
+    }
+
+    /// Pays out the request once its timelock has passed. Open bets keep
+    /// priority: the amount must still be unreserved when it is claimed.
+    pub fn claim_withdrawal(&mut self) -> Result<()> {
+        let amount = self.config.pending_withdrawal;
+        require!(amount > 0, DiceError::NoPendingWithdrawal);
+        require!(
+            Clock::get()?.slot >= self.config.withdrawal_unlock_slot,
+            DiceError::WithdrawalStillLocked
+        );
+        self.config.pending_withdrawal = 0;
+        self.transfer_unreserved(amount)
+    }
+
+    fn transfer_unreserved(&self, amount: u64) -> Result<()> {
+        let remaining = self
+            .vault
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(DiceError::InsufficientUnreservedFunds)?;
+        let locked = self
+            .config
+            .reserved_lamports
+            .checked_add(self.jackpot.balance)
+            .and_then(|locked| locked.checked_add(self.jackpots.total()))
+            .ok_or(DiceError::Overflow)?;
+        require!(
+            remaining >= locked,
+            DiceError::InsufficientUnreservedFunds
+        );
+
+        require_vault_rent_exempt(self.vault.lamports(), amount)?;
+        let accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.authority.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]] =
+            &[&[b"vault", self.config.house_id.as_ref(), &[self.config.game_id], &[self.config.vault_bump]]];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds,
+        );
+
+        transfer(ctx, amount)
+    }
+}
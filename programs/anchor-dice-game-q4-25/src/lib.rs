@@ -1,9 +1,15 @@
 pub mod errors;
+pub mod events;
 pub mod instructions;
+pub mod randomness;
 pub mod state;
+pub mod utils;
 
 use anchor_lang::prelude::*;
 
+#[cfg(all(feature = "test-randomness", feature = "mainnet"))]
+compile_error!("the test-randomness feature must never be enabled in a mainnet build");
+
 pub use instructions::*;
 pub use state::*;
 
@@ -13,22 +19,257 @@ declare_id!("DCuZEURtt8gjXUsRTc5v9xNrua1qNRZ9Laz7RYR1Bnn2");
 pub mod anchor_dice_2026 {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, amount: u64) -> Result<()> {
+    pub fn initialize(ctx: Context<Initialize>, _game_id: u8, amount: u64) -> Result<()> {
         ctx.accounts.init(amount)
     }
 
-    pub fn place_bet(ctx: Context<PlaceBet>, seed: u128, roll: u8, amount: u64) -> Result<()> {
-        ctx.accounts.create_bet(&ctx.bumps, seed, roll, amount)?;
-        ctx.accounts.deposit(amount)
+    pub fn fund_vault(ctx: Context<FundVault>, _game_id: u8, amount: u64) -> Result<()> {
+        ctx.accounts.fund_vault(amount)
+    }
+
+    pub fn place_bet(ctx: Context<PlaceBet>, seed: u128, args: BetArgs) -> Result<()> {
+        ctx.accounts.create_bet(&ctx.bumps, seed, args)?;
+        ctx.accounts.deposit()
+    }
+
+    pub fn place_next_bet(ctx: Context<PlaceNextBet>, args: BetArgs) -> Result<()> {
+        ctx.accounts.create_bet(&ctx.bumps, args)?;
+        ctx.accounts.deposit()
+    }
+
+    pub fn place_bet_signed(ctx: Context<PlaceBetSigned>, seed: u128, args: BetArgs, sig: Vec<u8>) -> Result<()> {
+        ctx.accounts.verify_placement(seed, &args, &sig)?;
+        ctx.accounts.create_bet(&ctx.bumps, seed, args)?;
+        ctx.accounts.deposit()
+    }
+
+    pub fn deposit_balance(ctx: Context<DepositBalance>, amount: u64) -> Result<()> {
+        ctx.accounts.deposit_balance(&ctx.bumps, amount)
+    }
+
+    pub fn withdraw_balance(ctx: Context<WithdrawBalance>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw_balance(amount)
+    }
+
+    pub fn self_exclude(ctx: Context<SelfExclude>, until_slot: u64) -> Result<()> {
+        ctx.accounts.self_exclude(&ctx.bumps, until_slot)
+    }
+
+    pub fn grant_free_credit(ctx: Context<GrantFreeCredit>, amount: u64) -> Result<()> {
+        ctx.accounts.grant_free_credit(&ctx.bumps, amount)
+    }
+
+    pub fn resolve_bet<'info>(ctx: Context<'_, '_, '_, 'info, ResolveBet<'info>>, sig: Vec<u8>) -> Result<ResolveOutcome> {
+        ctx.accounts.verify_ed25519_signature(&sig)?;
+        ctx.accounts.verify_resolver_quorum()?;
+        ctx.accounts.verify_resolution_window()?;
+        ctx.accounts.resolve_bet(&sig, ctx.remaining_accounts)
+    }
+
+    #[cfg(feature = "test-randomness")]
+    pub fn resolve_bet_with_roll<'info>(ctx: Context<'_, '_, '_, 'info, ResolveBet<'info>>, roll: u8) -> Result<ResolveOutcome> {
+        ctx.accounts.resolve_bet_with_roll(roll, ctx.remaining_accounts)
+    }
+
+    pub fn resolve_bets_batch<'info>(ctx: Context<'_, '_, 'info, 'info, ResolveBetsBatch<'info>>, sigs: Vec<Vec<u8>>) -> Result<()> {
+        ctx.accounts.resolve_bets_batch(&sigs, ctx.remaining_accounts)
+    }
+
+    pub fn resolve_bet_from_source<'info>(ctx: Context<'_, '_, '_, 'info, ResolveBet<'info>>, sig: Vec<u8>) -> Result<ResolveOutcome> {
+        ctx.accounts.verify_resolver_quorum()?;
+        ctx.accounts.verify_resolution_window()?;
+        ctx.accounts.resolve_bet_from_source(&sig, ctx.remaining_accounts)
+    }
+
+    pub fn reveal_and_resolve<'info>(ctx: Context<'_, '_, '_, 'info, ResolveBet<'info>>, reveal: [u8; 32], house_seed: [u8; 32]) -> Result<ResolveOutcome> {
+        ctx.accounts.verify_commit(&reveal)?;
+        ctx.accounts.verify_resolver_quorum()?;
+        ctx.accounts.verify_resolution_window()?;
+        ctx.accounts.reveal_and_resolve(reveal, house_seed, ctx.remaining_accounts)
+    }
+
+    pub fn resolve_bet_cpi<'info>(ctx: Context<'_, '_, '_, 'info, ResolveBetCpi<'info>>, randomness: [u8; 32]) -> Result<ResolveOutcome> {
+        ctx.accounts.resolve_bet_cpi(randomness, ctx.remaining_accounts)
     }
 
-    pub fn resolve_bet(ctx: Context<ResolveBet>, sig: Vec<u8>) -> Result<()> {
+    pub fn close_settled_bet(ctx: Context<CloseSettledBet>) -> Result<()> {
+        ctx.accounts.close_settled_bet()
+    }
+
+    pub fn keeper_resolve_bet<'info>(ctx: Context<'_, '_, '_, 'info, KeeperResolveBet<'info>>, sig: Vec<u8>, resolver_sig: Vec<u8>) -> Result<ResolveOutcome> {
+        ctx.accounts.keeper_resolve_bet(&sig, &resolver_sig, ctx.remaining_accounts)
+    }
+
+    pub fn presign_bet(ctx: Context<PresignBet>, sig: Vec<u8>) -> Result<()> {
+        ctx.accounts.presign_bet(&sig)
+    }
+
+    pub fn resolve_bet_auto<'info>(ctx: Context<'_, '_, '_, 'info, ResolveBetAuto<'info>>) -> Result<ResolveOutcome> {
+        ctx.accounts.resolve_bet_auto(ctx.remaining_accounts)
+    }
+
+    pub fn request_randomness(ctx: Context<RequestRandomness>) -> Result<()> {
+        ctx.accounts.request_randomness()
+    }
+
+    pub fn consume_randomness<'info>(ctx: Context<'_, '_, '_, 'info, ConsumeRandomness<'info>>) -> Result<()> {
+        ctx.accounts.consume_randomness(ctx.remaining_accounts)
+    }
+
+    pub fn place_bet_spl(ctx: Context<PlaceBetSpl>, seed: u128, args: BetArgs) -> Result<()> {
+        ctx.accounts.create_bet(&ctx.bumps, seed, args)?;
+        ctx.accounts.deposit()
+    }
+
+    pub fn resolve_bet_spl(ctx: Context<ResolveBetSpl>, sig: Vec<u8>) -> Result<()> {
         ctx.accounts.verify_ed25519_signature(&sig)?;
-        ctx.accounts.resolve_bet(&sig, &ctx.bumps)
+        ctx.accounts.verify_resolver_quorum()?;
+        ctx.accounts.verify_resolution_window()?;
+        ctx.accounts.resolve_bet(&sig)
+    }
+
+    pub fn resolve_bet_wsol(ctx: Context<ResolveBetWsol>, sig: Vec<u8>, unwrap: bool) -> Result<()> {
+        ctx.accounts.verify_ed25519_signature(&sig)?;
+        ctx.accounts.verify_resolver_quorum()?;
+        ctx.accounts.verify_resolution_window()?;
+        ctx.accounts.resolve_bet(&sig, unwrap)
+    }
+
+    pub fn cancel_bet_spl(ctx: Context<RefundBetSpl>) -> Result<()> {
+        ctx.accounts.cancel_bet()
+    }
+
+    pub fn refund_bet_spl(ctx: Context<RefundBetSpl>) -> Result<()> {
+        ctx.accounts.refund_bet()
     }
 
     pub fn refund_bet(ctx: Context<RefundBet>) -> Result<()> {
-        ctx.accounts.refund_bet(&ctx.bumps)
+        ctx.accounts.refund_bet()
+    }
+
+    pub fn cancel_bet(ctx: Context<CancelBet>) -> Result<()> {
+        ctx.accounts.cancel_bet()
+    }
+
+    pub fn claim_payout(ctx: Context<ClaimPayout>) -> Result<()> {
+        ctx.accounts.claim_payout()
+    }
+
+    pub fn void_payout(ctx: Context<VoidPayout>) -> Result<()> {
+        ctx.accounts.void_payout()
+    }
+
+    pub fn emergency_refund(ctx: Context<EmergencyRefundBet>) -> Result<()> {
+        ctx.accounts.emergency_refund()
+    }
+
+    pub fn sweep_expired<'info>(ctx: Context<'_, '_, 'info, 'info, SweepExpired<'info>>) -> Result<()> {
+        ctx.accounts.sweep_expired(ctx.remaining_accounts)
+    }
+
+    pub fn reconcile_reserved<'info>(ctx: Context<'_, '_, 'info, 'info, ReconcileReserved<'info>>, reserved: Option<u64>) -> Result<()> {
+        ctx.accounts.reconcile_reserved(reserved, ctx.remaining_accounts)
+    }
+
+    pub fn cashout(ctx: Context<Cashout>, amount: u64, sig: Vec<u8>) -> Result<()> {
+        ctx.accounts.cashout(amount, &sig)
+    }
+
+    pub fn migrate_bet(ctx: Context<MigrateBet>) -> Result<()> {
+        ctx.accounts.migrate_bet()
+    }
+
+    #[cfg(feature = "test-randomness")]
+    pub fn create_legacy_bet(ctx: Context<CreateLegacyBet>, seed: u128, data: Vec<u8>) -> Result<()> {
+        ctx.accounts.create_legacy_bet(&ctx.bumps, seed, &data)
+    }
+
+    pub fn get_quote(ctx: Context<GetQuote>, amount: u64, roll: u8, direction: BetDirection, dice_count: u8, roll_low: u8, roll_high: u8) -> Result<u64> {
+        ctx.accounts.get_quote(amount, roll, direction, dice_count, (roll_low, roll_high))
+    }
+
+    pub fn get_house_edge(ctx: Context<GetHouseEdge>, amount: u64, roll: u8, direction: BetDirection, dice_count: u8, roll_low: u8, roll_high: u8) -> Result<i64> {
+        ctx.accounts.get_house_edge(amount, roll, direction, dice_count, (roll_low, roll_high))
+    }
+
+    pub fn initialize_config(ctx: Context<InitializeConfig>, args: HouseConfigArgs) -> Result<()> {
+        ctx.accounts.init_config(&ctx.bumps, args)
+    }
+
+    pub fn update_fee(ctx: Context<UpdateFee>, fee_bps: u16) -> Result<()> {
+        ctx.accounts.update_fee(fee_bps)
+    }
+
+    pub fn update_limits(ctx: Context<UpdateLimits>, min_bet: u64, max_bet: u64) -> Result<()> {
+        ctx.accounts.update_limits(min_bet, max_bet)
+    }
+
+    pub fn withdraw_house(ctx: Context<WithdrawHouse>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw(amount)
+    }
+
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, amount: u64) -> Result<()> {
+        ctx.accounts.request_withdrawal(amount)
+    }
+
+    pub fn claim_withdrawal(ctx: Context<WithdrawHouse>) -> Result<()> {
+        ctx.accounts.claim_withdrawal()
+    }
+
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw_fees(amount)
+    }
+
+    pub fn rebalance_from_treasury(ctx: Context<RebalanceFromTreasury>) -> Result<()> {
+        ctx.accounts.rebalance_from_treasury()
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        ctx.accounts.set_paused(paused)
+    }
+
+    pub fn set_bonus_roll_offset(ctx: Context<SetBonusRollOffset>, offset: i8) -> Result<()> {
+        ctx.accounts.set_bonus_roll_offset(offset)
+    }
+
+    pub fn rotate_resolver(ctx: Context<RotateResolver>, resolver: Pubkey) -> Result<()> {
+        ctx.accounts.rotate_resolver(resolver)
+    }
+
+    pub fn set_cpi_resolver(ctx: Context<SetCpiResolver>, cpi_resolver: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.set_cpi_resolver(cpi_resolver)
+    }
+
+    pub fn set_resolver_set(ctx: Context<SetResolverSet>, resolvers: [Pubkey; MAX_RESOLVERS], threshold: u8) -> Result<()> {
+        ctx.accounts.set_resolver_set(resolvers, threshold)
+    }
+
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>) -> Result<()> {
+        ctx.accounts.add_to_allowlist(&ctx.bumps)
+    }
+
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+        ctx.accounts.remove_from_allowlist()
+    }
+
+    pub fn seed_jackpot(ctx: Context<SeedJackpot>, amount: u64) -> Result<()> {
+        ctx.accounts.seed_jackpot(amount)
+    }
+
+    pub fn set_jackpot_tiers(ctx: Context<SetJackpotTiers>, tiers: [JackpotTierArgs; 3]) -> Result<()> {
+        ctx.accounts.set_jackpot_tiers(tiers)
+    }
+
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.propose_authority(new_authority)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        ctx.accounts.accept_authority()
+    }
+
+    pub fn close_game(ctx: Context<CloseGame>) -> Result<()> {
+        ctx.accounts.close_game()
     }
 
 
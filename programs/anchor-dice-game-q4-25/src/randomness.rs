@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use switchboard_on_demand::accounts::RandomnessAccountData;
+
+use crate::{
+    errors::DiceError,
+    instructions::{verify_ed25519_signature, CANCEL_WINDOW_SLOTS},
+    state::{Bet, HouseConfig},
+    utils::slot_hash_at,
+};
+
+/// Fewest slots after placement whose hash a bet is rolled on: the first
+/// slot the player can no longer cancel in, so seeing the hash never leaves
+/// them a way out of a loss. `reveal_delay_slots` must exceed it.
+#[constant]
+pub const SLOT_HASH_DELAY_SLOTS: u64 = CANCEL_WINDOW_SLOTS + 1;
+
+/// What a bet's `Rolls` are derived from: `input` hashed with `slot_hash`,
+/// as in `outcome_hash`, and published the same way in `OutcomeProof`.
+pub struct Entropy {
+    pub input: Vec<u8>,
+    pub slot_hash: [u8; 32],
+}
+
+/// One backend per `RandomnessSource`. Each checks that what it's handed is
+/// what the bet was committed to before returning its entropy, so the
+/// resolution paths only differ in which backend they build.
+pub trait RandomnessBackend {
+    fn entropy(&self, bet: &Bet, config: &HouseConfig) -> Result<Entropy>;
+}
+
+/// `RandomnessSource::Signature`: the player's signature over the bet
+/// message, verified against the ed25519 instruction before this one.
+pub struct Ed25519Sig<'a, 'info> {
+    pub instructions: &'a AccountInfo<'info>,
+    pub slot_hashes: Option<&'a AccountInfo<'info>>,
+    pub sig: &'a [u8],
+}
+
+impl RandomnessBackend for Ed25519Sig<'_, '_> {
+    fn entropy(&self, bet: &Bet, config: &HouseConfig) -> Result<Entropy> {
+        require_eq!(self.sig.len(), 64, DiceError::Ed25519SignatureLength);
+        verify_ed25519_signature(self.instructions, &bet.player, bet, self.sig)?;
+        Ok(Entropy {
+            input: self.sig.to_vec(),
+            slot_hash: bet.outcome_slot_hash(config, self.slot_hashes)?,
+        })
+    }
+}
+
+/// `RandomnessSource::SlotHash`: nothing but the bet's address and the hash
+/// of the first block from `HouseConfig::reveal_slot` on, like any other
+/// bet's outcome. Neither side can know it in time to act on it,
+/// but the leader producing that block could grind it, so it only suits
+/// stakes too small to be worth a validator's while.
+pub struct SlotHash<'a, 'info> {
+    pub bet_key: Pubkey,
+    pub slot_hashes: Option<&'a AccountInfo<'info>>,
+}
+
+impl RandomnessBackend for SlotHash<'_, '_> {
+    fn entropy(&self, bet: &Bet, config: &HouseConfig) -> Result<Entropy> {
+        let slot_hashes = self.slot_hashes.ok_or(DiceError::SlotHashesRequired)?;
+        Ok(Entropy {
+            input: self.bet_key.to_bytes().to_vec(),
+            slot_hash: slot_hash_at(slot_hashes, config.reveal_slot(bet.slot)?)?,
+        })
+    }
+}
+
+/// `RandomnessSource::Switchboard`: the revealed value of the randomness
+/// account `request_randomness` bound to the bet.
+pub struct SwitchboardVrf<'a, 'info> {
+    pub randomness_account: &'a AccountInfo<'info>,
+}
+
+impl RandomnessBackend for SwitchboardVrf<'_, '_> {
+    fn entropy(&self, bet: &Bet, _config: &HouseConfig) -> Result<Entropy> {
+        require_keys_eq!(
+            bet.randomness_account.ok_or(DiceError::InvalidRandomnessAccount)?,
+            self.randomness_account.key(),
+            DiceError::InvalidRandomnessAccount
+        );
+        let value = RandomnessAccountData::parse(self.randomness_account.data.borrow())
+            .map_err(|_| DiceError::InvalidRandomnessAccount)?
+            .get_value(&Clock::get()?)
+            .map_err(|_| DiceError::RandomnessNotResolved)?;
+        Ok(Entropy { input: value.to_vec(), slot_hash: bet.slot_hash })
+    }
+}
+
+/// `RandomnessSource::Beacon`: an external beacon account, whose layout
+/// isn't settled yet. Bets can't be placed with it until it is.
+pub struct GenericBeacon<'a, 'info> {
+    pub beacon: &'a AccountInfo<'info>,
+}
+
+impl RandomnessBackend for GenericBeacon<'_, '_> {
+    fn entropy(&self, _bet: &Bet, _config: &HouseConfig) -> Result<Entropy> {
+        err!(DiceError::RandomnessBackendUnsupported)
+    }
+}
@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+/// Marks a trusted integrator, such as a front-end or market maker, whose
+/// bets skip the house's bet cooldown. It exists only while the player is
+/// allowlisted; the authority closes it to remove them.
+#[account]
+#[derive(InitSpace)]
+pub struct Allowlisted {
+    pub player: Pubkey,
+    pub bump: u8
+}
@@ -0,0 +1,559 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+
+use crate::{errors::DiceError, instructions::ResolveOutcome, state::HouseConfig, utils::{apply_fee_floor, basis_odds, bet_odds, compute_face_roll, compute_leg_rolls, compute_outcome, compute_roll_u16, face_odds, parlay_odds, payout_for, slot_hash_at, D100_FACES}};
+
+#[constant]
+pub const BET_MESSAGE_VERSION: u8 = 11;
+
+/// Most extra predictions a parlay may add to the bet's own.
+pub const MAX_LEGS: usize = 3;
+
+/// Length of the signed bet message with neither `mint` nor `referrer` set
+/// and no parlay legs; each of the first two adds 32 bytes, each leg 2.
+const BET_MESSAGE_BASE_LEN: usize = 181;
+pub const MAX_BET_MESSAGE_LEN: usize = BET_MESSAGE_BASE_LEN + 64 + 2 * MAX_LEGS;
+
+/// Appended to a bet's message in `Bet::keeper_message`.
+#[constant]
+pub const KEEPER_MESSAGE_TAG: &[u8] = b"keeper";
+
+/// Appended to a bet's message in `Bet::quorum_message`.
+#[constant]
+pub const QUORUM_MESSAGE_TAG: &[u8] = b"quorum";
+
+/// Appended to a bet's message in `Bet::window_message`, ahead of the slots.
+#[constant]
+pub const WINDOW_MESSAGE_TAG: &[u8] = b"window";
+
+/// Stored in every `Bet` so `migrate_bet` can tell which mapping to apply.
+/// Bets from before the field existed carry no version and are recognised
+/// by their length instead. New fields go just before `layout_version`.
+#[constant]
+pub const BET_LAYOUT_VERSION: u8 = 13;
+
+/// A bet is only ever closed by an instruction that also settles or refunds
+/// it, and each of those is gated on a signer with a claim to it:
+///
+/// - resolution (`resolve_bet`, `resolve_bet_spl`, `resolve_bets_batch`,
+///   `consume_randomness`): the house's resolver, plus the player's signature
+///   over the bet; `keeper_resolve_bet` takes the resolver's signature in
+///   place of its signer, and `resolve_bet_cpi` the configured CPI resolver
+///   alongside the player
+/// - `cancel_bet`, `refund_bet`, `cashout`: the player
+/// - `emergency_refund`, `sweep_expired`: the house authority, only while
+///   paused or after expiry respectively
+///
+/// Rent always goes back to `rent_payer`, so no path lets a third party
+/// close someone's bet or redirect its lamports. Each of them closes the bet
+/// in the same instruction that settles it, so there's no resolved-but-open
+/// state and no rent left to reclaim afterwards; a retried settlement fails
+/// on the missing account instead. The one exception is `resolve_bet_cpi`
+/// under `cache_outcomes`, which leaves the settled bet open for retries
+/// until `close_settled_bet`.
+#[account]
+#[derive(InitSpace)]
+pub struct Bet {
+    pub player: Pubkey,
+    pub seed: u128,
+    /// Unique per house, so a signature for a closed bet can't be replayed
+    /// against a later bet with identical parameters.
+    pub nonce: u64,
+    /// Slot the bet was placed in, which `outcome_slot_hash` counts the
+    /// house's reveal delay from.
+    pub slot: u64,
+    /// Last slot at which the bet can be resolved; after it only a refund
+    /// is possible.
+    pub expiry_slot: u64,
+    pub amount: u64,
+    pub roll: u8,
+    pub bump : u8,
+    pub mint: Option<Pubkey>,
+    pub direction: BetDirection,
+    pub potential_payout: u64,
+    /// Number of d6 summed for the outcome; 0 rolls the classic d100.
+    pub dice_count: u8,
+    pub player_commit: [u8; 32],
+    pub referrer: Option<Pubkey>,
+    pub randomness_source: RandomnessSource,
+    /// Switchboard randomness account bound by `request_randomness`.
+    pub randomness_account: Option<Pubkey>,
+    /// A player's first bet may be placed risk-free: a loss refunds the stake.
+    pub risk_free: bool,
+    /// Inclusive bounds of a `Range` bet; unused by the other directions.
+    pub roll_low: u8,
+    pub roll_high: u8,
+    /// Pays out the payout's USD value at placement rather than a fixed
+    /// lamport amount, using the SOL/USD price recorded below.
+    pub usd_denominated: bool,
+    pub entry_price: i64,
+    pub entry_price_expo: i32,
+    /// Client reference (order id, campaign tag) for indexers. Not part of
+    /// the signed message, so it has no bearing on the outcome.
+    pub memo: [u8; 32],
+    /// Slots after `slot` within which the player's signature must be
+    /// submitted, fixed at placement and signed over; 0 leaves only the bet's
+    /// expiry.
+    pub signature_window_slots: u64,
+    /// Newest `SlotHashes` entry when the bet was placed. A Switchboard bet's
+    /// outcome mixes it in; every other one uses `outcome_slot_hash`.
+    pub slot_hash: [u8; 32],
+    /// Funded the bet account at placement, and gets its rent back when the
+    /// bet closes.
+    pub rent_payer: Pubkey,
+    /// Staked from the player's `PlayerBalance`, which its payouts go back to.
+    pub from_balance: bool,
+    /// Further predictions that must all win alongside the bet's own for it
+    /// to pay; only the first `leg_count` are used. Fixed-size so the account
+    /// layout doesn't depend on how many legs a bet has.
+    pub legs: [ParlayLeg; MAX_LEGS],
+    pub leg_count: u8,
+    /// `Basis` judges the bet on a 0..=9999 outcome against `roll_bps`
+    /// instead of a 1..=100 roll against `roll`.
+    pub precision: PrecisionMode,
+    pub roll_bps: u16,
+    /// Staked from the player's `FreeCredits`. Winnings go to the wallet as
+    /// usual, but a cancelled or refunded stake goes back to the credits and
+    /// the bet can't be cashed out, so a grant can't be turned into cash
+    /// without winning.
+    pub free_credit: bool,
+    /// Faces of the die a single-die `Percent` bet is judged on, 100 for
+    /// the classic d100; see `face_odds`.
+    pub faces: u16,
+    /// The house's pricing and judging rules when the bet was placed, which
+    /// it is settled under however the config changes before resolution.
+    pub terms: BetTerms,
+    /// The player's signature over the bet message, stored by `presign_bet`
+    /// for `resolve_bet_auto`; all zeroes until then.
+    pub presigned_sig: [u8; 64],
+    /// Entropy of the player's choosing, part of the signed message and so
+    /// of the signature `compute_outcome` hashes: a different seed gives a
+    /// different roll from the same bet. To audit a bet, a player fixes the
+    /// seed before placing (the hash of a phrase only they know, say), then
+    /// rebuilds the message from the bet, verifies the resolving signature
+    /// over it, and checks that signature hashed with
+    /// `OutcomeProof::slot_hash` gives `OutcomeProof::input_hash`.
+    pub client_seed: [u8; 32],
+    /// What `resolve_bet_cpi` settled the bet as, while the house's
+    /// `cache_outcomes` keeps it open afterwards; `None` for an open bet.
+    /// Every other path that pays out or refunds refuses a bet with one.
+    pub cached_outcome: Option<ResolveOutcome>,
+    /// `BET_LAYOUT_VERSION` at the time the account was written.
+    pub layout_version: u8,
+}
+
+// Bets are created through a system program CPI, which can't allocate more
+// than this.
+const _: () = assert!(Bet::LEN <= MAX_PERMITTED_DATA_INCREASE);
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BetArgs {
+    pub roll: u8,
+    pub amount: u64,
+    pub direction: BetDirection,
+    pub dice_count: u8,
+    pub player_commit: [u8; 32],
+    pub referrer: Option<Pubkey>,
+    pub risk_free: bool,
+    pub roll_low: u8,
+    pub roll_high: u8,
+    pub usd_denominated: bool,
+    pub memo: [u8; 32],
+    /// Turns the bet into a parlay; see `parlay_odds`. At most `MAX_LEGS`.
+    pub legs: Vec<ParlayLeg>,
+    pub precision: PrecisionMode,
+    /// Threshold of a `Basis` bet, in basis points; see `basis_odds`.
+    pub roll_bps: u16,
+    /// 100 for a d100 bet; anything else bets on that die; see `face_odds`.
+    pub faces: u16,
+    /// See `RandomnessSource::for_placement`.
+    pub randomness_source: RandomnessSource,
+    /// See `Bet::client_seed`; all zeroes if the player doesn't pick one.
+    pub client_seed: [u8; 32],
+}
+
+/// The parts of `HouseConfig` that decide what a bet pays and whether it
+/// wins, fixed into the bet at placement by `HouseConfig::bet_terms`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct BetTerms {
+    /// `fee_bps_for` the stake less the player's volume rebate, so a later
+    /// fee change, tier edit or rebate doesn't reprice the bet.
+    pub fee_bps: u16,
+    pub round_up_payouts: bool,
+    pub min_fee_lamports: u64,
+    pub player_wins_ties: bool,
+    pub bonus_roll_offset: i8,
+}
+
+/// One extra d100 prediction of a parlay, judged against its own roll from
+/// `compute_leg_rolls`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ParlayLeg {
+    pub roll: u8,
+    pub direction: BetDirection,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum BetDirection {
+    #[default]
+    Under,
+    Over,
+    /// Wins only when the outcome equals the chosen value.
+    Exact,
+    /// Wins when the outcome falls within `roll_low..=roll_high`.
+    Range,
+}
+
+/// How finely a bet's outcome is drawn. `Percent` is the classic 1..=100
+/// roll (or dice sum); `Basis` draws 0..=9999 from `compute_roll_u16`, for
+/// d100 Under and Over bets priced to the basis point.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum PrecisionMode {
+    #[default]
+    Percent,
+    Basis,
+}
+
+/// Everything a bet is judged on, all drawn from one outcome hash by
+/// `Bet::rolls`.
+#[derive(Clone, Copy)]
+pub struct Rolls {
+    /// The d100 roll or dice sum; for a `Basis` bet, the percentile of
+    /// `basis`, so jackpots and events see the usual 1..=100.
+    pub roll: u8,
+    /// The 0..=9999 outcome a `Basis` bet is judged on; 0 for other bets.
+    pub basis: u16,
+    /// The 1..=faces roll a bet on a die other than the d100 is judged on;
+    /// 0 for other bets. `roll` stays the d100, so jackpots trigger as often
+    /// whatever die was bet on.
+    pub face: u16,
+    pub legs: [u8; MAX_LEGS],
+}
+
+/// Where a bet's outcome comes from; each has a backend in `randomness`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum RandomnessSource {
+    /// The player's ed25519 signature over the bet message.
+    Signature,
+    /// The player's revealed secret and the house seed.
+    CommitReveal,
+    /// A Switchboard randomness account bound by `request_randomness`.
+    Switchboard,
+    /// The hash of a slot after the bet's cancel window.
+    SlotHash,
+    /// An external beacon account; not yet supported.
+    Beacon,
+}
+
+impl RandomnessSource {
+    pub fn for_commit(player_commit: &[u8; 32]) -> Self {
+        if *player_commit == [0; 32] {
+            RandomnessSource::Signature
+        } else {
+            RandomnessSource::CommitReveal
+        }
+    }
+
+    /// The source a bet is placed with when `requested`: `Signature` turns
+    /// into `CommitReveal` when the player commits to a secret, as it always
+    /// has. `Switchboard` is only ever switched to by `request_randomness`.
+    pub fn for_placement(requested: Self, player_commit: &[u8; 32]) -> Result<Self> {
+        let committed = *player_commit != [0; 32];
+        match requested {
+            RandomnessSource::Signature => Ok(Self::for_commit(player_commit)),
+            RandomnessSource::CommitReveal if committed => Ok(requested),
+            RandomnessSource::SlotHash if !committed => Ok(requested),
+            RandomnessSource::Beacon => err!(DiceError::RandomnessBackendUnsupported),
+            _ => err!(DiceError::RandomnessSourceMismatch),
+        }
+    }
+}
+
+impl BetDirection {
+    pub fn wins(&self, threshold: u8, (low, high): (u8, u8), roll: u8) -> bool {
+        match self {
+            BetDirection::Under => roll <= threshold,
+            BetDirection::Over => roll > threshold,
+            BetDirection::Exact => roll == threshold,
+            BetDirection::Range => (low..=high).contains(&roll),
+        }
+    }
+
+    /// `wins`, except that an Under bet landing exactly on its threshold
+    /// loses unless the house lets players win ties.
+    pub fn wins_at(&self, threshold: u8, range: (u8, u8), roll: u8, player_wins_ties: bool) -> bool {
+        if *self == BetDirection::Under && roll == threshold && !player_wins_ties {
+            return false;
+        }
+        self.wins(threshold, range, roll)
+    }
+
+    pub fn winning_outcomes(&self, threshold: u8, (low, high): (u8, u8)) -> u8 {
+        match self {
+            BetDirection::Under => threshold,
+            BetDirection::Over => 100 - threshold,
+            BetDirection::Exact => 1,
+            BetDirection::Range => high - low + 1,
+        }
+    }
+
+    /// The threshold after the house's `bonus_roll_offset`: an Under bet's
+    /// rises and an Over bet's falls by `offset`, clamped so the bet can
+    /// still both win and lose. Exact and Range bets are unaffected.
+    pub fn effective_threshold(&self, roll: u8, dice_count: u8, offset: i8) -> u8 {
+        let shift = match self {
+            BetDirection::Under => offset as i16,
+            BetDirection::Over => -(offset as i16),
+            BetDirection::Exact | BetDirection::Range => return roll,
+        };
+        let (min, max) = match dice_count {
+            0 => (2, 96),
+            dice => (dice as i16, 6 * dice as i16 - 1),
+        };
+        (roll as i16 + shift).clamp(min, max) as u8
+    }
+
+    /// `wins_at` for a roll of a die other than the d100, which can land
+    /// past a `u8`.
+    pub fn wins_face(&self, threshold: u8, face: u16, player_wins_ties: bool) -> bool {
+        let threshold = threshold as u16;
+        match self {
+            BetDirection::Under => face < threshold || (face == threshold && player_wins_ties),
+            BetDirection::Over => face > threshold,
+            BetDirection::Exact => face == threshold,
+            BetDirection::Range => false,
+        }
+    }
+
+    /// `wins` for a `Basis` bet: Under takes the `threshold` outcomes below
+    /// it and Over the rest, so there are no ties to break.
+    pub fn wins_basis(&self, threshold: u16, outcome: u16) -> bool {
+        match self {
+            BetDirection::Under => outcome < threshold,
+            BetDirection::Over => outcome >= threshold,
+            BetDirection::Exact | BetDirection::Range => false,
+        }
+    }
+
+    /// How far a roll landed from the nearest winning outcome's boundary;
+    /// only meaningful for a losing roll.
+    pub fn miss_distance(&self, threshold: u8, (low, high): (u8, u8), roll: u8) -> u8 {
+        match self {
+            BetDirection::Range => roll.abs_diff(low).min(roll.abs_diff(high)),
+            _ => roll.abs_diff(threshold),
+        }
+    }
+}
+
+impl Bet {
+    /// Size of a bet account: the discriminator plus the largest encoding of
+    /// the fields, with every `Option` set. Every bet `init` and
+    /// `migrate_bet` size the account from this alone.
+    pub const LEN: usize = 8 + Bet::INIT_SPACE;
+
+    /// Judges the bet's own prediction; see `legs_win` for a parlay's legs.
+    /// `bonus_roll_offset` only applies to d100 and dice-sum `Percent` bets,
+    /// and `player_wins_ties` to every `Percent` bet; both come from the
+    /// bet's `terms`.
+    pub fn is_winner(&self, rolls: &Rolls) -> bool {
+        let terms = &self.terms;
+        if self.faces != D100_FACES {
+            return self.direction.wins_face(self.roll, rolls.face, terms.player_wins_ties);
+        }
+        if self.precision == PrecisionMode::Basis {
+            return self.direction.wins_basis(self.roll_bps, rolls.basis);
+        }
+        let threshold = self.effective_roll(terms.bonus_roll_offset);
+        self.direction.wins_at(threshold, self.range(), rolls.roll, terms.player_wins_ties)
+    }
+
+    /// The bet's `Rolls` from `input` and the slot hash it's resolved with.
+    pub fn rolls(&self, input: &[u8], slot_hash: &[u8; 32]) -> Rolls {
+        let (roll, basis) = match self.precision {
+            PrecisionMode::Percent => (compute_outcome(input, slot_hash, self.dice_count), 0),
+            PrecisionMode::Basis => {
+                let basis = compute_roll_u16(input, slot_hash);
+                ((basis / 100 + 1) as u8, basis)
+            }
+        };
+        let face = match self.faces {
+            D100_FACES => 0,
+            faces => compute_face_roll(input, slot_hash, faces),
+        };
+        Rolls { roll, basis, face, legs: compute_leg_rolls(input, slot_hash) }
+    }
+
+    /// Whether every parlay leg holds against its roll; always true for a
+    /// plain bet. `bonus_roll_offset` only ever shifts the bet's own roll.
+    pub fn legs_win(&self, leg_rolls: &[u8; MAX_LEGS]) -> bool {
+        self.legs()
+            .iter()
+            .zip(leg_rolls)
+            .all(|(leg, roll)| leg.direction.wins_at(leg.roll, (0, 0), *roll, self.terms.player_wins_ties))
+    }
+
+    /// Slot hash mixed into the outcome: that of the first slot from
+    /// `HouseConfig::reveal_slot` on, which nobody could know when the bet
+    /// was placed nor while it could still be cancelled.
+    pub fn outcome_slot_hash(&self, config: &HouseConfig, slot_hashes: Option<&AccountInfo>) -> Result<[u8; 32]> {
+        let slot_hashes = slot_hashes.ok_or(DiceError::SlotHashesRequired)?;
+        slot_hash_at(slot_hashes, config.reveal_slot(self.slot)?)
+    }
+
+    pub fn legs(&self) -> &[ParlayLeg] {
+        &self.legs[..self.leg_count as usize]
+    }
+
+    /// Stores `legs`, already checked against `MAX_LEGS` by `parlay_odds`, in
+    /// the fixed-size `Bet::legs`.
+    pub fn pack_legs(legs: &[ParlayLeg]) -> [ParlayLeg; MAX_LEGS] {
+        let mut packed = [ParlayLeg::default(); MAX_LEGS];
+        packed[..legs.len()].copy_from_slice(legs);
+        packed
+    }
+
+    /// See `BetDirection::effective_threshold`.
+    pub fn effective_roll(&self, offset: i8) -> u8 {
+        self.direction.effective_threshold(self.roll, self.dice_count, offset)
+    }
+
+    /// See `BetDirection::miss_distance`.
+    pub fn miss_distance(&self, roll: u8, offset: i8) -> u8 {
+        self.direction.miss_distance(self.effective_roll(offset), self.range(), roll)
+    }
+
+    pub fn range(&self) -> (u8, u8) {
+        (self.roll_low, self.roll_high)
+    }
+
+    /// What the bet pays if it wins, priced under its `terms`; see
+    /// `apply_fee_floor` for their `min_fee_lamports`.
+    pub fn payout(&self) -> Result<u64> {
+        let BetTerms { fee_bps, round_up_payouts, min_fee_lamports, .. } = self.terms;
+        let odds = match self.precision {
+            PrecisionMode::Percent if self.faces != D100_FACES => face_odds(self.direction, self.roll, self.dice_count, self.faces)?,
+            PrecisionMode::Percent => bet_odds(self.direction, self.roll, self.range(), self.dice_count)?,
+            PrecisionMode::Basis => basis_odds(self.direction, self.roll_bps, self.dice_count)?,
+        };
+        let (winning_outcomes, total_outcomes) = parlay_odds(odds, self.legs())?;
+        let payout = payout_for(self.amount, winning_outcomes, total_outcomes, fee_bps, round_up_payouts)?;
+        apply_fee_floor(payout, self.amount, winning_outcomes, total_outcomes, min_fee_lamports)
+    }
+
+    /// The share of the stake distributed as the house fee under the bet's
+    /// `terms`, raised to their `min_fee_lamports` but never past the stake.
+    pub fn house_fee(&self) -> Result<u64> {
+        let fee = self
+            .amount
+            .checked_mul(self.terms.fee_bps as u64)
+            .ok_or(DiceError::Overflow)?
+            / 10_000;
+        Ok(fee.max(self.terms.min_fee_lamports).min(self.amount))
+    }
+
+    pub fn to_slice(&self) -> Vec<u8> {
+        let mut buf = [0; MAX_BET_MESSAGE_LEN];
+        let len = self.write_message(&mut buf);
+        buf[..len].to_vec()
+    }
+
+    /// What the resolver signs to offer buying the bet back for `amount`:
+    /// the bet's own message followed by the amount, so an offer is bound to
+    /// one bet and can never pass for the player's resolution signature.
+    pub fn cashout_message(&self, amount: u64) -> Vec<u8> {
+        let mut message = self.to_slice();
+        message.extend_from_slice(&amount.to_le_bytes());
+        message
+    }
+
+    /// What the resolver signs to let anyone resolve the bet as a keeper:
+    /// the bet's own message followed by a tag that no other message ends in.
+    pub fn keeper_message(&self) -> Vec<u8> {
+        let mut message = self.to_slice();
+        message.extend_from_slice(KEEPER_MESSAGE_TAG);
+        message
+    }
+
+    /// What each member of the house's resolver quorum signs to approve the
+    /// bet being resolved.
+    pub fn quorum_message(&self) -> Vec<u8> {
+        let mut message = self.to_slice();
+        message.extend_from_slice(QUORUM_MESSAGE_TAG);
+        message
+    }
+
+    /// What the resolver signs to vouch that the bet may be resolved from
+    /// `valid_from` to `valid_until`, both inclusive.
+    pub fn window_message(&self, valid_from: u64, valid_until: u64) -> Vec<u8> {
+        let mut message = self.to_slice();
+        message.extend_from_slice(WINDOW_MESSAGE_TAG);
+        message.extend_from_slice(&valid_from.to_le_bytes());
+        message.extend_from_slice(&valid_until.to_le_bytes());
+        message
+    }
+
+    /// Checks `message` against this bet's encoding without allocating: the
+    /// length alone rejects most mismatches before any bytes are written.
+    pub fn message_matches(&self, message: &[u8]) -> bool {
+        if message.len() != self.message_len() {
+            return false;
+        }
+        let mut buf = [0; MAX_BET_MESSAGE_LEN];
+        let len = self.write_message(&mut buf);
+        *message == buf[..len]
+    }
+
+    /// Length of the bet message, which varies with the optional mint,
+    /// referrer and parlay legs.
+    pub fn message_len(&self) -> usize {
+        BET_MESSAGE_BASE_LEN
+            + 32 * (self.mint.is_some() as usize + self.referrer.is_some() as usize)
+            + 2 * self.leg_count as usize
+    }
+
+    fn write_message(&self, buf: &mut [u8; MAX_BET_MESSAGE_LEN]) -> usize {
+        let mut len = 0;
+        let mut put = |bytes: &[u8]| {
+            buf[len..len + bytes.len()].copy_from_slice(bytes);
+            len += bytes.len();
+        };
+        put(&[BET_MESSAGE_VERSION]);
+        put(&self.player.to_bytes());
+        put(&self.seed.to_le_bytes());
+        put(&self.nonce.to_le_bytes());
+        put(&self.slot.to_le_bytes());
+        put(&self.expiry_slot.to_le_bytes());
+        put(&self.amount.to_le_bytes());
+        put(&[self.roll, self.bump]);
+        match self.mint {
+            Some(mint) => {
+                put(&[1]);
+                put(&mint.to_bytes());
+            }
+            None => put(&[0]),
+        }
+        put(&[self.direction as u8, self.dice_count]);
+        put(&[self.roll_low, self.roll_high]);
+        put(&self.player_commit);
+        match self.referrer {
+            Some(referrer) => {
+                put(&[1]);
+                put(&referrer.to_bytes());
+            }
+            None => put(&[0]),
+        }
+        put(&[self.risk_free as u8, self.usd_denominated as u8]);
+        put(&self.entry_price.to_le_bytes());
+        put(&self.entry_price_expo.to_le_bytes());
+        put(&self.signature_window_slots.to_le_bytes());
+        put(&[self.precision as u8]);
+        put(&self.roll_bps.to_le_bytes());
+        put(&self.faces.to_le_bytes());
+        put(&[self.leg_count]);
+        for leg in self.legs() {
+            put(&[leg.roll, leg.direction as u8]);
+        }
+        put(&self.client_seed);
+        len
+    }
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+/// Holds realized house profit; every lamport above rent is withdrawable.
+#[account]
+#[derive(InitSpace)]
+pub struct FeeVault {
+    pub bump: u8
+}
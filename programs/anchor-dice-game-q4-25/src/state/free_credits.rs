@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+/// Promotional credit the house has granted a player, held as lamports above
+/// the account's rent. A bet staked from it pays out to the player's wallet
+/// like any other; a lost one simply uses the credit up.
+#[account]
+#[derive(InitSpace)]
+pub struct FreeCredits {
+    pub player: Pubkey,
+    pub bump: u8
+}
+
+impl FreeCredits {
+    /// Unspent credit, i.e. everything above the account's rent.
+    pub fn available(info: &AccountInfo) -> Result<u64> {
+        let rent = Rent::get()?.minimum_balance(info.data_len());
+        Ok(info.lamports().saturating_sub(rent))
+    }
+}
@@ -0,0 +1,344 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, randomness::SLOT_HASH_DELAY_SLOTS, state::BetTerms};
+
+/// Most keys a house's resolver quorum may contain.
+pub const MAX_RESOLVERS: usize = 5;
+
+#[account]
+#[derive(InitSpace)]
+pub struct HouseConfig {
+    /// The key the house was created with. The vault is seeded from this
+    /// rather than from whichever key currently controls the house, so
+    /// rotating the authority or resolver leaves the vault and its open
+    /// bets where they are.
+    pub house_id: Pubkey,
+    /// Tells apart independent games run by the same house. Every PDA of the
+    /// game is seeded with it after the house, so each game has its own
+    /// vault, bankroll, config and player accounts.
+    pub game_id: u8,
+    pub authority: Pubkey,
+    /// Proposed successor to `authority`; it has no admin rights until it
+    /// signs `accept_authority`.
+    pub pending_authority: Option<Pubkey>,
+    /// Hot key allowed to resolve bets; it cannot move funds or change config.
+    pub resolver: Pubkey,
+    /// Signer, usually another program's PDA, allowed to resolve bets through
+    /// `resolve_bet_cpi`; `None` disables it.
+    pub cpi_resolver: Option<Pubkey>,
+    pub fee_bps: u16,
+    /// Reduced fees for larger bets; see `fee_bps_for`.
+    pub fee_tiers: [FeeTier; 4],
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub reserved_lamports: u64,
+    pub referral_bps: u16,
+    pub jackpot_contribution_bps: u16,
+    /// Share of each lost stake, in bps, moved to the treasury.
+    pub insurance_bps: u16,
+    /// Vault balance below which `rebalance_from_treasury` may top it up.
+    pub rebalance_threshold: u64,
+    pub paused: bool,
+    /// Largest payout a single bet may be placed for.
+    pub max_payout: u64,
+    /// Largest share of the vault a single bet's payout may put at risk.
+    pub max_exposure_bps: u16,
+    /// Number of bets ever placed against this house; stamps each bet with a
+    /// nonce that never recurs.
+    pub bet_count: u64,
+    /// Bonus added per consecutive prior win, as bps of the payout.
+    pub streak_bonus_bps: u16,
+    pub max_streak_bonus_bps: u16,
+    pub risk_free_enabled: bool,
+    /// Rounds payouts up to the next lamport instead of down.
+    pub round_up_payouts: bool,
+    /// Disclosed promotional shift of Under/Over thresholds for bets placed
+    /// while it is set, in the player's favour when positive; emitted with
+    /// every `BetResolved`.
+    pub bonus_roll_offset: i8,
+    /// Whether an Under bet wins when the outcome equals its threshold. Over
+    /// bets always lose on the threshold. Turning this off leaves payouts
+    /// unchanged but removes one winning outcome, so a d100 Under bet at
+    /// threshold `t` keeps only `(t - 1) / t` of its expected return.
+    pub player_wins_ties: bool,
+    /// Minimum slots between two bets from the same player; 0 disables it.
+    pub bet_cooldown_slots: u64,
+    /// Wins paying at least this much are escrowed in a `PendingPayout`
+    /// instead of paid immediately; 0 pays every win immediately.
+    pub large_win_threshold: u64,
+    /// Slots an escrowed payout waits before the player can claim it.
+    pub challenge_slots: u64,
+    /// Most bets one player may have open at once; 0 is unlimited.
+    pub max_open_bets_per_player: u32,
+    /// Pyth feed id of the SOL/USD price used to settle USD-denominated bets.
+    pub sol_usd_feed_id: [u8; 32],
+    /// Oldest SOL/USD price, in seconds, a USD-denominated bet may use.
+    pub max_price_age_secs: u64,
+    /// A loss landing within this many outcomes of the player's threshold
+    /// returns `near_miss_bps` of the stake; 0 disables it.
+    pub near_miss_window: u8,
+    pub near_miss_bps: u16,
+    /// Copied onto each new bet; see `Bet::signature_window_slots`.
+    pub signature_window_slots: u64,
+    /// Mints `reward_rate` of `reward_mint` per lamport wagered to the player
+    /// at resolution; the vault must be the mint's authority.
+    pub rewards_enabled: bool,
+    pub reward_mint: Option<Pubkey>,
+    pub reward_rate: u64,
+    /// Share of the house fee paid to a keeper that resolves a bet through
+    /// `keeper_resolve_bet`.
+    pub keeper_fee_bps: u16,
+    /// Largest share of the vault the reserved payouts of all open bets may
+    /// add up to.
+    pub max_total_exposure_bps: u16,
+    /// Tokens of `burn_mint` the player burns with every bet, as friction
+    /// against bot farming; 0 disables it.
+    pub bet_burn_amount: u64,
+    pub burn_mint: Option<Pubkey>,
+    /// Leaves the house's share of each fee in the vault instead of moving it
+    /// to the fee vault. Reinvested fees grow the bankroll, and with it the
+    /// largest bet `max_exposure_bps` allows, but only come back out through
+    /// `withdraw_house`, which can't touch what open bets have reserved;
+    /// fees in the fee vault can always be withdrawn in full.
+    pub reinvest_fees: bool,
+    /// Vault balance below which lamport bets are refused, so an operator
+    /// can't run the game on a bankroll too thin to be credible. Resolving
+    /// and refunding open bets is unaffected; 0 disables it.
+    pub min_vault_balance: u64,
+    /// Slots after placement whose hash a bet's outcome is drawn from, so
+    /// the outcome depends on a block produced after the bet was fixed and
+    /// its cancel window had closed. A bet can't be resolved until that slot
+    /// has passed, nor once its hash has aged out of `SlotHashes` (512 slots
+    /// later), and is then only refundable. Must exceed `CANCEL_WINDOW_SLOTS`;
+    /// houses configured before that was enforced use `SLOT_HASH_DELAY_SLOTS`.
+    pub reveal_delay_slots: u64,
+    /// Keys whose ed25519 approvals count towards `resolver_threshold`; the
+    /// default key marks an unused slot.
+    pub resolver_set: [Pubkey; MAX_RESOLVERS],
+    /// Distinct members of `resolver_set` that must each sign a bet's
+    /// `quorum_message` before it can be resolved, so no single compromised
+    /// key can settle bets; 0 disables it.
+    pub resolver_threshold: u8,
+    /// Slots a `request_withdrawal` must wait before `claim_withdrawal` can
+    /// pay it out, so the bankroll can't be pulled the moment the house
+    /// takes a big loss. While set, `withdraw_house` is refused; 0 disables
+    /// it.
+    pub withdrawal_timelock_slots: u64,
+    /// Amount of the outstanding withdrawal request, or 0 if there is none.
+    /// It stays in the vault, backing open bets, until it is claimed.
+    pub pending_withdrawal: u64,
+    /// First slot at which `pending_withdrawal` can be claimed.
+    pub withdrawal_unlock_slot: u64,
+    /// Least a lamport bet pays the house, however small the stake: a win pays
+    /// that much less than the fair payout (but never less than the stake),
+    /// and the distributed house fee is raised to it. Stakes below it are
+    /// refused; 0 disables it.
+    pub min_fee_lamports: u64,
+    /// Balance the jackpot restarts from after it is won, set aside from the
+    /// vault's unreserved funds (less if they can't cover it), so players
+    /// never face an empty jackpot; 0 lets it reset to nothing.
+    pub min_jackpot: u64,
+    /// Widest slot range the resolver may sign a bet's `window_message`
+    /// over. While set, every ed25519-resolved bet needs such a signature
+    /// whose range includes the current slot, so a resolution withheld and
+    /// replayed later is refused; 0 disables it.
+    pub resolution_window_slots: u64,
+    /// Address that receives `charity_bps` of the stake the house keeps on
+    /// each losing lamport bet; it must be passed in the resolution's
+    /// remaining accounts. `None` disables it.
+    pub charity: Option<Pubkey>,
+    pub charity_bps: u16,
+    /// Whether every placement records its seed in a `UsedSeed` marker that
+    /// is never closed, so a seed can't be reused even after its bet is,
+    /// at the cost of the marker's rent.
+    pub consume_seeds: bool,
+    /// Share of the stake `cancel_bet` keeps as a fee, going to the fee vault
+    /// like any other house fee, so cancelling isn't free. Timeout refunds
+    /// are the house's fault and never pay it; 0 disables it.
+    pub cancel_fee_bps: u16,
+    /// Whether `resolve_bet_auto` may settle presigned bets without the
+    /// resolver; see there for what the house gives up.
+    pub auto_resolve: bool,
+    /// Fee rebates for players by lifetime volume; see `rebate_bps_for`.
+    pub volume_rebates: [VolumeRebate; 4],
+    /// Whether `resolve_bet_cpi` keeps settled bets open with their outcome,
+    /// so a caller retrying the same resolution gets that outcome back
+    /// instead of a second settlement; see `Bet::cached_outcome`.
+    pub cache_outcomes: bool,
+    /// Token bets placed but not yet resolved, refunded or cancelled, in any
+    /// mint. Their payouts are reserved in each mint's `TokenReserve` rather
+    /// than `reserved_lamports`, so `close_game` checks this as well.
+    pub open_token_bets: u64,
+    /// Canonical bump of the vault PDA, recorded once so signer seeds never
+    /// re-derive it.
+    pub vault_bump: u8,
+    pub bump: u8
+}
+
+/// Fee charged on bets of at least `min_amount`. A `min_amount` of 0 marks
+/// an unused slot.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct FeeTier {
+    pub min_amount: u64,
+    pub fee_bps: u16,
+}
+
+/// Taken off the fee of bets by players who had already wagered at least
+/// `min_wagered`. A `min_wagered` of 0 marks an unused slot.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct VolumeRebate {
+    pub min_wagered: u64,
+    pub rebate_bps: u16,
+}
+
+impl HouseConfig {
+    /// Fee of the highest tier `amount` reaches, or the base `fee_bps` below
+    /// every tier.
+    pub fn fee_bps_for(&self, amount: u64) -> u16 {
+        self.fee_tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.min_amount > 0 && amount >= tier.min_amount)
+            .map_or(self.fee_bps, |tier| tier.fee_bps)
+    }
+
+    /// Rebate of the highest tier `total_wagered` reaches, or none below
+    /// every tier.
+    pub fn rebate_bps_for(&self, total_wagered: u64) -> u16 {
+        self.volume_rebates
+            .iter()
+            .rev()
+            .find(|tier| tier.min_wagered > 0 && total_wagered >= tier.min_wagered)
+            .map_or(0, |tier| tier.rebate_bps)
+    }
+
+    /// The terms a bet of `amount` placed now is settled under, by a player
+    /// who had wagered `total_wagered` before it. A rebate never takes the
+    /// fee below zero.
+    pub fn bet_terms(&self, amount: u64, total_wagered: u64) -> BetTerms {
+        BetTerms {
+            fee_bps: self.fee_bps_for(amount).saturating_sub(self.rebate_bps_for(total_wagered)),
+            round_up_payouts: self.round_up_payouts,
+            min_fee_lamports: self.min_fee_lamports,
+            player_wins_ties: self.player_wins_ties,
+            bonus_roll_offset: self.bonus_roll_offset,
+        }
+    }
+
+    /// Used tiers must come first with strictly increasing thresholds, and no
+    /// tier may charge more than the base fee or the tier below it.
+    pub fn validate_fee_tiers(fee_bps: u16, fee_tiers: &[FeeTier]) -> Result<()> {
+        require!(fee_bps <= 10_000, DiceError::FeeTooHigh);
+        let (mut min_amount, mut max_fee, mut unused_seen) = (0, fee_bps, false);
+        for tier in fee_tiers {
+            if tier.min_amount == 0 {
+                unused_seen = true;
+                continue;
+            }
+            require!(
+                !unused_seen && tier.min_amount > min_amount && tier.fee_bps <= max_fee,
+                DiceError::InvalidFeeTiers
+            );
+            min_amount = tier.min_amount;
+            max_fee = tier.fee_bps;
+        }
+        Ok(())
+    }
+
+    /// Used tiers must come first with strictly increasing thresholds, and no
+    /// tier may rebate less than the one below it or more than the whole fee.
+    pub fn validate_volume_rebates(volume_rebates: &[VolumeRebate]) -> Result<()> {
+        let (mut min_wagered, mut min_rebate, mut unused_seen) = (0, 0, false);
+        for tier in volume_rebates {
+            if tier.min_wagered == 0 {
+                unused_seen = true;
+                continue;
+            }
+            require!(
+                !unused_seen
+                    && tier.min_wagered > min_wagered
+                    && tier.rebate_bps >= min_rebate
+                    && tier.rebate_bps <= 10_000,
+                DiceError::InvalidVolumeRebates
+            );
+            min_wagered = tier.min_wagered;
+            min_rebate = tier.rebate_bps;
+        }
+        Ok(())
+    }
+
+    /// Releases a settled bet's reservation. Fails rather than wrapping if the
+    /// bet was never reserved, so a double-processed bet can't inflate the
+    /// withdrawable balance.
+    pub fn release_reservation(&mut self, potential_payout: u64) -> Result<()> {
+        self.reserved_lamports = self
+            .reserved_lamports
+            .checked_sub(potential_payout)
+            .ok_or(DiceError::ReservedAccountingUnderflow)?;
+        Ok(())
+    }
+
+    /// Counts a settled token bet out of `open_token_bets`.
+    pub fn release_token_bet(&mut self) {
+        self.open_token_bets = self.open_token_bets.saturating_sub(1);
+    }
+
+    /// Slot whose hash decides a bet placed at `bet_slot`; see
+    /// `reveal_delay_slots`.
+    pub fn reveal_slot(&self, bet_slot: u64) -> Result<u64> {
+        let delay = self.reveal_delay_slots.max(SLOT_HASH_DELAY_SLOTS);
+        bet_slot.checked_add(delay).ok_or_else(|| DiceError::Overflow.into())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct HouseConfigArgs {
+    pub game_id: u8,
+    pub resolver: Pubkey,
+    pub fee_bps: u16,
+    pub fee_tiers: [FeeTier; 4],
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub referral_bps: u16,
+    pub jackpot_contribution_bps: u16,
+    pub insurance_bps: u16,
+    pub rebalance_threshold: u64,
+    pub max_payout: u64,
+    pub max_exposure_bps: u16,
+    pub streak_bonus_bps: u16,
+    pub max_streak_bonus_bps: u16,
+    pub risk_free_enabled: bool,
+    pub round_up_payouts: bool,
+    pub bonus_roll_offset: i8,
+    pub player_wins_ties: bool,
+    pub bet_cooldown_slots: u64,
+    pub large_win_threshold: u64,
+    pub challenge_slots: u64,
+    pub max_open_bets_per_player: u32,
+    pub sol_usd_feed_id: [u8; 32],
+    pub max_price_age_secs: u64,
+    pub near_miss_window: u8,
+    pub near_miss_bps: u16,
+    pub signature_window_slots: u64,
+    pub rewards_enabled: bool,
+    pub reward_mint: Option<Pubkey>,
+    pub reward_rate: u64,
+    pub keeper_fee_bps: u16,
+    pub max_total_exposure_bps: u16,
+    pub bet_burn_amount: u64,
+    pub burn_mint: Option<Pubkey>,
+    pub reinvest_fees: bool,
+    pub min_vault_balance: u64,
+    pub reveal_delay_slots: u64,
+    pub withdrawal_timelock_slots: u64,
+    pub min_fee_lamports: u64,
+    pub min_jackpot: u64,
+    pub resolution_window_slots: u64,
+    pub charity: Option<Pubkey>,
+    pub charity_bps: u16,
+    pub consume_seeds: bool,
+    pub cancel_fee_bps: u16,
+    pub auto_resolve: bool,
+    pub volume_rebates: [VolumeRebate; 4],
+    pub cache_outcomes: bool,
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::DiceError;
+
+/// Running totals over every bet settled by `resolve_bet` and
+/// `reveal_and_resolve`, the same bets `RecentOutcomes` shows, for the
+/// operator's P&L. Lamport sums are u128 so a long-running game can't
+/// overflow them.
+#[account]
+#[derive(InitSpace)]
+pub struct HouseLedger {
+    pub lifetime_bets: u64,
+    /// Stakes of the settled bets.
+    pub lifetime_volume: u128,
+    /// Win payouts, bonuses included; jackpots, refunds and near-miss
+    /// consolations are left out.
+    pub lifetime_payouts: u128,
+    /// House fees taken on the settled bets, before they're split with
+    /// referrers, keepers and the jackpots.
+    pub lifetime_fees: u128,
+    pub bump: u8,
+}
+
+impl HouseLedger {
+    pub fn record(&mut self, amount: u64, payout: u64, fee: u64) -> Result<()> {
+        self.lifetime_bets = self.lifetime_bets.checked_add(1).ok_or(DiceError::Overflow)?;
+        self.lifetime_volume = self
+            .lifetime_volume
+            .checked_add(amount as u128)
+            .ok_or(DiceError::Overflow)?;
+        self.lifetime_payouts = self
+            .lifetime_payouts
+            .checked_add(payout as u128)
+            .ok_or(DiceError::Overflow)?;
+        self.lifetime_fees = self
+            .lifetime_fees
+            .checked_add(fee as u128)
+            .ok_or(DiceError::Overflow)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Jackpot {
+    pub balance: u64,
+    pub bump: u8
+}
+
+/// The tiers of `Jackpots`, from most to least frequent.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum JackpotTierKind {
+    Mini,
+    Major,
+    Grand,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct JackpotTier {
+    pub balance: u64,
+    /// A d100 roll at or below this triggers the tier; 0 disables it.
+    pub trigger_roll: u8,
+    /// Share of each bet's house fee added to `balance`.
+    pub contribution_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct JackpotTierArgs {
+    pub trigger_roll: u8,
+    pub contribution_bps: u16,
+}
+
+/// Mini, major and grand jackpots alongside the single `Jackpot`. Like it,
+/// the balances stay in the vault and are only tracked here.
+#[account]
+#[derive(InitSpace)]
+pub struct Jackpots {
+    pub tiers: [JackpotTier; 3],
+    pub bump: u8,
+}
+
+impl Jackpots {
+    pub const KINDS: [JackpotTierKind; 3] =
+        [JackpotTierKind::Mini, JackpotTierKind::Major, JackpotTierKind::Grand];
+
+    pub fn total(&self) -> u64 {
+        self.tiers
+            .iter()
+            .fold(0u64, |total, tier| total.saturating_add(tier.balance))
+    }
+
+    /// The rarest funded tier `roll` triggers. Trigger rolls fall with each
+    /// tier, so a roll matching the grand also matches the ones below it and
+    /// only the grand pays.
+    pub fn triggered(&self, roll: u8) -> Option<usize> {
+        (0..self.tiers.len()).rev().find(|&index| {
+            let tier = self.tiers[index];
+            tier.trigger_roll > 0 && roll <= tier.trigger_roll && tier.balance > 0
+        })
+    }
+}
@@ -1,24 +1,70 @@
-use anchor_lang::prelude::*;
-
-#[account]
-#[derive(InitSpace)]
-pub struct Bet {
-    pub player: Pubkey,
-    pub seed: u128,
-    pub slot: u64,
-    pub amount: u64,
-    pub roll: u8,
-    pub bump : u8
-}
-
-impl Bet {
-    
-    pub fn to_slice(&self) -> Vec<u8> {
-        let mut s = self.player.to_bytes().to_vec();
-        s.extend_from_slice(&self.seed.to_le_bytes());
-        s.extend_from_slice(&self.slot.to_le_bytes());
-        s.extend_from_slice(&self.amount.to_le_bytes());
-        s.extend_from_slice(&[self.roll, self.bump]);
-        s        
-    }
-}
\ No newline at end of file
+//! Every PDA is namespaced by the house it belongs to and the `game_id` of
+//! the game within it, so independent operators, or one operator's separate
+//! games, can share one deployment without their accounts colliding:
+//!
+//! - vault: `[b"vault", house, game_id]`
+//! - config: `[b"config", house, game_id]`
+//! - jackpot: `[b"jackpot", house, game_id]`
+//! - tiered jackpots: `[b"jackpots", house, game_id]`
+//! - fee vault: `[b"fee_vault", house, game_id]`
+//! - treasury: `[b"treasury", house, game_id]`
+//! - recent outcomes: `[b"recent_outcomes", house, game_id]`
+//! - ledger: `[b"ledger", house, game_id]`
+//! - stats: `[b"stats", house, game_id, player]`
+//! - bet: `[b"bet", vault, player, seed]`, scoped to the game through its
+//!   vault; `place_next_bet` uses the player's `bet_nonce` as the seed
+//! - pending payout: `[b"pending_payout", vault, player, seed]`, matching the
+//!   bet it settles
+//! - allowlist entry: `[b"allowlist", house, game_id, player]`
+//! - player balance: `[b"balance", house, game_id, player]`
+//! - free credits: `[b"free_credits", house, game_id, player]`
+//! - self exclusion: `[b"self_exclusion", player]`, deliberately shared by
+//!   every house and game
+//! - used seed: `[b"used_seed", vault, player, seed]`, outliving the bet it
+//!   marks
+//! - token reserve: `[b"token_reserve", vault, mint]`
+
+pub mod bet;
+pub use bet::*;
+
+pub mod house_config;
+pub use house_config::*;
+
+pub mod jackpot;
+pub use jackpot::*;
+
+pub mod player_stats;
+pub use player_stats::*;
+
+pub mod fee_vault;
+pub use fee_vault::*;
+
+pub mod pending_payout;
+pub use pending_payout::*;
+
+pub mod treasury;
+pub use treasury::*;
+
+pub mod allowlisted;
+pub use allowlisted::*;
+
+pub mod player_balance;
+pub use player_balance::*;
+
+pub mod recent_outcomes;
+pub use recent_outcomes::*;
+
+pub mod self_exclusion;
+pub use self_exclusion::*;
+
+pub mod free_credits;
+pub use free_credits::*;
+
+pub mod used_seed;
+pub use used_seed::*;
+
+pub mod house_ledger;
+pub use house_ledger::*;
+
+pub mod token_reserve;
+pub use token_reserve::*;
@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+/// A large win held back for the challenge window. The funds stay in the
+/// vault, reserved, until the player claims them or the authority voids the
+/// payout.
+#[account]
+#[derive(InitSpace)]
+pub struct PendingPayout {
+    pub player: Pubkey,
+    pub seed: u128,
+    pub amount: u64,
+    /// First slot at which the player may claim; the authority may void the
+    /// payout before it.
+    pub release_slot: u64,
+    /// Whoever submitted the resolution and paid this account's rent, which
+    /// goes back to them when it's closed.
+    pub rent_payer: Pubkey,
+    pub bump: u8
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::state::BetArgs;
+
+/// Prefixed to the message a player signs in `PlayerBalance::placement_message`.
+#[constant]
+pub const PLACEMENT_MESSAGE_TAG: &[u8] = b"place-bet";
+
+/// Lamports a player has deposited with a house, held above the account's
+/// rent. Bets can be staked from it without a wallet transfer, always for
+/// relayed `place_bet_signed` bets, and their winnings are credited back.
+#[account]
+#[derive(InitSpace)]
+pub struct PlayerBalance {
+    pub player: Pubkey,
+    /// Counts signed placements, so each placement signature is good once.
+    pub nonce: u64,
+    pub bump: u8
+}
+
+impl PlayerBalance {
+    /// What the player signs to let a relayer place a bet for them: the tag,
+    /// the house, this balance's current nonce, the seed and the
+    /// Borsh-encoded `args`.
+    pub fn placement_message(&self, house: &Pubkey, seed: u128, args: &BetArgs) -> Result<Vec<u8>> {
+        let mut message = [
+            PLACEMENT_MESSAGE_TAG,
+            house.as_ref(),
+            &self.nonce.to_le_bytes(),
+            &seed.to_le_bytes(),
+        ]
+        .concat();
+        args.serialize(&mut message)?;
+        Ok(message)
+    }
+
+    /// Deposited lamports, i.e. everything above the account's rent.
+    pub fn available(info: &AccountInfo) -> Result<u64> {
+        let rent = Rent::get()?.minimum_balance(info.data_len());
+        Ok(info.lamports().saturating_sub(rent))
+    }
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DiceError, state::HouseConfig};
+
+#[account]
+#[derive(InitSpace)]
+pub struct PlayerStats {
+    pub player: Pubkey,
+    pub total_bets: u64,
+    pub total_wagered: u64,
+    pub total_won: u64,
+    pub win_count: u64,
+    /// Consecutive wins up to the most recent resolution.
+    pub current_streak: u32,
+    /// Slot of the player's most recent bet, for the cooldown check.
+    pub last_bet_slot: u64,
+    /// Seed of this player's next `place_next_bet` bet.
+    pub bet_nonce: u64,
+    /// Bets placed but not yet resolved, refunded or cancelled.
+    pub open_bets: u32,
+    pub bump: u8
+}
+
+impl PlayerStats {
+    /// `bet_cooldown_slots`, which allowlisted players skip, and
+    /// `max_open_bets_per_player`, for a bet placed at `slot`.
+    pub fn require_can_bet(&self, config: &HouseConfig, slot: u64, allowlisted: bool) -> Result<()> {
+        if self.total_bets > 0 && !allowlisted {
+            require!(
+                slot >= self.last_bet_slot.saturating_add(config.bet_cooldown_slots),
+                DiceError::BetCooldownActive
+            );
+        }
+        require!(
+            config.max_open_bets_per_player == 0
+                || self.open_bets < config.max_open_bets_per_player,
+            DiceError::TooManyOpenBets
+        );
+        Ok(())
+    }
+
+    /// Counts a bet placed at `slot`. `wagered` is added to `total_wagered`,
+    /// which volume rebates read as lamports, so token bets pass 0.
+    pub fn record_bet(&mut self, player: Pubkey, bump: u8, wagered: u64, slot: u64) -> Result<()> {
+        self.player = player;
+        self.bump = bump;
+        self.total_bets = self.total_bets.checked_add(1).ok_or(DiceError::Overflow)?;
+        self.total_wagered = self
+            .total_wagered
+            .checked_add(wagered)
+            .ok_or(DiceError::Overflow)?;
+        self.last_bet_slot = slot;
+        self.bet_nonce = self.bet_nonce.checked_add(1).ok_or(DiceError::Overflow)?;
+        self.open_bets = self.open_bets.checked_add(1).ok_or(DiceError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn release_open_bet(&mut self) {
+        self.open_bets = self.open_bets.saturating_sub(1);
+    }
+}
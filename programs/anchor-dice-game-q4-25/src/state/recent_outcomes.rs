@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+/// Outcomes `RecentOutcomes` keeps. Fixed, so the account never needs a
+/// realloc.
+pub const RECENT_OUTCOMES_LEN: usize = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct RecentOutcome {
+    pub player: Pubkey,
+    pub roll: u8,
+    pub won: bool,
+    pub payout: u64,
+}
+
+/// Public feed of the last bets settled by `resolve_bet` and
+/// `reveal_and_resolve`, kept as a ring buffer: `position` is where the next
+/// outcome goes, so once the buffer has wrapped it also points at the oldest.
+/// Entries not yet written are all zeroes.
+#[account]
+#[derive(InitSpace)]
+pub struct RecentOutcomes {
+    pub outcomes: [RecentOutcome; RECENT_OUTCOMES_LEN],
+    pub position: u8,
+    pub bump: u8,
+}
+
+impl RecentOutcomes {
+    pub fn record(&mut self, outcome: RecentOutcome) {
+        self.outcomes[self.position as usize] = outcome;
+        self.position = ((self.position as usize + 1) % RECENT_OUTCOMES_LEN) as u8;
+    }
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+/// A player's voluntary ban from betting, shared by every house and game.
+/// `until_slot` can only ever move later, so a moment of weakness can't
+/// undo it.
+#[account]
+#[derive(InitSpace)]
+pub struct SelfExclusion {
+    pub player: Pubkey,
+    /// First slot at which the player may bet again.
+    pub until_slot: u64,
+    pub bump: u8
+}
+
+impl SelfExclusion {
+    /// Whether `account`, the player's self-exclusion PDA, bars them from
+    /// betting now. Players who never excluded themselves have no account
+    /// there, so placement takes it unchecked.
+    pub fn is_active(account: &AccountInfo) -> Result<bool> {
+        if account.owner != &crate::ID || account.data_is_empty() {
+            return Ok(false);
+        }
+        let exclusion = Account::<SelfExclusion>::try_from(account)?;
+        Ok(Clock::get()?.slot < exclusion.until_slot)
+    }
+}
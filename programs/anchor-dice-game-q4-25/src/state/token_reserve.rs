@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::DiceError;
+
+/// Potential payouts of a game's open bets in one mint, which its vault's
+/// token account has to keep back: `HouseConfig::reserved_lamports` for
+/// token bets, kept per mint since their amounts can't be added together.
+#[account]
+#[derive(InitSpace)]
+pub struct TokenReserve {
+    pub mint: Pubkey,
+    pub reserved: u64,
+    pub bump: u8,
+}
+
+impl TokenReserve {
+    pub fn reserve(&mut self, potential_payout: u64) -> Result<()> {
+        self.reserved = self
+            .reserved
+            .checked_add(potential_payout)
+            .ok_or(DiceError::Overflow)?;
+        Ok(())
+    }
+
+    /// Like `HouseConfig::release_reservation`, for a settled token bet.
+    pub fn release(&mut self, potential_payout: u64) -> Result<()> {
+        self.reserved = self
+            .reserved
+            .checked_sub(potential_payout)
+            .ok_or(DiceError::ReservedAccountingUnderflow)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+/// Insurance fund fed by a slice of lost stakes; `rebalance_from_treasury`
+/// moves it back into the vault when the vault runs low.
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub bump: u8
+}
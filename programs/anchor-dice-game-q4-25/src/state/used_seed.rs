@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::DiceError;
+
+/// Permanent record that a player has placed a bet with `seed`, kept while
+/// the house sets `consume_seeds`. The bet account itself is closed on
+/// settlement, which would otherwise let the same seed, and so the same
+/// bet address, be used again.
+#[account]
+#[derive(InitSpace)]
+pub struct UsedSeed {
+    pub player: Pubkey,
+    pub seed: u128,
+}
+
+impl UsedSeed {
+    /// Marks `seed` as spent, failing if an earlier bet already spent it.
+    /// The marker must be passed exactly while `consume_seeds` is set.
+    pub fn consume(used_seed: Option<&mut Account<UsedSeed>>, consume_seeds: bool, player: Pubkey, seed: u128) -> Result<()> {
+        require!(used_seed.is_some() == consume_seeds, DiceError::UsedSeedMismatch);
+        if let Some(used_seed) = used_seed {
+            require!(used_seed.player == Pubkey::default(), DiceError::SeedAlreadyConsumed);
+            used_seed.player = player;
+            used_seed.seed = seed;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,450 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use solana_program::hash::hashv;
+
+use crate::{errors::DiceError, state::{Bet, BetDirection, HouseConfig, ParlayLeg, MAX_LEGS}};
+
+/// Outcomes a `PrecisionMode::Basis` bet is drawn from.
+pub const BASIS_OUTCOMES: u16 = 10_000;
+
+pub const MAX_DICE: u8 = 8;
+
+/// Faces of the classic die every bet rolled before `Bet::faces` existed.
+pub const D100_FACES: u16 = 100;
+
+/// Bounds on `Bet::faces`.
+pub const MIN_FACES: u16 = 2;
+pub const MAX_FACES: u16 = 1_000;
+
+/// Domain-separation tag prefixed to every outcome hash, so a signature made
+/// for some other purpose never hashes to the same value as a roll.
+#[constant]
+pub const ROLL_DOMAIN: &[u8] = b"dice-roll-v1";
+
+/// The hash every outcome is drawn from: `ROLL_DOMAIN`, then the randomness
+/// input, then the slot hash captured when the bet was placed.
+///
+/// The slot hash doesn't exist until the placing transaction lands, so
+/// nobody can search for a favourable signature before the bet does; each
+/// attempt to steer an outcome has to be made against a live bet, inside its
+/// signature window, rather than precomputed offline.
+pub fn outcome_hash(input: &[u8], slot_hash: &[u8; 32]) -> [u8; 32] {
+    hashv(&[ROLL_DOMAIN, input, slot_hash]).to_bytes()
+}
+
+/// Domain-separation tag for parlay leg rolls; see `compute_leg_rolls`.
+#[constant]
+pub const LEG_DOMAIN: &[u8] = b"dice-leg-v1";
+
+/// The d100 roll, 1..=100; see `compute_face_roll`.
+pub fn compute_roll(sig: &[u8], slot_hash: &[u8; 32]) -> u8 {
+    compute_face_roll(sig, slot_hash, D100_FACES) as u8
+}
+
+/// A roll of a die with `faces` faces, 1..=faces. Reduces the whole 256-bit
+/// hash (little-endian) modulo `faces` one byte at a time. Folding it into a
+/// u128 first skews low rolls because 2^128 is rarely a multiple of `faces`;
+/// over 256 bits the skew is below 2^-246 for any die up to `MAX_FACES`.
+pub fn compute_face_roll(sig: &[u8], slot_hash: &[u8; 32], faces: u16) -> u16 {
+    let hash = outcome_hash(sig, slot_hash);
+
+    let remainder = hash
+        .iter()
+        .rev()
+        .fold(0u32, |acc, byte| (acc * 256 + *byte as u32) % faces as u32);
+
+    (remainder + 1) as u16
+}
+
+/// `compute_roll` at basis-point precision: the whole hash modulo 10_000,
+/// giving 0..=9999. Its last two digits are `compute_roll`'s remainder, so
+/// the two never disagree about the same hash.
+pub fn compute_roll_u16(sig: &[u8], slot_hash: &[u8; 32]) -> u16 {
+    let hash = outcome_hash(sig, slot_hash);
+
+    hash.iter()
+        .rev()
+        .fold(0u32, |acc, byte| (acc * 256 + *byte as u32) % BASIS_OUTCOMES as u32) as u16
+}
+
+pub fn compute_dice_sum(sig: &[u8], slot_hash: &[u8; 32], dice_count: u8) -> u8 {
+    let hash = outcome_hash(sig, slot_hash);
+
+    hash.chunks_exact(4)
+        .take(dice_count as usize)
+        .map(|chunk| (u32::from_le_bytes(chunk.try_into().unwrap()) % 6 + 1) as u8)
+        .sum()
+}
+
+pub fn compute_outcome(sig: &[u8], slot_hash: &[u8; 32], dice_count: u8) -> u8 {
+    match dice_count {
+        0 => compute_roll(sig, slot_hash),
+        _ => compute_dice_sum(sig, slot_hash, dice_count),
+    }
+}
+
+/// One d100 per parlay leg, each from the first 8 bytes of
+/// `hash(LEG_DOMAIN, leg index, outcome hash)`. Every roll a bet can be
+/// judged on reads the outcome hash itself, in whole or by chunks, so the
+/// legs hash it again under their own tag rather than reuse any of its bytes
+/// and stay independent of the main roll, dice sum and each other. The
+/// modulo skew per leg is below 2^-57.
+pub fn compute_leg_rolls(input: &[u8], slot_hash: &[u8; 32]) -> [u8; MAX_LEGS] {
+    let hash = outcome_hash(input, slot_hash);
+
+    let mut rolls = [0; MAX_LEGS];
+    for (leg, roll) in rolls.iter_mut().enumerate() {
+        let leg_hash = hashv(&[LEG_DOMAIN, &[leg as u8], &hash]).to_bytes();
+        *roll = (u64::from_le_bytes(leg_hash[..8].try_into().unwrap()) % 100 + 1) as u8;
+    }
+    rolls
+}
+
+/// Hash of the newest entry in the `SlotHashes` sysvar. The sysvar is too
+/// large to deserialize, so it's read raw: a u64 entry count, then
+/// `(slot, hash)` pairs, newest first.
+pub fn recent_slot_hash(slot_hashes: &AccountInfo) -> Result<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data()?;
+    let count = data
+        .get(..8)
+        .map_or(0, |bytes| u64::from_le_bytes(bytes.try_into().unwrap()));
+    require!(count > 0 && data.len() >= 48, DiceError::SlotHashUnavailable);
+    Ok(data[16..48].try_into().unwrap())
+}
+
+/// Hash of the earliest slot at or after `slot` in the `SlotHashes` sysvar,
+/// laid out as in `recent_slot_hash`. Skipped slots have no entry, so this is
+/// the first block produced from `slot` on. Fails with `ResolutionTooEarly`
+/// until that block exists, and with `SlotHashUnavailable` once the entries
+/// before it have aged out and it can no longer be told apart from later
+/// ones.
+pub fn slot_hash_at(slot_hashes: &AccountInfo, slot: u64) -> Result<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data()?;
+    let count = data
+        .get(..8)
+        .map_or(0, |bytes| u64::from_le_bytes(bytes.try_into().unwrap()));
+    let mut found = None;
+    for entry in data.get(8..).unwrap_or_default().chunks_exact(40).take(count as usize) {
+        if u64::from_le_bytes(entry[..8].try_into().unwrap()) < slot {
+            return found.ok_or_else(|| DiceError::ResolutionTooEarly.into());
+        }
+        found = Some(entry[8..].try_into().unwrap());
+    }
+    err!(DiceError::SlotHashUnavailable)
+}
+
+const DICE_SUMS: usize = 6 * MAX_DICE as usize + 1;
+
+/// Number of ways `dice_count` d6 can land on each sum, indexed by the sum.
+pub fn dice_sum_ways(dice_count: u8) -> [u64; DICE_SUMS] {
+    let mut ways = [0u64; DICE_SUMS];
+    ways[0] = 1;
+    for _ in 0..dice_count {
+        let mut next = [0u64; DICE_SUMS];
+        for sum in 0..DICE_SUMS - 6 {
+            for face in 1..=6 {
+                next[sum + face] += ways[sum];
+            }
+        }
+        ways = next;
+    }
+    ways
+}
+
+pub fn dice_sum_outcomes(dice_count: u8, direction: BetDirection, threshold: u8, range: (u8, u8)) -> u64 {
+    dice_sum_ways(dice_count)
+        .iter()
+        .enumerate()
+        .filter(|(sum, _)| direction.wins(threshold, range, *sum as u8))
+        .map(|(_, count)| count)
+        .sum()
+}
+
+/// Returns `(winning_outcomes, total_outcomes)` for a bet, rejecting bets that
+/// can never win or can never lose.
+pub fn bet_odds(direction: BetDirection, roll: u8, range: (u8, u8), dice_count: u8) -> Result<(u64, u64)> {
+    if direction == BetDirection::Range {
+        require!(range.0 <= range.1, DiceError::InvalidRange);
+    }
+    match dice_count {
+        0 => {
+            let valid = match direction {
+                // Must leave at least one losing roll.
+                BetDirection::Range => range.0 >= 1 && range.1 <= 100 && range.1 - range.0 < 99,
+                BetDirection::Exact => (1..=100).contains(&roll),
+                _ => (2..=96).contains(&roll),
+            };
+            require!(valid, DiceError::RollOutOfRange);
+            Ok((direction.winning_outcomes(roll, range) as u64, 100))
+        }
+        1..=MAX_DICE => {
+            let total = 6u64.pow(dice_count as u32);
+            let winning = dice_sum_outcomes(dice_count, direction, roll, range);
+            require!(winning > 0 && winning < total, DiceError::RollOutOfRange);
+            Ok((winning, total))
+        }
+        _ => err!(DiceError::InvalidDiceCount),
+    }
+}
+
+/// Odds of a bet on a single die with `faces` other than `D100_FACES`: an
+/// Under bet wins on the rolls up to `roll`, an Over bet on those above it
+/// and an Exact bet on `roll` alone, each leaving at least one losing roll.
+/// The threshold is a `u8`, as on a d100, so dice past 255 faces can only
+/// be bet against their lower rolls.
+pub fn face_odds(direction: BetDirection, roll: u8, dice_count: u8, faces: u16) -> Result<(u64, u64)> {
+    require!((MIN_FACES..=MAX_FACES).contains(&faces), DiceError::InvalidFaces);
+    require!(
+        dice_count == 0 && direction != BetDirection::Range,
+        DiceError::InvalidFaces
+    );
+    let roll = roll as u16;
+    let valid = match direction {
+        BetDirection::Exact => (1..=faces).contains(&roll),
+        _ => (1..faces).contains(&roll),
+    };
+    require!(valid, DiceError::RollOutOfRange);
+    let winning = match direction {
+        BetDirection::Under => roll,
+        BetDirection::Over => faces - roll,
+        _ => 1,
+    };
+    Ok((winning as u64, faces as u64))
+}
+
+/// Odds of a `PrecisionMode::Basis` bet: an Under bet wins on the
+/// `threshold` outcomes below it, an Over bet on the rest. Thresholds are
+/// held to the same bounds as a d100 bet's, scaled by 100.
+pub fn basis_odds(direction: BetDirection, threshold: u16, dice_count: u8) -> Result<(u64, u64)> {
+    require!(
+        dice_count == 0 && matches!(direction, BetDirection::Under | BetDirection::Over),
+        DiceError::InvalidBasisBet
+    );
+    require!((200..=9_600).contains(&threshold), DiceError::RollOutOfRange);
+    let winning = match direction {
+        BetDirection::Under => threshold,
+        _ => BASIS_OUTCOMES - threshold,
+    };
+    Ok((winning as u64, BASIS_OUTCOMES as u64))
+}
+
+/// Odds of a parlay made of a bet with `odds` plus `legs`: it only wins if
+/// every leg does, so the winning and total outcomes multiply and the payout
+/// is the product of each leg's odds. Legs are d100 Under, Over or Exact
+/// predictions, held to the same bounds as a plain bet.
+pub fn parlay_odds((mut winning, mut total): (u64, u64), legs: &[ParlayLeg]) -> Result<(u64, u64)> {
+    require!(legs.len() <= MAX_LEGS, DiceError::TooManyLegs);
+    for leg in legs {
+        require!(leg.direction != BetDirection::Range, DiceError::InvalidParlayLeg);
+        let (leg_winning, leg_total) = bet_odds(leg.direction, leg.roll, (0, 0), 0)?;
+        winning = winning.checked_mul(leg_winning).ok_or(DiceError::Overflow)?;
+        total = total.checked_mul(leg_total).ok_or(DiceError::Overflow)?;
+    }
+    Ok((winning, total))
+}
+
+/// Payouts round down to the nearest lamport, so any fractional remainder
+/// stays with the house, unless `round_up` hands it to the player instead. A
+/// `fee_bps` of 0 pays the fair odds exactly, e.g. twice the stake on an
+/// even-money bet.
+///
+/// The rounded-up amount is what placement reserves and checks against the
+/// vault, so the extra lamport never goes unfunded.
+///
+/// The math runs in u128, where even `u64::MAX` times the most outcomes any
+/// bet has (6^8, times 100^3 for a full parlay) times 10_000 fits, so for
+/// real inputs the multiplication can't fail and the division only fails on
+/// zero winning outcomes, which `bet_odds` never returns. What large stakes do hit is a payout beyond
+/// `u64::MAX`, which comes back as `DiceError::Overflow` rather than being
+/// truncated.
+pub fn payout_for(amount: u64, winning_outcomes: u64, total_outcomes: u64, fee_bps: u16, round_up: bool) -> Result<u64> {
+    let edge_factor = 10_000u128
+        .checked_sub(fee_bps as u128)
+        .ok_or(DiceError::FeeTooHigh)?;
+    let numerator = (amount as u128)
+        .checked_mul(total_outcomes as u128 * edge_factor)
+        .ok_or(DiceError::Overflow)?;
+    let denominator = winning_outcomes as u128 * 10_000;
+    let mut payout = numerator
+        .checked_div(denominator)
+        .ok_or(DiceError::Overflow)?;
+    if round_up && !numerator.is_multiple_of(denominator) {
+        payout += 1;
+    }
+    u64::try_from(payout).map_err(|_| DiceError::Overflow.into())
+}
+
+/// Trims a winning `payout` so the house keeps at least `min_fee` lamports
+/// out of the fair payout, where a percentage fee on a tiny stake would round
+/// to next to nothing. The trimmed payout never drops below the stake, and a
+/// payout the percentage fee already put lower is left alone.
+pub fn apply_fee_floor(payout: u64, amount: u64, winning_outcomes: u64, total_outcomes: u64, min_fee: u64) -> Result<u64> {
+    if min_fee == 0 {
+        return Ok(payout);
+    }
+    let fair = (amount as u128)
+        .checked_mul(total_outcomes as u128)
+        .and_then(|stake| stake.checked_div(winning_outcomes as u128))
+        .ok_or(DiceError::Overflow)?;
+    let floored = fair.saturating_sub(min_fee as u128).max(amount as u128);
+    Ok(u64::try_from(floored).map_or(payout, |floored| payout.min(floored)))
+}
+
+/// Theoretical house edge of a bet under `config`, in basis points of the
+/// stake: what's left once every outcome's payout is weighed by its odds,
+/// using the same win, tie and near-miss rules as settlement. Negative once
+/// bonuses hand players the advantage.
+///
+/// Anything depending on the player or the vault is left out: streak
+/// bonuses, risk-free bets, the caps on bonuses and consolations, and the
+/// jackpots, which are funded from the fee and paid back out to players.
+pub fn house_edge_bps(
+    config: &HouseConfig,
+    amount: u64,
+    direction: BetDirection,
+    roll: u8,
+    range: (u8, u8),
+    dice_count: u8,
+) -> Result<i64> {
+    require!(amount > 0, DiceError::MinimumBet);
+    let (winning_outcomes, total_outcomes) = bet_odds(direction, roll, range, dice_count)?;
+    let payout = payout_for(amount, winning_outcomes, total_outcomes, config.fee_bps_for(amount), config.round_up_payouts)?;
+    let payout = apply_fee_floor(payout, amount, winning_outcomes, total_outcomes, config.min_fee_lamports)?;
+    let consolation = amount
+        .checked_mul(config.near_miss_bps as u64)
+        .ok_or(DiceError::Overflow)?
+        / 10_000;
+    let threshold = direction.effective_threshold(roll, dice_count, config.bonus_roll_offset);
+
+    let ways = dice_sum_ways(dice_count);
+    let outcomes = match dice_count {
+        0 => 1..=100,
+        dice => dice..=6 * dice,
+    };
+    let mut returned = 0u128;
+    for outcome in outcomes {
+        let weight = match dice_count {
+            0 => 1,
+            _ => ways[outcome as usize] as u128,
+        };
+        if direction.wins_at(threshold, range, outcome, config.player_wins_ties) {
+            returned += payout as u128 * weight;
+        } else if config.near_miss_window > 0
+            && direction.miss_distance(threshold, range, outcome) <= config.near_miss_window
+        {
+            returned += consolation as u128 * weight;
+        }
+    }
+
+    let staked = amount as i128 * total_outcomes as i128;
+    Ok(((staked - returned as i128) * 10_000 / staked) as i64)
+}
+
+/// Reads the house's SOL/USD price as `(price, exponent)` from a Pyth
+/// `PriceUpdateV2` account, rejecting any other feed and any price older than
+/// `max_price_age_secs`.
+pub fn read_sol_usd_price(price_update: &AccountInfo, config: &HouseConfig) -> Result<(i64, i32)> {
+    let price_update = Account::<PriceUpdateV2>::try_from(price_update)?;
+    require!(
+        price_update.price_message.feed_id == config.sol_usd_feed_id,
+        DiceError::PriceFeedMismatch
+    );
+    let price = price_update
+        .get_price_no_older_than(&Clock::get()?, config.max_price_age_secs, &config.sol_usd_feed_id)
+        .map_err(|_| DiceError::StalePriceFeed)?;
+    require!(price.price > 0, DiceError::InvalidPrice);
+    Ok((price.price, price.exponent))
+}
+
+/// Rescales a lamport payout so it is worth as many dollars at `current` as
+/// it was at `entry`; both prices are `(price, exponent)` pairs.
+pub fn usd_pegged_payout(payout: u64, entry: (i64, i32), current: (i64, i32)) -> Result<u64> {
+    let (entry_price, entry_expo) = entry;
+    let (price, expo) = current;
+    let scale = 10u128
+        .checked_pow(entry_expo.abs_diff(expo))
+        .ok_or(DiceError::Overflow)?;
+    let mut numerator = (payout as u128)
+        .checked_mul(entry_price as u128)
+        .ok_or(DiceError::Overflow)?;
+    let mut denominator = price as u128;
+    if entry_expo > expo {
+        numerator = numerator.checked_mul(scale).ok_or(DiceError::Overflow)?;
+    } else {
+        denominator = denominator.checked_mul(scale).ok_or(DiceError::Overflow)?;
+    }
+    u64::try_from(numerator / denominator).map_err(|_| DiceError::Overflow.into())
+}
+
+/// Fails if moving `amount` out of a vault holding `balance` would leave it
+/// below the rent-exempt minimum, at which point the runtime could reclaim
+/// the vault while bets are still open.
+pub fn require_vault_rent_exempt(balance: u64, amount: u64) -> Result<()> {
+    let remaining = balance
+        .checked_sub(amount)
+        .ok_or(DiceError::VaultInsufficientFunds)?;
+    require!(
+        remaining >= Rent::get()?.minimum_balance(0),
+        DiceError::VaultWouldBecomeRentExempt
+    );
+    Ok(())
+}
+
+/// Whether `config`, `vault` and `bet` all belong to `house`. The seed
+/// constraints reject a mix as well, but checked up front this names the
+/// problem instead of failing on whichever account's seeds happen to be
+/// checked first.
+pub fn belongs_to_house(house: &Pubkey, config: &HouseConfig, vault: &Pubkey, bet: &Bet, bet_key: &Pubkey) -> bool {
+    let derives = |seeds: &[&[u8]], key: &Pubkey| {
+        Pubkey::create_program_address(seeds, &crate::ID).is_ok_and(|address| address == *key)
+    };
+    config.house_id == *house
+        && derives(&[b"vault", house.as_ref(), &[config.game_id], &[config.vault_bump]], vault)
+        && derives(
+            &[b"bet", vault.as_ref(), bet.player.as_ref(), bet.seed.to_le_bytes().as_ref(), &[bet.bump]],
+            bet_key,
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn compute_roll_is_a_deterministic_d100(
+            sig in prop::collection::vec(any::<u8>(), 64),
+            slot_hash in any::<[u8; 32]>(),
+        ) {
+            let roll = compute_roll(&sig, &slot_hash);
+            prop_assert!((1..=100).contains(&roll));
+            prop_assert_eq!(roll, compute_roll(&sig, &slot_hash));
+        }
+    }
+
+    #[test]
+    fn compute_roll_handles_boundary_inputs() {
+        assert_eq!(compute_roll(&[0; 64], &[0; 32]), 63);
+        assert_eq!(compute_roll(&[0xFF; 64], &[0xFF; 32]), 35);
+    }
+
+    #[test]
+    fn compute_roll_is_uniform() {
+        const SAMPLES: u32 = 100_000;
+        let mut counts = [0u32; 100];
+        for i in 0..SAMPLES {
+            let mut sig = [0u8; 64];
+            sig[..4].copy_from_slice(&i.to_le_bytes());
+            counts[compute_roll(&sig, &[0; 32]) as usize - 1] += 1;
+        }
+        let expected = SAMPLES as f64 / 100.0;
+        let chi_square: f64 = counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum();
+        // The 99.9th percentile of chi-square with 99 degrees of freedom.
+        assert!(chi_square < 148.2, "chi-square {chi_square} over 100 buckets");
+    }
+}
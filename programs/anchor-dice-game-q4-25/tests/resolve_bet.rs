@@ -0,0 +1,426 @@
+//! Places and resolves bets against the built program in LiteSVM, with the
+//! player's signature checked by the real ed25519 precompile. Run
+//! `anchor build` first so `target/deploy/anchor_dice_2026.so` exists.
+
+use anchor_dice_2026::{
+    accounts, errors::DiceError, instruction, Bet, BetArgs, BetDirection, FeeTier, HouseConfig, HouseConfigArgs,
+    PrecisionMode, RandomnessSource, VolumeRebate,
+};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use litesvm::LiteSVM;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    ed25519_program,
+    hash::Hash,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    slot_hashes::SlotHashes,
+    system_program,
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+const GAME_ID: u8 = 0;
+const FEE_BPS: u16 = 150;
+const ROLL: u8 = 50;
+const AMOUNT: u64 = LAMPORTS_PER_SOL / 10;
+
+struct Game {
+    svm: LiteSVM,
+    house: Keypair,
+    resolver: Keypair,
+    player: Keypair,
+    seed: u128,
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &anchor_dice_2026::ID).0
+}
+
+impl Game {
+    /// A funded, configured house and a funded player.
+    fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let program = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/anchor_dice_2026.so");
+        svm.add_program_from_file(anchor_dice_2026::ID, program)
+            .expect("run `anchor build` first");
+
+        let house = Keypair::new();
+        let resolver = Keypair::new();
+        let player = Keypair::new();
+        for key in [house.pubkey(), resolver.pubkey(), player.pubkey()] {
+            svm.airdrop(&key, 10 * LAMPORTS_PER_SOL).unwrap();
+        }
+        // `place_bet` records the newest slot hash, so the sysvar can't be
+        // empty.
+        svm.set_sysvar(&SlotHashes::new(&[(0, Hash::new_unique())]));
+
+        let mut game = Game { svm, house, resolver, player, seed: 0 };
+        game.initialize();
+        game.fund_vault(2 * LAMPORTS_PER_SOL);
+        game.initialize_config();
+        game
+    }
+
+    fn house_pda(&self, name: &[u8]) -> Pubkey {
+        pda(&[name, self.house.pubkey().as_ref(), &[GAME_ID]])
+    }
+
+    fn vault(&self) -> Pubkey {
+        self.house_pda(b"vault")
+    }
+
+    fn config(&self) -> Pubkey {
+        self.house_pda(b"config")
+    }
+
+    fn stats(&self) -> Pubkey {
+        pda(&[b"stats", self.house.pubkey().as_ref(), &[GAME_ID], self.player.pubkey().as_ref()])
+    }
+
+    fn bet_pda(&self, seed: u128) -> Pubkey {
+        pda(&[b"bet", self.vault().as_ref(), self.player.pubkey().as_ref(), &seed.to_le_bytes()])
+    }
+
+    fn send(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), TransactionError> {
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&signers[0].pubkey()),
+            signers,
+            self.svm.latest_blockhash(),
+        );
+        self.svm.send_transaction(transaction).map(|_| ()).map_err(|failed| failed.err)
+    }
+
+    fn fetch<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self.svm.get_account(address).unwrap();
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    fn lamports(&self, address: &Pubkey) -> u64 {
+        self.svm.get_account(address).map_or(0, |account| account.lamports)
+    }
+
+    fn initialize(&mut self) {
+        let initialize = Instruction {
+            program_id: anchor_dice_2026::ID,
+            accounts: accounts::Initialize {
+                house: self.house.pubkey(),
+                vault: self.vault(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Initialize { _game_id: GAME_ID, amount: 0 }.data(),
+        };
+        let house = self.house.insecure_clone();
+        self.send(&[initialize], &[&house]).unwrap();
+    }
+
+    /// A house with every side payment off and fees reinvested, so a
+    /// resolution only moves the stake and payout.
+    fn initialize_config(&mut self) {
+        let house = self.house.pubkey();
+        let args = HouseConfigArgs {
+            game_id: GAME_ID,
+            resolver: self.resolver.pubkey(),
+            fee_bps: FEE_BPS,
+            fee_tiers: [FeeTier::default(); 4],
+            min_bet: LAMPORTS_PER_SOL / 100,
+            max_bet: LAMPORTS_PER_SOL,
+            referral_bps: 0,
+            jackpot_contribution_bps: 0,
+            insurance_bps: 0,
+            rebalance_threshold: 0,
+            max_payout: 2 * LAMPORTS_PER_SOL,
+            max_exposure_bps: 10_000,
+            streak_bonus_bps: 0,
+            max_streak_bonus_bps: 0,
+            risk_free_enabled: false,
+            round_up_payouts: false,
+            bonus_roll_offset: 0,
+            player_wins_ties: true,
+            bet_cooldown_slots: 0,
+            large_win_threshold: 0,
+            challenge_slots: 0,
+            max_open_bets_per_player: 0,
+            sol_usd_feed_id: [0; 32],
+            max_price_age_secs: 60,
+            near_miss_window: 0,
+            near_miss_bps: 0,
+            signature_window_slots: 0,
+            rewards_enabled: false,
+            reward_mint: None,
+            reward_rate: 0,
+            keeper_fee_bps: 0,
+            max_total_exposure_bps: 10_000,
+            bet_burn_amount: 0,
+            burn_mint: None,
+            reinvest_fees: true,
+            min_vault_balance: 0,
+            reveal_delay_slots: 11,
+            withdrawal_timelock_slots: 0,
+            min_fee_lamports: 0,
+            min_jackpot: 0,
+            resolution_window_slots: 0,
+            charity: None,
+            charity_bps: 0,
+            consume_seeds: false,
+            cancel_fee_bps: 0,
+            auto_resolve: false,
+            volume_rebates: [VolumeRebate::default(); 4],
+            cache_outcomes: false,
+        };
+        let initialize_config = Instruction {
+            program_id: anchor_dice_2026::ID,
+            accounts: accounts::InitializeConfig {
+                house,
+                vault: self.vault(),
+                config: self.config(),
+                jackpot: self.house_pda(b"jackpot"),
+                jackpots: self.house_pda(b"jackpots"),
+                fee_vault: self.house_pda(b"fee_vault"),
+                treasury: self.house_pda(b"treasury"),
+                recent_outcomes: self.house_pda(b"recent_outcomes"),
+                ledger: self.house_pda(b"ledger"),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::InitializeConfig { args }.data(),
+        };
+        let house = self.house.insecure_clone();
+        self.send(&[initialize_config], &[&house]).unwrap();
+    }
+
+    fn fund_vault(&mut self, amount: u64) {
+        let fund_vault = Instruction {
+            program_id: anchor_dice_2026::ID,
+            accounts: accounts::FundVault {
+                funder: self.house.pubkey(),
+                house: self.house.pubkey(),
+                vault: self.vault(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::FundVault { _game_id: GAME_ID, amount }.data(),
+        };
+        let house = self.house.insecure_clone();
+        self.send(&[fund_vault], &[&house]).unwrap();
+    }
+
+    /// Places a bet on rolling at most `ROLL` and returns it.
+    fn place_bet(&mut self) -> (Pubkey, Bet) {
+        self.seed += 1;
+        let bet = self.bet_pda(self.seed);
+        let player = self.player.pubkey();
+        let args = BetArgs {
+            roll: ROLL,
+            amount: AMOUNT,
+            direction: BetDirection::Under,
+            dice_count: 0,
+            player_commit: [0; 32],
+            referrer: None,
+            risk_free: false,
+            roll_low: 0,
+            roll_high: 0,
+            usd_denominated: false,
+            memo: [0; 32],
+            legs: vec![],
+            precision: PrecisionMode::Percent,
+            roll_bps: 0,
+            faces: 100,
+            randomness_source: RandomnessSource::Signature,
+            client_seed: [0; 32],
+        };
+        let place_bet = Instruction {
+            program_id: anchor_dice_2026::ID,
+            accounts: accounts::PlaceBet {
+                player,
+                rent_payer: player,
+                house: self.house.pubkey(),
+                vault: self.vault(),
+                config: self.config(),
+                stats: self.stats(),
+                bet,
+                price_update: None,
+                slot_hashes: sysvar::slot_hashes::ID,
+                allowlisted: None,
+                self_exclusion: pda(&[b"self_exclusion", player.as_ref()]),
+                used_seed: None,
+                player_balance: None,
+                free_credits: None,
+                burn_mint: None,
+                player_burn_ata: None,
+                token_program: None,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::PlaceBet { seed: self.seed, args }.data(),
+        };
+        let signer = self.player.insecure_clone();
+        self.send(&[place_bet], &[&signer]).unwrap();
+        (bet, self.fetch(&bet))
+    }
+
+    /// The player's signature over `bet`, and the ed25519 instruction
+    /// verifying it, laid out as `Ed25519Program.createInstructionWithPrivateKey`
+    /// does: the offsets, then the public key, signature and message.
+    fn sign(&self, bet: &Bet) -> ([u8; 64], Instruction) {
+        let message = bet.to_slice();
+        let signature: [u8; 64] = self.player.sign_message(&message).into();
+        const PUBLIC_KEY_OFFSET: u16 = 16;
+        const SIGNATURE_OFFSET: u16 = PUBLIC_KEY_OFFSET + 32;
+        const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+        let mut data = vec![1, 0];
+        for field in [
+            SIGNATURE_OFFSET,
+            u16::MAX,
+            PUBLIC_KEY_OFFSET,
+            u16::MAX,
+            MESSAGE_OFFSET,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(self.player.pubkey().as_ref());
+        data.extend_from_slice(&signature);
+        data.extend_from_slice(&message);
+        (signature, Instruction { program_id: ed25519_program::ID, accounts: vec![], data })
+    }
+
+    /// Produces the bet's reveal slot with a hash that makes `signature` win
+    /// or lose it, as asked, and moves the clock past it.
+    fn reveal(&mut self, bet: &Bet, signature: &[u8; 64], win: bool) {
+        let config: HouseConfig = self.fetch(&self.config());
+        let reveal_slot = config.reveal_slot(bet.slot).unwrap();
+        let slot_hash = (0u64..)
+            .map(|candidate| {
+                let mut hash = [0; 32];
+                hash[..8].copy_from_slice(&candidate.to_le_bytes());
+                hash
+            })
+            .find(|hash| bet.is_winner(&bet.rolls(signature, hash)) == win)
+            .unwrap();
+        self.svm.set_sysvar(&SlotHashes::new(&[
+            (reveal_slot, Hash::new_from_array(slot_hash)),
+            (reveal_slot - 1, Hash::new_unique()),
+        ]));
+        self.svm.warp_to_slot(reveal_slot + 1);
+    }
+
+    fn resolve_bet(&mut self, bet: Pubkey, sig: [u8; 64], ed25519: Instruction) -> Result<(), TransactionError> {
+        let player = self.player.pubkey();
+        let resolve_bet = Instruction {
+            program_id: anchor_dice_2026::ID,
+            accounts: accounts::ResolveBet {
+                resolver: self.resolver.pubkey(),
+                house: self.house.pubkey(),
+                vault: self.vault(),
+                config: self.config(),
+                jackpot: self.house_pda(b"jackpot"),
+                jackpots: self.house_pda(b"jackpots"),
+                fee_vault: self.house_pda(b"fee_vault"),
+                treasury: self.house_pda(b"treasury"),
+                recent_outcomes: self.house_pda(b"recent_outcomes"),
+                ledger: self.house_pda(b"ledger"),
+                player,
+                bet,
+                rent_payer: player,
+                stats: self.stats(),
+                player_balance: None,
+                pending_payout: None,
+                price_update: None,
+                reward_mint: None,
+                player_reward_ata: None,
+                token_program: None,
+                slot_hashes: Some(sysvar::slot_hashes::ID),
+                instructions: sysvar::instructions::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::ResolveBet { sig: sig.to_vec() }.data(),
+        };
+        let resolver = self.resolver.insecure_clone();
+        let compute_budget = ComputeBudgetInstruction::set_compute_unit_limit(400_000);
+        self.send(&[compute_budget, ed25519, resolve_bet], &[&resolver])
+    }
+}
+
+#[test]
+fn resolves_a_win() {
+    let mut game = Game::new();
+    let (bet_pda, bet) = game.place_bet();
+    let (sig, ed25519) = game.sign(&bet);
+    game.reveal(&bet, &sig, true);
+    let player = game.player.pubkey();
+    let player_before = game.lamports(&player);
+    let vault_before = game.lamports(&game.vault());
+    let bet_rent = game.lamports(&bet_pda);
+
+    game.resolve_bet(bet_pda, sig, ed25519).unwrap();
+
+    let payout = bet.payout().unwrap();
+    assert_eq!(game.lamports(&player), player_before + payout + bet_rent);
+    assert_eq!(game.lamports(&game.vault()), vault_before - payout);
+    assert_eq!(game.lamports(&bet_pda), 0);
+}
+
+#[test]
+fn resolves_a_loss() {
+    let mut game = Game::new();
+    let (bet_pda, bet) = game.place_bet();
+    let (sig, ed25519) = game.sign(&bet);
+    game.reveal(&bet, &sig, false);
+    let player = game.player.pubkey();
+    let player_before = game.lamports(&player);
+    let vault_before = game.lamports(&game.vault());
+    let bet_rent = game.lamports(&bet_pda);
+
+    game.resolve_bet(bet_pda, sig, ed25519).unwrap();
+
+    // The player only gets the bet account's rent back; the stake stays.
+    assert_eq!(game.lamports(&player), player_before + bet_rent);
+    assert_eq!(game.lamports(&game.vault()), vault_before);
+}
+
+#[test]
+fn rejects_a_signature_the_ed25519_instruction_does_not_carry() {
+    let mut game = Game::new();
+    let (bet_pda, bet) = game.place_bet();
+    let (sig, ed25519) = game.sign(&bet);
+    game.reveal(&bet, &sig, true);
+    let mut forged = sig;
+    forged[0] ^= 1;
+
+    let err = game.resolve_bet(bet_pda, forged, ed25519).unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(2, InstructionError::Custom(DiceError::Ed25519SignatureValue.into()))
+    );
+}
+
+#[test]
+fn rejects_a_win_the_vault_cannot_pay() {
+    let mut game = Game::new();
+    let (bet_pda, bet) = game.place_bet();
+    let (sig, ed25519) = game.sign(&bet);
+    game.reveal(&bet, &sig, true);
+    // Leave the vault one lamport short of the payout, as if it had been
+    // drained by other wins since the bet was placed.
+    let vault = game.vault();
+    let mut account = game.svm.get_account(&vault).unwrap();
+    account.lamports = bet.payout().unwrap() - 1;
+    game.svm.set_account(vault, account).unwrap();
+
+    let err = game.resolve_bet(bet_pda, sig, ed25519).unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(2, InstructionError::Custom(DiceError::VaultInsufficientFunds.into()))
+    );
+    assert!(game.svm.get_account(&bet_pda).is_some(), "The bet should stay open");
+}
@@ -7,10 +7,178 @@ import {
   SystemProgram,
   LAMPORTS_PER_SOL,
   Ed25519Program,
+  ComputeBudgetProgram,
   Transaction,
+  TransactionInstruction,
   sendAndConfirmTransaction,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SYSVAR_SLOT_HASHES_PUBKEY,
 } from "@solana/web3.js";
 import { assert, expect } from "chai";
+import { createHash } from "crypto";
+
+const encodeOption = (key: PublicKey | null): Buffer =>
+  key
+    ? Buffer.concat([Buffer.from([1]), key.toBuffer()])
+    : Buffer.from([0]);
+
+const BET_MESSAGE_VERSION = 11;
+const REFUND_DELAY_SLOTS = 1000;
+
+// Borsh variant index of a `BetDirection`.
+const directionIndex = (direction: any): number =>
+  ["under", "over", "exact", "range"].findIndex((name) => name in direction);
+
+// Borsh variant index of a `PrecisionMode`.
+const precisionIndex = (precision: any): number =>
+  ["percent", "basis"].findIndex((name) => name in precision);
+
+// Mirrors `Bet::to_slice` on-chain: the message the player signs over.
+const betMessage = (bet: any): Buffer =>
+  Buffer.concat([
+    Buffer.from([BET_MESSAGE_VERSION]),
+    bet.player.toBuffer(),
+    bet.seed.toArrayLike(Buffer, "le", 16),
+    bet.nonce.toArrayLike(Buffer, "le", 8),
+    bet.slot.toArrayLike(Buffer, "le", 8),
+    bet.expirySlot.toArrayLike(Buffer, "le", 8),
+    bet.amount.toArrayLike(Buffer, "le", 8),
+    Buffer.from([bet.roll, bet.bump]),
+    encodeOption(bet.mint),
+    Buffer.from([directionIndex(bet.direction), bet.diceCount]),
+    Buffer.from([bet.rollLow, bet.rollHigh]),
+    Buffer.from(bet.playerCommit),
+    encodeOption(bet.referrer),
+    Buffer.from([bet.riskFree ? 1 : 0, bet.usdDenominated ? 1 : 0]),
+    bet.entryPrice.toArrayLike(Buffer, "le", 8),
+    Buffer.from(new Int32Array([bet.entryPriceExpo]).buffer),
+    bet.signatureWindowSlots.toArrayLike(Buffer, "le", 8),
+    Buffer.from([precisionIndex(bet.precision)]),
+    new anchor.BN(bet.rollBps).toArrayLike(Buffer, "le", 2),
+    new anchor.BN(bet.faces).toArrayLike(Buffer, "le", 2),
+    Buffer.from([bet.legCount]),
+    ...bet.legs
+      .slice(0, bet.legCount)
+      .map((leg: any) =>
+        Buffer.from([leg.roll, directionIndex(leg.direction)]),
+      ),
+    Buffer.from(bet.clientSeed),
+  ]);
+
+// Packs several single-signature ed25519 instructions into one, the way a
+// relayer bundling unrelated verifications would.
+const bundleEd25519 = (
+  singles: TransactionInstruction[],
+): TransactionInstruction => {
+  const headerLength = 2 + 14 * singles.length;
+  const header = Buffer.alloc(headerLength);
+  header.writeUInt8(singles.length, 0);
+  const bodies: Buffer[] = [];
+  let offset = headerLength;
+  singles.forEach((ix, i) => {
+    const publicKey = ix.data.subarray(16, 48);
+    const signature = ix.data.subarray(48, 112);
+    const message = ix.data.subarray(112);
+    const base = 2 + 14 * i;
+    header.writeUInt16LE(offset + 32, base);
+    header.writeUInt16LE(0xffff, base + 2);
+    header.writeUInt16LE(offset, base + 4);
+    header.writeUInt16LE(0xffff, base + 6);
+    header.writeUInt16LE(offset + 96, base + 8);
+    header.writeUInt16LE(message.length, base + 10);
+    header.writeUInt16LE(0xffff, base + 12);
+    bodies.push(publicKey, signature, message);
+    offset += 96 + message.length;
+  });
+  return new TransactionInstruction({
+    programId: Ed25519Program.programId,
+    keys: [],
+    data: Buffer.concat([header, ...bodies]),
+  });
+};
+
+// Mirrors `utils::ROLL_DOMAIN` on-chain.
+const ROLL_DOMAIN = Buffer.from("dice-roll-v1");
+
+// Mirrors `utils::outcome_hash` on-chain: the domain tag, the randomness
+// input, then the bet's placement slot hash.
+const outcomeHash = (
+  sig: Uint8Array,
+  slotHash: Uint8Array = Buffer.alloc(32),
+): Buffer =>
+  createHash("sha256")
+    .update(ROLL_DOMAIN)
+    .update(sig)
+    .update(slotHash)
+    .digest();
+
+// Reduces a 256-bit little-endian hash to a roll in 1..=100.
+const rollFromHash = (hash: Uint8Array): number => {
+  const value = BigInt("0x" + Buffer.from(hash).reverse().toString("hex"));
+  return Number(value % 100n) + 1;
+};
+
+// Mirrors `utils::compute_roll` on-chain.
+const computeRoll = (
+  sig: Uint8Array,
+  slotHash: Uint8Array = Buffer.alloc(32),
+): number => rollFromHash(outcomeHash(sig, slotHash));
+
+// Mirrors `utils::compute_face_roll` on-chain: 1..=faces.
+const computeFaceRoll = (
+  sig: Uint8Array,
+  slotHash: Uint8Array,
+  faces: number,
+): number => {
+  const hash = outcomeHash(sig, slotHash).reverse();
+  return Number(BigInt("0x" + hash.toString("hex")) % BigInt(faces)) + 1;
+};
+
+// Mirrors `utils::compute_roll_u16` on-chain: 0..=9999 for basis-point bets.
+const computeRollU16 = (
+  sig: Uint8Array,
+  slotHash: Uint8Array = Buffer.alloc(32),
+): number => {
+  const hash = outcomeHash(sig, slotHash).reverse();
+  return Number(BigInt("0x" + hash.toString("hex")) % 10_000n);
+};
+
+// Mirrors `utils::LEG_DOMAIN` on-chain.
+const LEG_DOMAIN = Buffer.from("dice-leg-v1");
+
+// Mirrors `utils::compute_leg_rolls` on-chain: one d100 per leg, from the
+// first 8 bytes of the outcome hash rehashed under the leg's index.
+const computeLegRolls = (
+  sig: Uint8Array,
+  slotHash: Uint8Array = Buffer.alloc(32),
+): number[] => {
+  const hash = outcomeHash(sig, slotHash);
+  return [0, 1, 2].map((leg) =>
+    Number(
+      createHash("sha256")
+        .update(LEG_DOMAIN)
+        .update(Buffer.from([leg]))
+        .update(hash)
+        .digest()
+        .readBigUInt64LE(0) % 100n,
+    ) + 1,
+  );
+};
+
+// Mirrors `utils::payout_for` on-chain.
+const payoutFor = (
+  amount: number,
+  winningOutcomes: number,
+  totalOutcomes: number,
+  feeBps: number,
+  roundUp = false,
+): number => {
+  const numerator =
+    BigInt(amount) * BigInt(totalOutcomes) * BigInt(10_000 - feeBps);
+  const denominator = BigInt(winningOutcomes) * 10_000n;
+  const payout = numerator / denominator;
+  return Number(roundUp && numerator % denominator ? payout + 1n : payout);
+};
 
 describe("anchor-dice-game-q4-25", () => {
   // Configure the client to use the local cluster.
@@ -24,8 +192,429 @@ describe("anchor-dice-game-q4-25", () => {
   console.log(program.programId.toBase58());
   const house = Keypair.generate();
   const player = Keypair.generate();
+  const resolver = Keypair.generate();
 
   let vault: PublicKey;
+  let config: PublicKey;
+  let jackpot: PublicKey;
+  let jackpots: PublicKey;
+  let feeVault: PublicKey;
+  let treasury: PublicKey;
+  let recentOutcomes: PublicKey;
+  let ledger: PublicKey;
+  let stats: PublicKey;
+  const feeBps = 150;
+  const under = { under: {} };
+
+  const betArgs = (roll: number, amount: anchor.BN, overrides = {}) => ({
+    roll,
+    amount,
+    direction: under,
+    diceCount: 0,
+    playerCommit: Array(32).fill(0),
+    referrer: null,
+    riskFree: false,
+    rollLow: 0,
+    rollHigh: 0,
+    usdDenominated: false,
+    memo: Array(32).fill(0),
+    legs: [],
+    precision: { percent: {} },
+    rollBps: 0,
+    faces: 100,
+    randomnessSource: { signature: {} },
+    clientSeed: Array(32).fill(0),
+    ...overrides,
+  });
+  const minBet = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+  const maxBet = new anchor.BN(1 * LAMPORTS_PER_SOL);
+  const referralBps = 2_000;
+  const jackpotContributionBps = 1_000;
+  const maxExposureBps = 10_000;
+  const streakBonusBps = 100;
+  const maxStreakBonusBps = 500;
+  const noFeeTiers = Array(4).fill({ minAmount: new anchor.BN(0), feeBps: 0 });
+  const noVolumeRebates = Array(4).fill({
+    minWagered: new anchor.BN(0),
+    rebateBps: 0,
+  });
+  // A max-size bet at roll 50 pays exactly the cap.
+  const maxPayout = new anchor.BN(
+    payoutFor(maxBet.toNumber(), 50, 100, feeBps),
+  );
+
+  // Every PDA for one of a house's games, following the seed scheme in
+  // `state/mod.rs`.
+  const selfExclusionFor = (playerKey: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("self_exclusion"), playerKey.toBuffer()],
+      program.programId,
+    )[0];
+
+  const housePdas = (houseKey: PublicKey, gameId = 0) => {
+    const pda = (...seeds: Buffer[]) =>
+      PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const game = Buffer.from([gameId]);
+    const houseVault = pda(Buffer.from("vault"), houseKey.toBuffer(), game);
+    return {
+      vault: houseVault,
+      config: pda(Buffer.from("config"), houseKey.toBuffer(), game),
+      jackpot: pda(Buffer.from("jackpot"), houseKey.toBuffer(), game),
+      jackpots: pda(Buffer.from("jackpots"), houseKey.toBuffer(), game),
+      feeVault: pda(Buffer.from("fee_vault"), houseKey.toBuffer(), game),
+      treasury: pda(Buffer.from("treasury"), houseKey.toBuffer(), game),
+      recentOutcomes: pda(
+        Buffer.from("recent_outcomes"),
+        houseKey.toBuffer(),
+        game,
+      ),
+      ledger: pda(Buffer.from("ledger"), houseKey.toBuffer(), game),
+      stats: (playerKey: PublicKey) =>
+        pda(
+          Buffer.from("stats"),
+          houseKey.toBuffer(),
+          game,
+          playerKey.toBuffer(),
+        ),
+      freeCredits: (playerKey: PublicKey) =>
+        pda(
+          Buffer.from("free_credits"),
+          houseKey.toBuffer(),
+          game,
+          playerKey.toBuffer(),
+        ),
+      playerBalance: (playerKey: PublicKey) =>
+        pda(
+          Buffer.from("balance"),
+          houseKey.toBuffer(),
+          game,
+          playerKey.toBuffer(),
+        ),
+      bet: (seed: anchor.BN, playerKey = player.publicKey) =>
+        pda(
+          Buffer.from("bet"),
+          houseVault.toBuffer(),
+          playerKey.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 16),
+        ),
+      usedSeed: (seed: anchor.BN, playerKey = player.publicKey) =>
+        pda(
+          Buffer.from("used_seed"),
+          houseVault.toBuffer(),
+          playerKey.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 16),
+        ),
+    };
+  };
+
+  // Funds and configures an extra house, or another game of a house,
+  // alongside the main one.
+  const setupHouse = async (
+    houseKp: Keypair,
+    configOverrides = {},
+    gameId = 0,
+  ) => {
+    const pdas = housePdas(houseKp.publicKey, gameId);
+    houseKeypairs.set(houseKp.publicKey.toBase58(), houseKp);
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(houseKp.publicKey, 5 * LAMPORTS_PER_SOL),
+    );
+    await program.methods
+      .initialize(gameId, new anchor.BN(2 * LAMPORTS_PER_SOL))
+      .accountsStrict({
+        house: houseKp.publicKey,
+        vault: pdas.vault,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([houseKp])
+      .rpc();
+    await program.methods
+      .initializeConfig({
+        gameId,
+        resolver: resolver.publicKey,
+        feeBps,
+        feeTiers: noFeeTiers,
+        minBet,
+        maxBet,
+        referralBps,
+        jackpotContributionBps,
+        insuranceBps: 0,
+        rebalanceThreshold: new anchor.BN(0),
+        maxPayout,
+        maxExposureBps,
+        streakBonusBps,
+        maxStreakBonusBps,
+        riskFreeEnabled: false,
+        roundUpPayouts: false,
+        bonusRollOffset: 0,
+        playerWinsTies: true,
+        betCooldownSlots: new anchor.BN(0),
+        largeWinThreshold: new anchor.BN(0),
+        challengeSlots: new anchor.BN(0),
+        maxOpenBetsPerPlayer: 0,
+        solUsdFeedId: Array(32).fill(0),
+        maxPriceAgeSecs: new anchor.BN(60),
+        nearMissWindow: 0,
+        nearMissBps: 0,
+        signatureWindowSlots: new anchor.BN(0),
+        rewardsEnabled: false,
+        rewardMint: null,
+        rewardRate: new anchor.BN(0),
+        keeperFeeBps: 0,
+        maxTotalExposureBps: 10_000,
+        betBurnAmount: new anchor.BN(0),
+        burnMint: null,
+        reinvestFees: false,
+        minVaultBalance: new anchor.BN(0),
+        revealDelaySlots: new anchor.BN(minRevealDelaySlots),
+        withdrawalTimelockSlots: new anchor.BN(0),
+        minFeeLamports: new anchor.BN(0),
+        minJackpot: new anchor.BN(0),
+        resolutionWindowSlots: new anchor.BN(0),
+        charity: null,
+        charityBps: 0,
+        consumeSeeds: false,
+        cancelFeeBps: 0,
+        autoResolve: false,
+        volumeRebates: noVolumeRebates,
+        cacheOutcomes: false,
+        ...configOverrides,
+      })
+      .accountsStrict({
+        house: houseKp.publicKey,
+        vault: pdas.vault,
+        config: pdas.config,
+        jackpot: pdas.jackpot,
+        jackpots: pdas.jackpots,
+        feeVault: pdas.feeVault,
+        treasury: pdas.treasury,
+        recentOutcomes: pdas.recentOutcomes,
+        ledger: pdas.ledger,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([houseKp])
+      .rpc();
+    return pdas;
+  };
+
+  // Configures a house's mini, major and grand jackpots as
+  // `[triggerRoll, contributionBps]` pairs.
+  const setJackpotTiers = (
+    authority: Keypair,
+    houseKey: PublicKey,
+    tiers: [number, number][],
+  ) => {
+    const pdas = housePdas(houseKey);
+    return program.methods
+      .setJackpotTiers(
+        tiers.map(([triggerRoll, contributionBps]) => ({
+          triggerRoll,
+          contributionBps,
+        })),
+      )
+      .accountsStrict({
+        authority: authority.publicKey,
+        house: houseKey,
+        config: pdas.config,
+        jackpots: pdas.jackpots,
+      })
+      .signers([authority])
+      .rpc();
+  };
+
+  // Cancels an open bet while it is still inside the cancel window.
+  const cancelBetFor = (
+    playerKp: Keypair,
+    houseKey: PublicKey,
+    betPda: PublicKey,
+    freeCredits: PublicKey | null = null,
+  ) => {
+    const pdas = housePdas(houseKey);
+    return program.methods
+      .cancelBet()
+      .accountsStrict({
+        player: playerKp.publicKey,
+        rentPayer: playerKp.publicKey,
+        house: houseKey,
+        vault: pdas.vault,
+        config: pdas.config,
+        bet: betPda,
+        stats: pdas.stats(playerKp.publicKey),
+        freeCredits,
+        playerBalance: null,
+        feeVault: pdas.feeVault,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([playerKp])
+      .rpc();
+  };
+
+  // Authorities of the houses `setupHouse` creates, keyed by house address,
+  // so `discardBet` can pause one on its behalf.
+  const houseKeypairs = new Map<string, Keypair>([
+    [house.publicKey.toBase58(), house],
+  ]);
+
+  // `SLOT_HASH_DELAY_SLOTS` in `randomness.rs`: the shortest reveal delay a
+  // house can set, one slot past the cancel window.
+  const minRevealDelaySlots = 11;
+
+  // Waits for a bet's reveal slot to pass, then returns the hash its outcome
+  // is drawn from: the oldest `SlotHashes` entry at or after that slot, as
+  // `utils::slot_hash_at` picks it.
+  const revealedSlotHash = async (bet: any, configPda: PublicKey) => {
+    const { revealDelaySlots } =
+      await program.account.houseConfig.fetch(configPda);
+    const revealSlot =
+      bet.slot.toNumber() +
+      Math.max(revealDelaySlots.toNumber(), minRevealDelaySlots);
+    // SlotHashes only holds slots older than the current one.
+    while ((await connection.getSlot("confirmed")) <= revealSlot + 1) {
+      await new Promise((resolve) => setTimeout(resolve, 200));
+    }
+    const { data } = await connection.getAccountInfo(
+      SYSVAR_SLOT_HASHES_PUBKEY,
+      "confirmed",
+    );
+    let slotHash: Buffer;
+    // Entries are newest first, after a u64 count.
+    for (let offset = 8; offset + 40 <= data.length; offset += 40) {
+      if (data.readBigUInt64LE(offset) < BigInt(revealSlot)) break;
+      slotHash = data.subarray(offset + 8, offset + 40);
+    }
+    return slotHash;
+  };
+
+  // Refunds an open bet past its cancel window, pausing its house for the
+  // emergency refund and resuming it straight after.
+  const discardBet = async (
+    houseKey: PublicKey,
+    betPda: PublicKey,
+    freeCredits: PublicKey | null = null,
+  ) => {
+    const authority = houseKeypairs.get(houseKey.toBase58());
+    const pdas = housePdas(houseKey);
+    const bet = await program.account.bet.fetch(betPda);
+    const setPaused = (paused: boolean) =>
+      program.methods
+        .setPaused(paused)
+        .accountsStrict({
+          authority: authority.publicKey,
+          house: houseKey,
+          config: pdas.config,
+        })
+        .signers([authority])
+        .rpc();
+    await setPaused(true);
+    await program.methods
+      .emergencyRefund()
+      .accountsStrict({
+        authority: authority.publicKey,
+        house: houseKey,
+        vault: pdas.vault,
+        config: pdas.config,
+        player: bet.player,
+        rentPayer: bet.rentPayer,
+        bet: betPda,
+        stats: pdas.stats(bet.player),
+        freeCredits,
+        playerBalance: bet.fromBalance ? pdas.playerBalance(bet.player) : null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await setPaused(false);
+  };
+
+  // Places a plain Under bet and signs its message as the player.
+  let outcomeSeed = 20_000;
+  const placeSignedBet = async (
+    playerKp: Keypair,
+    houseKey: PublicKey,
+    roll: number,
+    amount: anchor.BN,
+    freeCredits: PublicKey | null = null,
+  ) => {
+    const pdas = housePdas(houseKey);
+    const seed = new anchor.BN(outcomeSeed++);
+    const betPda = pdas.bet(seed, playerKp.publicKey);
+    await program.methods
+      .placeBet(seed, betArgs(roll, amount))
+      .accountsStrict({
+        player: playerKp.publicKey,
+        rentPayer: playerKp.publicKey,
+        house: houseKey,
+        vault: pdas.vault,
+        config: pdas.config,
+        stats: pdas.stats(playerKp.publicKey),
+        bet: betPda,
+        priceUpdate: null,
+        slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        allowlisted: null,
+        selfExclusion: selfExclusionFor(playerKp.publicKey),
+        usedSeed: null,
+        freeCredits,
+        playerBalance: null,
+        burnMint: null,
+        playerBurnAta: null,
+        tokenProgram: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([playerKp])
+      .rpc();
+
+    const bet = await program.account.bet.fetch(betPda);
+    const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: playerKp.secretKey,
+      message: betMessage(bet),
+    });
+    return { bet, betPda, ed25519Ix };
+  };
+
+  // The player's signature is deterministic, so the outcome is fixed once the
+  // reveal slot has passed, by which time the bet can no longer be
+  // cancelled. Bets landing on the wrong side are discarded until one gives
+  // the outcome a test needs; roll 1 is skipped so the jackpot never pays
+  // out.
+  const placeBetWithOutcome = async (
+    playerKp: Keypair,
+    houseKey: PublicKey,
+    roll: number,
+    amount: anchor.BN,
+    wantWin: boolean,
+    accept = (outcome: number) => outcome <= roll === wantWin,
+    freeCredits: PublicKey | null = null,
+  ) => {
+    const pdas = housePdas(houseKey);
+    for (;;) {
+      const { bet, betPda, ed25519Ix } = await placeSignedBet(
+        playerKp,
+        houseKey,
+        roll,
+        amount,
+        freeCredits,
+      );
+      const outcome = computeRoll(
+        ed25519Ix.data.subarray(48, 112),
+        await revealedSlotHash(bet, pdas.config),
+      );
+      if (outcome !== 1 && accept(outcome)) {
+        return { bet, betPda, ed25519Ix };
+      }
+      await discardBet(houseKey, betPda, freeCredits);
+    }
+  };
+
+  const findBetPda = (seed: anchor.BN, playerKey = player.publicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("bet"),
+        vault.toBytes(),
+        playerKey.toBytes(),
+        seed.toArrayLike(Buffer, "le", 16),
+      ],
+      program.programId,
+    )[0];
 
   before(async () => {
     const houseAirdrop = await connection.requestAirdrop(
@@ -40,18 +629,213 @@ describe("anchor-dice-game-q4-25", () => {
     );
     await connection.confirmTransaction(playerAirdrop);
 
+    const resolverAirdrop = await connection.requestAirdrop(
+      resolver.publicKey,
+      LAMPORTS_PER_SOL,
+    );
+    await connection.confirmTransaction(resolverAirdrop);
+
     [vault] = PublicKey.findProgramAddressSync(
-      [Buffer.from("vault"), house.publicKey.toBytes()],
+      [Buffer.from("vault"), house.publicKey.toBytes(), Buffer.from([0])],
+      program.programId,
+    );
+
+    [config] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config"), house.publicKey.toBytes(), Buffer.from([0])],
+      program.programId,
+    );
+
+    [jackpot] = PublicKey.findProgramAddressSync(
+      [Buffer.from("jackpot"), house.publicKey.toBytes(), Buffer.from([0])],
+      program.programId,
+    );
+    [jackpots] = PublicKey.findProgramAddressSync(
+      [Buffer.from("jackpots"), house.publicKey.toBytes(), Buffer.from([0])],
+      program.programId,
+    );
+
+    [feeVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault"), house.publicKey.toBytes(), Buffer.from([0])],
+      program.programId,
+    );
+
+    [treasury] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), house.publicKey.toBytes(), Buffer.from([0])],
+      program.programId,
+    );
+
+    [recentOutcomes] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("recent_outcomes"),
+        house.publicKey.toBytes(),
+        Buffer.from([0]),
+      ],
+      program.programId,
+    );
+
+    [ledger] = PublicKey.findProgramAddressSync(
+      [Buffer.from("ledger"), house.publicKey.toBytes(), Buffer.from([0])],
+      program.programId,
+    );
+
+    [stats] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("stats"),
+        house.publicKey.toBytes(),
+        Buffer.from([0]),
+        player.publicKey.toBytes(),
+      ],
       program.programId,
     );
   });
 
+  describe("Compute Roll", () => {
+    const vectors: [Buffer, number][] = [
+      [Buffer.alloc(64, 0x00), 63],
+      [Buffer.alloc(64, 0xff), 77],
+      [Buffer.from([...Array(64).keys()]), 81],
+    ];
+
+    it("Maps fixed signatures to fixed rolls", () => {
+      for (const [sig, expected] of vectors) {
+        assert.equal(
+          computeRoll(sig),
+          expected,
+          "Roll should be deterministic",
+        );
+      }
+    });
+
+    it("Rolls differently from an untagged hash of the signature", () => {
+      const slotHash = Buffer.alloc(32);
+      for (const [sig] of vectors) {
+        const untagged = createHash("sha256")
+          .update(sig)
+          .update(slotHash)
+          .digest();
+        assert.notDeepEqual(outcomeHash(sig, slotHash), untagged);
+        assert.notEqual(computeRoll(sig, slotHash), rollFromHash(untagged));
+      }
+    });
+
+    it("Spreads rolls evenly across 1..=100", () => {
+      const samples = 100_000;
+      const buckets = new Array(100).fill(0);
+      for (let i = 0; i < samples; i++) {
+        const sig = Buffer.alloc(64);
+        sig.writeUInt32LE(i);
+        buckets[computeRoll(sig) - 1]++;
+      }
+
+      const expected = samples / 100;
+      for (const count of buckets) {
+        assert.isTrue(
+          Math.abs(count - expected) < expected * 0.15,
+          `Bucket count ${count} outside tolerance of ${expected}`,
+        );
+      }
+    });
+
+    it("Changes the outcome with the slot hash", () => {
+      // Two bets with the same signature but different placement slot hashes
+      // collide only as often as independent rolls would (1 in 100).
+      const trials = 1_000;
+      let collisions = 0;
+      for (let i = 0; i < trials; i++) {
+        const sig = createHash("sha512").update(`salted-${i}`).digest();
+        const slotA = createHash("sha256").update(`slot-${i}-a`).digest();
+        const slotB = createHash("sha256").update(`slot-${i}-b`).digest();
+        if (computeRoll(sig, slotA) === computeRoll(sig, slotB)) collisions++;
+      }
+      assert.isBelow(collisions, 30);
+
+      const sig = Buffer.alloc(64, 0x00);
+      assert.notEqual(computeRoll(sig), computeRoll(sig, Buffer.alloc(32, 1)));
+    });
+
+    describe("over generated signatures", () => {
+      // Seeded rather than random so a run is reproducible: signature `i` is
+      // sha512(i), which is 64 bytes like a real ed25519 signature.
+      const samples = 50_000;
+      const signature = (i: number) =>
+        createHash("sha512").update(`sig-${i}`).digest();
+
+      it("Always rolls within 1..=100", () => {
+        for (let i = 0; i < samples; i++) {
+          const roll = computeRoll(signature(i));
+          assert.isTrue(
+            Number.isInteger(roll) && roll >= 1 && roll <= 100,
+            `Roll ${roll} out of range for signature ${i}`,
+          );
+        }
+      });
+
+      it("Rolls the same for identical input", () => {
+        for (let i = 0; i < 1_000; i++) {
+          const sig = signature(i);
+          assert.equal(computeRoll(sig), computeRoll(Buffer.from(sig)));
+        }
+      });
+
+      it("Passes a chi-square test against a uniform distribution", () => {
+        const buckets = new Array(100).fill(0);
+        for (let i = 0; i < samples; i++) {
+          buckets[computeRoll(signature(i)) - 1]++;
+        }
+        const expected = samples / 100;
+        const chiSquare = buckets.reduce(
+          (sum, count) => sum + (count - expected) ** 2 / expected,
+          0,
+        );
+        // Critical value for 99 degrees of freedom at p = 0.001.
+        assert.isBelow(chiSquare, 148.23);
+      });
+
+      it("Keeps single-byte variations of the all-zero and all-0xFF signatures in range", () => {
+        for (const fill of [0x00, 0xff]) {
+          for (let byte = 0; byte < 64; byte++) {
+            const sig = Buffer.alloc(64, fill);
+            sig[byte] ^= 0x01;
+            const roll = computeRoll(sig);
+            assert.isTrue(roll >= 1 && roll <= 100);
+          }
+        }
+      });
+    });
+  });
+
+  describe("Payout", () => {
+    const amount = 0.1 * LAMPORTS_PER_SOL;
+
+    it("Pays more for a low roll threshold", () => {
+      assert.equal(payoutFor(amount, 2, 100, 150), 4_925_000_000);
+    });
+
+    it("Pays less for a high roll threshold", () => {
+      assert.equal(payoutFor(amount, 95, 100, 150), 103_684_210);
+    });
+
+    it("Pays exactly double on an even-money bet with no house edge", () => {
+      assert.equal(payoutFor(amount, 50, 100, 0), 2 * amount);
+      assert.equal(payoutFor(101, 50, 100, 0), 202);
+    });
+
+    it("Pays roughly 100x minus the edge on an exact roll", () => {
+      assert.equal(payoutFor(amount, 1, 100, 150), 9_850_000_000);
+    });
+
+    it("Rounds odd amounts down in favour of the house", () => {
+      // 101 * 100 * 9850 / (50 * 10000) = 198.97
+      assert.equal(payoutFor(101, 50, 100, 150), 198);
+    });
+  });
+
   describe("Initialize", () => {
     it("Initializes the vault with funds", async () => {
       const amount = new anchor.BN(2 * LAMPORTS_PER_SOL);
 
       const sig = await program.methods
-        .initialize(amount)
+        .initialize(0, amount)
         .accountsStrict({
           house: house.publicKey,
           vault,
@@ -69,228 +853,9686 @@ describe("anchor-dice-game-q4-25", () => {
         "Vault should have 2 SOL",
       );
     });
-  });
-
-  describe("Place Bet", () => {
-    const seed = new anchor.BN(1);
-    const roll = 50;
-    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
-
-    it("Places a bet successfully", async () => {
-      const [betPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("bet"),
-          vault.toBytes(),
-          seed.toArrayLike(Buffer, "le", 16),
-        ],
-        program.programId,
-      );
 
-      const vaultBalanceBefore = await connection.getBalance(vault);
-
-      const sig = await program.methods
-        .placeBet(seed, roll, amount)
+    it("Initializes the house config", async () => {
+      await program.methods
+        .initializeConfig({
+          gameId: 0,
+          resolver: resolver.publicKey,
+          feeBps,
+          feeTiers: noFeeTiers,
+          minBet,
+          maxBet,
+          referralBps,
+          jackpotContributionBps,
+          insuranceBps: 0,
+          rebalanceThreshold: new anchor.BN(0),
+          maxPayout,
+          maxExposureBps,
+          streakBonusBps,
+          maxStreakBonusBps,
+          riskFreeEnabled: true,
+          roundUpPayouts: false,
+          bonusRollOffset: 0,
+          playerWinsTies: true,
+          betCooldownSlots: new anchor.BN(0),
+          largeWinThreshold: new anchor.BN(0),
+          challengeSlots: new anchor.BN(0),
+          maxOpenBetsPerPlayer: 0,
+          solUsdFeedId: Array(32).fill(0),
+          maxPriceAgeSecs: new anchor.BN(60),
+          nearMissWindow: 0,
+          nearMissBps: 0,
+          signatureWindowSlots: new anchor.BN(0),
+          rewardsEnabled: false,
+          rewardMint: null,
+          rewardRate: new anchor.BN(0),
+          keeperFeeBps: 0,
+          maxTotalExposureBps: 10_000,
+          betBurnAmount: new anchor.BN(0),
+          burnMint: null,
+          reinvestFees: false,
+          minVaultBalance: new anchor.BN(0),
+          revealDelaySlots: new anchor.BN(minRevealDelaySlots),
+          withdrawalTimelockSlots: new anchor.BN(0),
+          minFeeLamports: new anchor.BN(0),
+          minJackpot: new anchor.BN(0),
+          resolutionWindowSlots: new anchor.BN(0),
+          charity: null,
+          charityBps: 0,
+          consumeSeeds: false,
+          cancelFeeBps: 0,
+          autoResolve: false,
+          volumeRebates: noVolumeRebates,
+          cacheOutcomes: false,
+        })
         .accountsStrict({
-          player: player.publicKey,
           house: house.publicKey,
           vault,
-          bet: betPda,
+          config,
+          jackpot,
+          jackpots,
+          feeVault,
+          treasury,
+          recentOutcomes,
+          ledger,
           systemProgram: SystemProgram.programId,
         })
-        .signers([player])
+        .signers([house])
         .rpc();
 
-      console.log("Place Bet tx:", sig);
-
-      const betAccount = await program.account.bet.fetch(betPda);
+      const configAccount = await program.account.houseConfig.fetch(config);
       assert.equal(
-        betAccount.player.toString(),
-        player.publicKey.toString(),
-        "Bet player should match",
+        configAccount.authority.toString(),
+        house.publicKey.toString(),
+        "Config authority should be the house",
       );
       assert.equal(
-        betAccount.seed.toString(),
-        seed.toString(),
-        "Bet seed should match",
+        configAccount.houseId.toString(),
+        house.publicKey.toString(),
+        "Config should record the house the vault is seeded from",
+      );
+      assert.equal(configAccount.feeBps, feeBps, "Config fee should match");
+
+      const [, vaultBump] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), house.publicKey.toBytes(), Buffer.from([0])],
+        program.programId,
       );
-      assert.equal(betAccount.roll, roll, "Bet roll should match");
       assert.equal(
-        betAccount.amount.toString(),
-        amount.toString(),
-        "Bet amount should match",
+        configAccount.vaultBump,
+        vaultBump,
+        "Config should record the canonical vault bump",
       );
+    });
 
-      const vaultBalanceAfter = await connection.getBalance(vault);
+    it("Rejects a second config init without changing the config", async () => {
+      const configBefore = await program.account.houseConfig.fetch(config);
+      try {
+        await program.methods
+          .initializeConfig({
+            gameId: 0,
+            resolver: player.publicKey,
+            feeBps: 0,
+            feeTiers: noFeeTiers,
+            minBet,
+            maxBet,
+            referralBps,
+            jackpotContributionBps,
+            insuranceBps: 0,
+            rebalanceThreshold: new anchor.BN(0),
+            maxPayout,
+            maxExposureBps,
+            streakBonusBps,
+            maxStreakBonusBps,
+            riskFreeEnabled: false,
+            roundUpPayouts: false,
+            bonusRollOffset: 0,
+            playerWinsTies: true,
+            betCooldownSlots: new anchor.BN(0),
+            largeWinThreshold: new anchor.BN(0),
+            challengeSlots: new anchor.BN(0),
+            maxOpenBetsPerPlayer: 0,
+            solUsdFeedId: Array(32).fill(0),
+            maxPriceAgeSecs: new anchor.BN(60),
+            nearMissWindow: 0,
+            nearMissBps: 0,
+            signatureWindowSlots: new anchor.BN(0),
+            rewardsEnabled: false,
+            rewardMint: null,
+            rewardRate: new anchor.BN(0),
+            keeperFeeBps: 0,
+            maxTotalExposureBps: 10_000,
+            betBurnAmount: new anchor.BN(0),
+            burnMint: null,
+            reinvestFees: false,
+            minVaultBalance: new anchor.BN(0),
+            revealDelaySlots: new anchor.BN(minRevealDelaySlots),
+            withdrawalTimelockSlots: new anchor.BN(0),
+            minFeeLamports: new anchor.BN(0),
+            minJackpot: new anchor.BN(0),
+            resolutionWindowSlots: new anchor.BN(0),
+            charity: null,
+            charityBps: 0,
+            consumeSeeds: false,
+            cancelFeeBps: 0,
+            autoResolve: false,
+            volumeRebates: noVolumeRebates,
+            cacheOutcomes: false,
+          })
+          .accountsStrict({
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([house])
+          .rpc();
+        assert.fail("Config init should fail - already initialized");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConfigAlreadyInitialized");
+      }
+
+      const configAfter = await program.account.houseConfig.fetch(config);
       assert.equal(
-        vaultBalanceAfter - vaultBalanceBefore,
-        amount.toNumber(),
-        "Vault balance should increase by bet amount",
+        configAfter.resolver.toBase58(),
+        configBefore.resolver.toBase58(),
       );
+      assert.equal(configAfter.feeBps, configBefore.feeBps);
+      assert.equal(configAfter.riskFreeEnabled, configBefore.riskFreeEnabled);
     });
   });
 
-  describe("Refund Bet", () => {
-    const seed = new anchor.BN(99);
-    const roll = 50;
-    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
-    let betPda: PublicKey;
-
-    before(async () => {
-      [betPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("bet"),
-          vault.toBytes(),
-          seed.toArrayLike(Buffer, "le", 16),
-        ],
-        program.programId,
-      );
+  describe("Fund Vault", () => {
+    it("Tops up the vault and emits VaultFunded", async () => {
+      const amount = new anchor.BN(0.5 * LAMPORTS_PER_SOL);
+      const vaultBalanceBefore = await connection.getBalance(vault);
 
-      await program.methods
-        .placeBet(seed, roll, amount)
+      const sig = await program.methods
+        .fundVault(0, amount)
         .accountsStrict({
-          player: player.publicKey,
+          funder: player.publicKey,
           house: house.publicKey,
           vault,
-          bet: betPda,
           systemProgram: SystemProgram.programId,
         })
         .signers([player])
-        .rpc();
+        .rpc({ commitment: "confirmed" });
+
+      const vaultBalanceAfter = await connection.getBalance(vault);
+      assert.equal(
+        vaultBalanceAfter - vaultBalanceBefore,
+        amount.toNumber(),
+        "Vault should grow by the funded amount",
+      );
+
+      const txDetails = await connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(txDetails.meta.logMessages)];
+      const funded = events.find((e) => e.name === "vaultFunded");
+      assert.ok(funded, "VaultFunded event should be emitted");
+      assert.equal(funded.data.funder.toString(), player.publicKey.toString());
+      assert.equal(funded.data.amount.toString(), amount.toString());
+      assert.equal(funded.data.newBalance.toNumber(), vaultBalanceAfter);
     });
+  });
 
-    it("Fails to refund before timeout is reached", async () => {
+  describe("Update Fee", () => {
+    it("Rejects a fee above 10000 bps", async () => {
       try {
         await program.methods
-          .refundBet()
+          .updateFee(10_001)
           .accountsStrict({
-            player: player.publicKey,
+            authority: house.publicKey,
             house: house.publicKey,
-            vault,
-            bet: betPda,
-            systemProgram: SystemProgram.programId,
+            config,
           })
-          .signers([player])
+          .signers([house])
           .rpc();
-        assert.fail("Refund should fail - timeout not reached");
+        assert.fail("Update should fail - fee too high");
       } catch (err: any) {
-        console.log("Refund correctly rejected before timeout");
+        expect(err.error.errorCode.code).to.equal("FeeTooHigh");
       }
     });
-  });
-
-  describe("Resolve Bet", () => {
-    const seed = new anchor.BN(100);
-    const roll = 50;
-    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
-    let betPda: PublicKey;
-    const instructionSysvar = new PublicKey(
-      "Sysvar1nstructions1111111111111111111111111",
-    );
 
-    before(async () => {
-      [betPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("bet"),
-          vault.toBytes(),
-          seed.toArrayLike(Buffer, "le", 16),
-        ],
-        program.programId,
-      );
+    it("Rejects an update from a non-authority signer", async () => {
+      try {
+        await program.methods
+          .updateFee(100)
+          .accountsStrict({
+            authority: player.publicKey,
+            house: house.publicKey,
+            config,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Update should fail - wrong authority");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
 
+    it("Updates the fee as the authority", async () => {
       await program.methods
-        .placeBet(seed, roll, amount)
+        .updateFee(feeBps)
         .accountsStrict({
-          player: player.publicKey,
+          authority: house.publicKey,
           house: house.publicKey,
-          vault,
-          bet: betPda,
-          systemProgram: SystemProgram.programId,
+          config,
         })
-        .signers([player])
+        .signers([house])
         .rpc();
-    });
 
-    it("Resolves a bet correctly", async () => {
-      const betAccountInfo = await connection.getAccountInfo(betPda);
-      const betAccountBefore = await program.account.bet.fetch(betPda);
+      const configAccount = await program.account.houseConfig.fetch(config);
+      assert.equal(configAccount.feeBps, feeBps, "Config fee should match");
+    });
+  });
 
+  describe("Update Limits", () => {
+    const updateLimits = (min: anchor.BN, max: anchor.BN) =>
+      program.methods
+        .updateLimits(min, max)
+        .accountsStrict({
+          authority: house.publicKey,
+          house: house.publicKey,
+          config,
+        })
+        .signers([house])
+        .rpc({ commitment: "confirmed" });
+
+    it("Rejects a minimum above the maximum", async () => {
+      try {
+        await updateLimits(maxBet, minBet);
+        assert.fail("Update should fail - min above max");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidBetLimits");
+      }
+    });
+
+    it("Rejects a maximum above the payout cap", async () => {
+      try {
+        await updateLimits(minBet, maxPayout.addn(1));
+        assert.fail("Update should fail - max above payout cap");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("MaxBetExceedsPayoutCap");
+      }
+    });
+
+    it("Updates the limits and emits LimitsUpdated", async () => {
+      const newMin = minBet.muln(2);
+      const newMax = maxBet.divn(2);
+      const sig = await updateLimits(newMin, newMax);
+
+      const configAccount = await program.account.houseConfig.fetch(config);
+      assert.equal(configAccount.minBet.toString(), newMin.toString());
+      assert.equal(configAccount.maxBet.toString(), newMax.toString());
+
+      const txDetails = await connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(txDetails.meta.logMessages)];
+      const updated = events.find((e) => e.name === "limitsUpdated");
+      assert.ok(updated, "LimitsUpdated event should be emitted");
+      assert.equal(updated.data.minBet.toString(), newMin.toString());
+      assert.equal(updated.data.maxBet.toString(), newMax.toString());
+
+      // Later tests rely on the original limits.
+      await updateLimits(minBet, maxBet);
+    });
+  });
+
+  describe("Rotate Resolver", () => {
+    const rotateResolver = (newResolver: PublicKey) =>
+      program.methods
+        .rotateResolver(newResolver)
+        .accountsStrict({
+          authority: house.publicKey,
+          house: house.publicKey,
+          config,
+        })
+        .signers([house])
+        .rpc();
+
+    it("Rotates the resolver as the authority", async () => {
+      const next = Keypair.generate().publicKey;
+      await rotateResolver(next);
+      let configAccount = await program.account.houseConfig.fetch(config);
+      assert.equal(configAccount.resolver.toString(), next.toString());
+
+      await rotateResolver(resolver.publicKey);
+      configAccount = await program.account.houseConfig.fetch(config);
+      assert.equal(
+        configAccount.resolver.toString(),
+        resolver.publicKey.toString(),
+      );
+    });
+  });
+
+  describe("Authority Transfer", () => {
+    const adminHouse = Keypair.generate();
+    const admin = housePdas(adminHouse.publicKey);
+    const successor = Keypair.generate();
+
+    before(async () => {
+      await setupHouse(adminHouse);
+    });
+
+    const setPausedAs = (signer: Keypair, paused: boolean) =>
+      program.methods
+        .setPaused(paused)
+        .accountsStrict({
+          authority: signer.publicKey,
+          house: adminHouse.publicKey,
+          config: admin.config,
+        })
+        .signers([signer])
+        .rpc();
+
+    it("Keeps admin rights with the current authority until accepted", async () => {
+      await program.methods
+        .proposeAuthority(successor.publicKey)
+        .accountsStrict({
+          authority: adminHouse.publicKey,
+          house: adminHouse.publicKey,
+          config: admin.config,
+        })
+        .signers([adminHouse])
+        .rpc();
+
+      const configAccount = await program.account.houseConfig.fetch(
+        admin.config,
+      );
+      assert.equal(
+        configAccount.pendingAuthority.toBase58(),
+        successor.publicKey.toBase58(),
+      );
+      assert.equal(
+        configAccount.authority.toBase58(),
+        adminHouse.publicKey.toBase58(),
+      );
+      try {
+        await setPausedAs(successor, true);
+        assert.fail("Pause should fail - pending authority not accepted");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("Rejects acceptance from a key that was not proposed", async () => {
+      try {
+        await program.methods
+          .acceptAuthority()
+          .accountsStrict({
+            pendingAuthority: player.publicKey,
+            house: adminHouse.publicKey,
+            config: admin.config,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Accept should fail - not the pending authority");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("NotPendingAuthority");
+      }
+    });
+
+    it("Hands over admin rights once the pending key accepts", async () => {
+      await program.methods
+        .acceptAuthority()
+        .accountsStrict({
+          pendingAuthority: successor.publicKey,
+          house: adminHouse.publicKey,
+          config: admin.config,
+        })
+        .signers([successor])
+        .rpc();
+
+      const configAccount = await program.account.houseConfig.fetch(
+        admin.config,
+      );
+      assert.equal(
+        configAccount.authority.toBase58(),
+        successor.publicKey.toBase58(),
+      );
+      assert.isNull(configAccount.pendingAuthority);
+      assert.equal(
+        configAccount.houseId.toBase58(),
+        adminHouse.publicKey.toBase58(),
+        "The vault seed should survive the handover",
+      );
+
+      try {
+        await setPausedAs(adminHouse, true);
+        assert.fail("Pause should fail - old authority");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+      await setPausedAs(successor, true);
+      await setPausedAs(successor, false);
+    });
+
+    it("Pays withdrawals to the new authority, not the original house", async () => {
+      const amount = 0.1 * LAMPORTS_PER_SOL;
+      const successorBefore = await connection.getBalance(successor.publicKey);
+      const houseBefore = await connection.getBalance(adminHouse.publicKey);
+
+      await program.methods
+        .withdrawHouse(new anchor.BN(amount))
+        .accountsStrict({
+          authority: successor.publicKey,
+          house: adminHouse.publicKey,
+          vault: admin.vault,
+          config: admin.config,
+          jackpot: admin.jackpot,
+          jackpots: admin.jackpots,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([successor])
+        .rpc();
+
+      assert.equal(
+        await connection.getBalance(successor.publicKey),
+        successorBefore + amount,
+      );
+      assert.equal(
+        await connection.getBalance(adminHouse.publicKey),
+        houseBefore,
+        "The original house key should no longer receive withdrawals",
+      );
+    });
+  });
+
+  describe("Get Quote", () => {
+    it("Quotes the same payout as the on-chain payout math", async () => {
+      const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+      const quote = await program.methods
+        .getQuote(amount, 50, under, 0, 0, 0)
+        .accountsStrict({
+          house: house.publicKey,
+          config,
+        })
+        .view();
+
+      assert.equal(
+        quote.toNumber(),
+        payoutFor(amount.toNumber(), 50, 100, feeBps),
+        "Quote should match the payout formula",
+      );
+    });
+
+    describe("at the u64 boundary", () => {
+      const U64_MAX = (1n << 64n) - 1n;
+      // Largest stake whose even-money payout still fits in a u64.
+      const maxStake =
+        ((U64_MAX + 1n) * 50n * 10_000n - 1n) /
+        (100n * BigInt(10_000 - feeBps));
+
+      const quote = (amount: bigint) =>
+        program.methods
+          .getQuote(new anchor.BN(amount.toString()), 50, under, 0, 0, 0)
+          .accountsStrict({ house: house.publicKey, config });
+
+      it("Quotes the largest stake whose payout fits in a u64", async () => {
+        const payout = await quote(maxStake).view();
+        assert.equal(
+          BigInt(payout.toString()),
+          (maxStake * 100n * BigInt(10_000 - feeBps)) / (50n * 10_000n),
+        );
+      });
+
+      for (const [label, amount] of [
+        ["one lamport past the boundary", maxStake + 1n],
+        ["u64::MAX", U64_MAX],
+      ] as const) {
+        it(`Reports Overflow for a stake of ${label}`, async () => {
+          try {
+            await quote(amount).rpc();
+            assert.fail("Quote should fail - payout exceeds u64");
+          } catch (err: any) {
+            expect(err.error.errorCode.code).to.equal("Overflow");
+          }
+        });
+      }
+    });
+  });
+
+  describe("House Edge", () => {
+    const amount = new anchor.BN(LAMPORTS_PER_SOL);
+    const edgeFor = async (
+      configOverrides: object,
+      roll = 50,
+      direction: any = under,
+      diceCount = 0,
+    ) => {
+      const edgeHouse = Keypair.generate();
+      const pdas = await setupHouse(edgeHouse, configOverrides);
+      const edge = await program.methods
+        .getHouseEdge(amount, roll, direction, diceCount, 0, 0)
+        .accountsStrict({ house: edgeHouse.publicKey, config: pdas.config })
+        .view();
+      return edge.toNumber();
+    };
+
+    it("Equals the fee when ties go to the player", async () => {
+      assert.equal(await edgeFor({}), feeBps);
+    });
+
+    it("Equals the fee on a dice bet", async () => {
+      // 21 of the 36 two-dice sums are 7 or under.
+      assert.equal(await edgeFor({}, 7, under, 2), feeBps);
+    });
+
+    it("Grows when the house wins ties", async () => {
+      // 49 of 100 rolls win at the payout for 50: 1 - 0.49 * 1.97.
+      assert.equal(await edgeFor({ playerWinsTies: false }), 347);
+    });
+
+    it("Shrinks with near-miss consolations", async () => {
+      // Rolls 51..=53 return 5% of the stake: 1 - 0.5 * 1.97 - 0.03 * 0.05.
+      assert.equal(await edgeFor({ nearMissWindow: 3, nearMissBps: 500 }), 135);
+    });
+
+    it("Goes negative when the roll offset favours the player", async () => {
+      // An Under 50 that wins through 55: 1 - 0.55 * 1.97.
+      assert.equal(await edgeFor({ bonusRollOffset: 5 }), -835);
+    });
+  });
+
+  describe("Fee Tiers", () => {
+    const tierHouse = Keypair.generate();
+    const tiers = housePdas(tierHouse.publicKey);
+    const tier = (sol: number, bps: number) => ({
+      minAmount: new anchor.BN(sol * LAMPORTS_PER_SOL),
+      feeBps: bps,
+    });
+    const feeTiers = [tier(0.1, 100), tier(0.5, 50), ...noFeeTiers.slice(2)];
+
+    before(async () => {
+      await setupHouse(tierHouse, { feeTiers });
+    });
+
+    it("Charges the fee of the highest tier a bet reaches", async () => {
+      const cases: [number, number][] = [
+        [0.1 * LAMPORTS_PER_SOL - 1, feeBps],
+        [0.1 * LAMPORTS_PER_SOL, 100],
+        [0.5 * LAMPORTS_PER_SOL - 1, 100],
+        [0.5 * LAMPORTS_PER_SOL, 50],
+        [maxBet.toNumber(), 50],
+      ];
+      for (const [amount, bps] of cases) {
+        const quote = await program.methods
+          .getQuote(new anchor.BN(amount), 50, under, 0, 0, 0)
+          .accountsStrict({ house: tierHouse.publicKey, config: tiers.config })
+          .view();
+        assert.equal(
+          quote.toNumber(),
+          payoutFor(amount, 50, 100, bps),
+          `A ${amount} lamport bet should pay at ${bps} bps`,
+        );
+      }
+    });
+
+    it("Reserves the tiered payout when a bet is placed", async () => {
+      const seed = new anchor.BN(6_001);
+      const amount = new anchor.BN(0.5 * LAMPORTS_PER_SOL);
+      await program.methods
+        .placeBet(seed, betArgs(50, amount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: tierHouse.publicKey,
+          vault: tiers.vault,
+          config: tiers.config,
+          stats: tiers.stats(player.publicKey),
+          bet: tiers.bet(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+      const betAccount = await program.account.bet.fetch(tiers.bet(seed));
+      assert.equal(
+        betAccount.potentialPayout.toNumber(),
+        payoutFor(amount.toNumber(), 50, 100, 50),
+      );
+      await cancelBetFor(player, tierHouse.publicKey, tiers.bet(seed));
+    });
+
+    it("Rejects a base fee below an existing tier", async () => {
+      try {
+        await program.methods
+          .updateFee(80)
+          .accountsStrict({
+            authority: tierHouse.publicKey,
+            house: tierHouse.publicKey,
+            config: tiers.config,
+          })
+          .signers([tierHouse])
+          .rpc();
+        assert.fail("Update should fail - tier above the base fee");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidFeeTiers");
+      }
+    });
+
+    const rejectsTiers = (name: string, badTiers: any[]) =>
+      it(name, async () => {
+        try {
+          await setupHouse(Keypair.generate(), { feeTiers: badTiers });
+          assert.fail("Config init should fail - invalid fee tiers");
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal("InvalidFeeTiers");
+        }
+      });
+    rejectsTiers("Rejects tiers out of order", [
+      tier(0.5, 50),
+      tier(0.1, 100),
+      ...noFeeTiers.slice(2),
+    ]);
+    rejectsTiers("Rejects a tier charging more than the one below", [
+      tier(0.1, 50),
+      tier(0.5, 100),
+      ...noFeeTiers.slice(2),
+    ]);
+    rejectsTiers("Rejects a tier above the base fee", [
+      tier(0.1, feeBps + 1),
+      ...noFeeTiers.slice(1),
+    ]);
+    rejectsTiers("Rejects a used tier after an unused slot", [
+      noFeeTiers[0],
+      tier(0.1, 100),
+      ...noFeeTiers.slice(2),
+    ]);
+  });
+
+  describe("Volume Rebates", () => {
+    const rebateHouse = Keypair.generate();
+    const rebated = housePdas(rebateHouse.publicKey);
+    const whale = Keypair.generate();
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    const rebate = (sol: number, bps: number) => ({
+      minWagered: new anchor.BN(sol * LAMPORTS_PER_SOL),
+      rebateBps: bps,
+    });
+    const volumeRebates = [
+      rebate(0.1, 100),
+      rebate(1, 150),
+      ...noVolumeRebates.slice(2),
+    ];
+
+    before(async () => {
+      await setupHouse(rebateHouse, { volumeRebates });
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(whale.publicKey, 2 * LAMPORTS_PER_SOL),
+      );
+    });
+
+    it("Charges the full fee until the player reaches a tier", async () => {
+      const seed = new anchor.BN(6_101);
+      const betPda = rebated.bet(seed, whale.publicKey);
+      await program.methods
+        .placeBet(seed, betArgs(50, amount))
+        .accountsStrict({
+          player: whale.publicKey,
+          rentPayer: whale.publicKey,
+          house: rebateHouse.publicKey,
+          vault: rebated.vault,
+          config: rebated.config,
+          stats: rebated.stats(whale.publicKey),
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(whale.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([whale])
+        .rpc();
+
+      const bet = await program.account.bet.fetch(betPda);
+      assert.equal(bet.terms.feeBps, feeBps);
+      await cancelBetFor(whale, rebateHouse.publicKey, betPda);
+    });
+
+    it("Pays a lower edge once the player's volume crosses a tier", async () => {
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        whale,
+        rebateHouse.publicKey,
+        50,
+        amount,
+        true,
+      );
+      const rebatedFee = feeBps - 100;
+      assert.equal(bet.terms.feeBps, rebatedFee);
+      assert.equal(
+        bet.potentialPayout.toNumber(),
+        payoutFor(amount.toNumber(), 50, 100, rebatedFee),
+      );
+
+      const whaleBefore = await connection.getBalance(whale.publicKey);
+      await program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: whale.publicKey,
+          rentPayer: whale.publicKey,
+          resolver: resolver.publicKey,
+          house: rebateHouse.publicKey,
+          vault: rebated.vault,
+          config: rebated.config,
+          jackpot: rebated.jackpot,
+          jackpots: rebated.jackpots,
+          feeVault: rebated.feeVault,
+          treasury: rebated.treasury,
+          recentOutcomes: rebated.recentOutcomes,
+          ledger: rebated.ledger,
+          bet: betPda,
+          stats: rebated.stats(whale.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+      const whaleAfter = await connection.getBalance(whale.publicKey);
+      const rent = await connection.getMinimumBalanceForRentExemption(
+        program.account.bet.size,
+      );
+      assert.equal(
+        whaleAfter - whaleBefore,
+        payoutFor(amount.toNumber(), 50, 100, rebatedFee) + rent,
+      );
+    });
+
+    const rejectsRebates = (name: string, badRebates: any[]) =>
+      it(name, async () => {
+        try {
+          await setupHouse(Keypair.generate(), { volumeRebates: badRebates });
+          assert.fail("Config init should fail - invalid volume rebates");
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal("InvalidVolumeRebates");
+        }
+      });
+    rejectsRebates("Rejects rebate tiers out of order", [
+      rebate(1, 150),
+      rebate(0.1, 100),
+      ...noVolumeRebates.slice(2),
+    ]);
+    rejectsRebates("Rejects a tier rebating less than the one below", [
+      rebate(0.1, 150),
+      rebate(1, 100),
+      ...noVolumeRebates.slice(2),
+    ]);
+  });
+
+  describe("Round-Up Payouts", () => {
+    const roundHouse = Keypair.generate();
+    const rounded = housePdas(roundHouse.publicKey);
+    // 1.97x of an odd stake leaves a fractional lamport to round.
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL + 1);
+
+    before(async () => {
+      await setupHouse(roundHouse, { roundUpPayouts: true });
+    });
+
+    it("Rounds an odd payout up where the main house rounds down", async () => {
+      const quote = (houseKey: PublicKey, houseConfig: PublicKey) =>
+        program.methods
+          .getQuote(amount, 50, under, 0, 0, 0)
+          .accountsStrict({ house: houseKey, config: houseConfig })
+          .view();
+      const down = await quote(house.publicKey, config);
+      const up = await quote(roundHouse.publicKey, rounded.config);
+
+      assert.equal(
+        down.toNumber(),
+        payoutFor(amount.toNumber(), 50, 100, feeBps),
+      );
+      assert.equal(
+        up.toNumber(),
+        payoutFor(amount.toNumber(), 50, 100, feeBps, true),
+      );
+      assert.equal(up.toNumber() - down.toNumber(), 1);
+    });
+
+    it("Leaves an exact payout unchanged", async () => {
+      const even = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+      const quote = await program.methods
+        .getQuote(even, 50, under, 0, 0, 0)
+        .accountsStrict({ house: roundHouse.publicKey, config: rounded.config })
+        .view();
+      assert.equal(
+        quote.toNumber(),
+        payoutFor(even.toNumber(), 50, 100, feeBps),
+      );
+    });
+
+    it("Reserves the rounded-up payout at placement", async () => {
+      const seed = new anchor.BN(6_101);
+      await program.methods
+        .placeBet(seed, betArgs(50, amount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: roundHouse.publicKey,
+          vault: rounded.vault,
+          config: rounded.config,
+          stats: rounded.stats(player.publicKey),
+          bet: rounded.bet(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+      const betAccount = await program.account.bet.fetch(rounded.bet(seed));
+      const configAccount = await program.account.houseConfig.fetch(
+        rounded.config,
+      );
+      const expected = payoutFor(amount.toNumber(), 50, 100, feeBps, true);
+      assert.equal(betAccount.potentialPayout.toNumber(), expected);
+      assert.equal(configAccount.reservedLamports.toNumber(), expected);
+      await cancelBetFor(player, roundHouse.publicKey, rounded.bet(seed));
+    });
+  });
+
+  describe("Place Bet", () => {
+    const seed = new anchor.BN(1);
+    const roll = 50;
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+    it("Places a bet successfully", async () => {
+      const [betPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("bet"),
+          vault.toBytes(),
+          player.publicKey.toBytes(),
+          seed.toArrayLike(Buffer, "le", 16),
+        ],
+        program.programId,
+      );
+
+      const vaultBalanceBefore = await connection.getBalance(vault);
+
+      const sig = await program.methods
+        .placeBet(seed, betArgs(roll, amount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+      console.log("Place Bet tx:", sig);
+
+      const betAccount = await program.account.bet.fetch(betPda);
+      assert.equal(
+        betAccount.player.toString(),
+        player.publicKey.toString(),
+        "Bet player should match",
+      );
+      assert.equal(
+        betAccount.seed.toString(),
+        seed.toString(),
+        "Bet seed should match",
+      );
+      assert.equal(betAccount.roll, roll, "Bet roll should match");
+      assert.isTrue(
+        betAccount.slotHash.some((byte: number) => byte !== 0),
+        "Bet should record the slot hash at placement",
+      );
+      assert.equal(
+        betAccount.expirySlot.toNumber(),
+        betAccount.slot.toNumber() + REFUND_DELAY_SLOTS,
+        "Bet should expire when it becomes refundable",
+      );
+      assert.deepEqual(
+        betAccount.direction,
+        under,
+        "Bet direction should match",
+      );
+
+      const statsAccount = await program.account.playerStats.fetch(stats);
+      assert.equal(statsAccount.totalBets.toNumber(), 1, "One bet recorded");
+      assert.equal(
+        statsAccount.totalWagered.toString(),
+        amount.toString(),
+        "Wagered amount recorded",
+      );
+      assert.equal(
+        betAccount.amount.toString(),
+        amount.toString(),
+        "Bet amount should match",
+      );
+
+      const vaultBalanceAfter = await connection.getBalance(vault);
+      assert.equal(
+        vaultBalanceAfter - vaultBalanceBefore,
+        amount.toNumber(),
+        "Vault balance should increase by bet amount",
+      );
+    });
+
+    it("Rejects a bet that reuses an open seed", async () => {
+      try {
+        await program.methods
+          .placeBet(seed, betArgs(roll, amount))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: findBetPda(seed),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Place bet should fail - seed already used");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("SeedAlreadyUsed");
+      }
+    });
+
+    it("Rejects a bet below the minimum", async () => {
+      const lowSeed = new anchor.BN(2_000);
+      try {
+        await program.methods
+          .placeBet(lowSeed, betArgs(roll, minBet.subn(1)))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: findBetPda(lowSeed),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Place bet should fail - below minimum");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("MinimumBet");
+      }
+    });
+
+    it("Rejects a bet above the maximum", async () => {
+      const highSeed = new anchor.BN(2_001);
+      try {
+        await program.methods
+          .placeBet(highSeed, betArgs(roll, maxBet.addn(1)))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: findBetPda(highSeed),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Place bet should fail - above maximum");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("MaximumBet");
+      }
+    });
+
+    it("Rejects a bet with more than 8 dice", async () => {
+      const diceSeed = new anchor.BN(2_002);
+      try {
+        await program.methods
+          .placeBet(diceSeed, betArgs(20, amount, { diceCount: 9 }))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: findBetPda(diceSeed),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Place bet should fail - too many dice");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidDiceCount");
+      }
+    });
+
+    for (const badRoll of [1, 97]) {
+      it(`Rejects a bet with roll ${badRoll}`, async () => {
+        const badSeed = new anchor.BN(1_000 + badRoll);
+        try {
+          await program.methods
+            .placeBet(badSeed, betArgs(badRoll, amount))
+            .accountsStrict({
+              player: player.publicKey,
+              rentPayer: player.publicKey,
+              house: house.publicKey,
+              vault,
+              config,
+              stats,
+              bet: findBetPda(badSeed),
+              priceUpdate: null,
+              slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+              allowlisted: null,
+              selfExclusion: selfExclusionFor(player.publicKey),
+              usedSeed: null,
+              freeCredits: null,
+              playerBalance: null,
+              burnMint: null,
+              playerBurnAta: null,
+              tokenProgram: null,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([player])
+            .rpc();
+          assert.fail("Place bet should fail - roll out of range");
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal("RollOutOfRange");
+        }
+      });
+    }
+  });
+
+  describe("Place Next Bet", () => {
+    // A fresh player, so no explicit-seed bet already occupies a nonce.
+    const nonceUser = Keypair.generate();
+    const nonceStats = housePdas(house.publicKey).stats(nonceUser.publicKey);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    before(async () => {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(nonceUser.publicKey, LAMPORTS_PER_SOL),
+      );
+    });
+
+    const placeNextBet = async (betNonce: anchor.BN) => {
+      const betPda = findBetPda(betNonce, nonceUser.publicKey);
+      await program.methods
+        .placeNextBet(betArgs(50, amount))
+        .accountsStrict({
+          player: nonceUser.publicKey,
+          rentPayer: nonceUser.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats: nonceStats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(nonceUser.publicKey),
+          usedSeed: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([nonceUser])
+        .rpc();
+      return betPda;
+    };
+
+    it("Seeds consecutive bets from the player's nonce", async () => {
+      const firstPda = await placeNextBet(new anchor.BN(0));
+      const firstBet = await program.account.bet.fetch(firstPda);
+      assert.equal(
+        firstBet.seed.toNumber(),
+        0,
+        "A new player's first bet should use nonce 0",
+      );
+
+      let statsAccount = await program.account.playerStats.fetch(nonceStats);
+      assert.equal(statsAccount.betNonce.toNumber(), 1);
+
+      const secondPda = await placeNextBet(statsAccount.betNonce);
+      const secondBet = await program.account.bet.fetch(secondPda);
+      assert.equal(
+        secondBet.seed.toNumber(),
+        1,
+        "Each bet should advance the nonce by one",
+      );
+      statsAccount = await program.account.playerStats.fetch(nonceStats);
+      assert.equal(statsAccount.betNonce.toNumber(), 2);
+    });
+  });
+
+  describe("Signed Placement", () => {
+    // The player never signs a transaction: they deposit once, then only sign
+    // placement messages that a relayer submits and pays for.
+    const gaslessPlayer = Keypair.generate();
+    const relayer = Keypair.generate();
+    const playerBalance = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("balance"),
+        house.publicKey.toBuffer(),
+        Buffer.from([0]),
+        gaslessPlayer.publicKey.toBuffer(),
+      ],
+      program.programId,
+    )[0];
+    const deposit = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    before(async () => {
+      for (const kp of [gaslessPlayer, relayer]) {
+        await connection.confirmTransaction(
+          await connection.requestAirdrop(kp.publicKey, LAMPORTS_PER_SOL),
+        );
+      }
+      await program.methods
+        .depositBalance(deposit)
+        .accountsStrict({
+          player: gaslessPlayer.publicKey,
+          house: house.publicKey,
+          config,
+          playerBalance,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([gaslessPlayer])
+        .rpc();
+    });
+
+    // Mirrors `PlayerBalance::placement_message` on-chain.
+    const placementMessage = (nonce: anchor.BN, seed: anchor.BN, args: any) =>
+      Buffer.concat([
+        Buffer.from("place-bet"),
+        house.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+        seed.toArrayLike(Buffer, "le", 16),
+        program.coder.types.encode("betArgs", args),
+      ]);
+
+    const available = async () => {
+      const info = await connection.getAccountInfo(playerBalance);
+      const rent = await connection.getMinimumBalanceForRentExemption(
+        info.data.length,
+      );
+      return info.lamports - rent;
+    };
+
+    const placeSigned = (
+      seed: anchor.BN,
+      args: any,
+      ed25519Ix: TransactionInstruction,
+      preInstructions: TransactionInstruction[] = [],
+    ) =>
+      program.methods
+        .placeBetSigned(seed, args, ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          relayer: relayer.publicKey,
+          player: gaslessPlayer.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats: housePdas(house.publicKey).stats(gaslessPlayer.publicKey),
+          bet: findBetPda(seed, gaslessPlayer.publicKey),
+          playerBalance,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(gaslessPlayer.publicKey),
+          usedSeed: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([...preInstructions, ed25519Ix])
+        .signers([relayer])
+        .rpc();
+
+    it("Rejects a signature over different bet arguments", async () => {
+      const seed = new anchor.BN(1);
+      const args = betArgs(50, amount);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: gaslessPlayer.secretKey,
+        message: placementMessage(new anchor.BN(0), seed, args),
+      });
+      try {
+        await placeSigned(seed, betArgs(50, amount.muln(2)), ed25519Ix);
+        assert.fail("Placement should fail - signed for another amount");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PlacementMessageMismatch");
+      }
+    });
+
+    it("Places a bet the player only signed off-chain", async () => {
+      const seed = new anchor.BN(1);
+      const args = betArgs(50, amount);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: gaslessPlayer.secretKey,
+        message: placementMessage(new anchor.BN(0), seed, args),
+      });
+      const availableBefore = await available();
+      const playerBefore = await connection.getBalance(gaslessPlayer.publicKey);
+
+      await placeSigned(seed, args, ed25519Ix);
+
+      const bet = await program.account.bet.fetch(
+        findBetPda(seed, gaslessPlayer.publicKey),
+      );
+      assert.equal(bet.player.toString(), gaslessPlayer.publicKey.toString());
+      assert.equal(bet.rentPayer.toString(), relayer.publicKey.toString());
+      assert.equal(availableBefore - (await available()), amount.toNumber());
+      assert.equal(
+        await connection.getBalance(gaslessPlayer.publicKey),
+        playerBefore,
+        "The player's wallet should not be touched",
+      );
+      const balance = await program.account.playerBalance.fetch(playerBalance);
+      assert.equal(balance.nonce.toNumber(), 1);
+
+      // The nonce has moved on, so the same signature can't place again.
+      try {
+        await placeSigned(seed, args, ed25519Ix, [
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 }),
+        ]);
+        assert.fail("Placement should fail - signature already used");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PlacementMessageMismatch");
+      }
+    });
+
+    it("Stakes a bet from the balance and credits the win back", async () => {
+      const pdas = housePdas(house.publicKey);
+      const stats = pdas.stats(gaslessPlayer.publicKey);
+      let seed: anchor.BN;
+      let betPda: PublicKey;
+      let bet: any;
+      let ed25519Ix: TransactionInstruction;
+      for (let next = 100; ; next++) {
+        seed = new anchor.BN(next);
+        betPda = findBetPda(seed, gaslessPlayer.publicKey);
+        await program.methods
+          .placeBet(seed, betArgs(50, amount))
+          .accountsStrict({
+            player: gaslessPlayer.publicKey,
+            rentPayer: gaslessPlayer.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: betPda,
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(gaslessPlayer.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([gaslessPlayer])
+          .rpc();
+        bet = await program.account.bet.fetch(betPda);
+        ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: gaslessPlayer.secretKey,
+          message: betMessage(bet),
+        });
+        const outcome = computeRoll(
+          ed25519Ix.data.subarray(48, 112),
+          await revealedSlotHash(bet, config),
+        );
+        if (outcome !== 1 && outcome <= 50) break;
+        await discardBet(house.publicKey, betPda);
+      }
+      assert.isTrue(bet.fromBalance);
+
+      const resolve = (balance: PublicKey | null) =>
+        program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: gaslessPlayer.publicKey,
+            rentPayer: gaslessPlayer.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: betPda,
+            stats,
+            playerBalance: balance,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+
+      try {
+        await resolve(null);
+        assert.fail("Resolve should fail - the win belongs in the balance");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PlayerBalanceMismatch");
+      }
+
+      const availableBefore = await available();
+      await resolve(playerBalance);
+      assert.equal(
+        (await available()) - availableBefore,
+        payoutFor(amount.toNumber(), 50, 100, feeBps),
+      );
+    });
+
+    it("Returns a cancelled balance-staked bet to the balance", async () => {
+      const pdas = housePdas(house.publicKey);
+      const stats = pdas.stats(gaslessPlayer.publicKey);
+      const seed = new anchor.BN(200);
+      const betPda = findBetPda(seed, gaslessPlayer.publicKey);
+      const availableBefore = await available();
+      await program.methods
+        .placeBet(seed, betArgs(50, amount))
+        .accountsStrict({
+          player: gaslessPlayer.publicKey,
+          rentPayer: gaslessPlayer.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(gaslessPlayer.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([gaslessPlayer])
+        .rpc();
+
+      const cancel = (balance: PublicKey | null) =>
+        program.methods
+          .cancelBet()
+          .accountsStrict({
+            player: gaslessPlayer.publicKey,
+            rentPayer: gaslessPlayer.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            bet: betPda,
+            stats,
+            freeCredits: null,
+            playerBalance: balance,
+            feeVault,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([gaslessPlayer])
+          .rpc();
+
+      try {
+        await cancel(null);
+        assert.fail("Cancel should fail - the stake belongs in the balance");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PlayerBalanceMismatch");
+      }
+      await cancel(playerBalance);
+      assert.equal(await available(), availableBefore);
+    });
+
+    it("Cashes out a balance-staked bet into the balance", async () => {
+      const pdas = housePdas(house.publicKey);
+      const stats = pdas.stats(gaslessPlayer.publicKey);
+      const seed = new anchor.BN(201);
+      const betPda = findBetPda(seed, gaslessPlayer.publicKey);
+      const playerCommit = [
+        ...createHash("sha256").update(Buffer.alloc(32, 4)).digest(),
+      ];
+      await program.methods
+        .placeBet(seed, betArgs(50, amount, { playerCommit }))
+        .accountsStrict({
+          player: gaslessPlayer.publicKey,
+          rentPayer: gaslessPlayer.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(gaslessPlayer.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([gaslessPlayer])
+        .rpc();
+
+      const bet = await program.account.bet.fetch(betPda);
+      const offerAmount = amount.divn(2);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: resolver.secretKey,
+        message: Buffer.concat([
+          betMessage(bet),
+          offerAmount.toArrayLike(Buffer, "le", 8),
+        ]),
+      });
+      const cashout = (balance: PublicKey | null) =>
+        program.methods
+          .cashout(offerAmount, ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: gaslessPlayer.publicKey,
+            rentPayer: gaslessPlayer.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            bet: betPda,
+            stats,
+            playerBalance: balance,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([gaslessPlayer])
+          .rpc();
+
+      try {
+        await cashout(null);
+        assert.fail("Cashout should fail - the proceeds belong in the balance");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PlayerBalanceMismatch");
+      }
+      const availableBefore = await available();
+      await cashout(playerBalance);
+      assert.equal(
+        (await available()) - availableBefore,
+        offerAmount.toNumber(),
+      );
+    });
+
+    it("Withdraws no more than the deposited balance", async () => {
+      const withdraw = (lamports: number) =>
+        program.methods
+          .withdrawBalance(new anchor.BN(lamports))
+          .accountsStrict({
+            player: gaslessPlayer.publicKey,
+            house: house.publicKey,
+            config,
+            playerBalance,
+          })
+          .signers([gaslessPlayer])
+          .rpc();
+
+      const remaining = await available();
+      try {
+        await withdraw(remaining + 1);
+        assert.fail("Withdrawal should fail - above the balance");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "InsufficientDepositBalance",
+        );
+      }
+      await withdraw(remaining);
+      assert.equal(await available(), 0);
+    });
+  });
+
+  describe("Range Bets", () => {
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    const range = { range: {} };
+
+    it("Pays in inverse proportion to the window width", async () => {
+      const seed = new anchor.BN(5_100);
+      await program.methods
+        .placeBet(
+          seed,
+          betArgs(0, amount, { direction: range, rollLow: 40, rollHigh: 59 }),
+        )
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: findBetPda(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+      const betAccount = await program.account.bet.fetch(findBetPda(seed));
+      assert.deepEqual(betAccount.direction, range);
+      assert.equal(betAccount.rollLow, 40);
+      assert.equal(betAccount.rollHigh, 59);
+      assert.equal(
+        betAccount.potentialPayout.toNumber(),
+        payoutFor(amount.toNumber(), 20, 100, feeBps),
+        "A 20-roll window should pay 100/20 minus the edge",
+      );
+
+      const quote = await program.methods
+        .getQuote(amount, 0, range, 0, 40, 59)
+        .accountsStrict({ house: house.publicKey, config })
+        .view();
+      assert.equal(
+        quote.toString(),
+        betAccount.potentialPayout.toString(),
+        "Quote should match the placed bet",
+      );
+    });
+
+    it("Rejects a range whose low bound exceeds its high bound", async () => {
+      const seed = new anchor.BN(5_101);
+      try {
+        await program.methods
+          .placeBet(
+            seed,
+            betArgs(0, amount, { direction: range, rollLow: 60, rollHigh: 40 }),
+          )
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: findBetPda(seed),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Place bet should fail - inverted range");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidRange");
+      }
+    });
+  });
+
+  describe("Parlay", () => {
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    // Under 50 on the bet's own roll, plus a second Under 50 leg.
+    const legs = [{ roll: 50, direction: under }];
+
+    // Like `placeBetWithOutcome`: discards parlays until the bet's own roll
+    // wins and the leg lands the way the test needs.
+    const placeParlay = async (legWins: boolean) => {
+      for (;;) {
+        const seed = new anchor.BN(outcomeSeed++);
+        const betPda = findBetPda(seed);
+        await program.methods
+          .placeBet(seed, betArgs(50, amount, { legs }))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: betPda,
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+
+        const bet = await program.account.bet.fetch(betPda);
+        const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: player.secretKey,
+          message: betMessage(bet),
+        });
+        const sig = ed25519Ix.data.subarray(48, 112);
+        const slotHash = await revealedSlotHash(bet, config);
+        const roll = computeRoll(sig, slotHash);
+        const [legRoll] = computeLegRolls(sig, slotHash);
+        if (roll !== 1 && roll <= 50 && legRoll <= 50 === legWins) {
+          return { bet, betPda, ed25519Ix };
+        }
+        await discardBet(house.publicKey, betPda);
+      }
+    };
+
+    const resolveParlay = (
+      betPda: PublicKey,
+      ed25519Ix: TransactionInstruction,
+    ) =>
+      program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          jackpot,
+          jackpots,
+          feeVault,
+          treasury,
+          recentOutcomes,
+          ledger,
+          bet: betPda,
+          stats,
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+
+    it("Pays the product of the odds when every leg wins", async () => {
+      const { bet, betPda, ed25519Ix } = await placeParlay(true);
+      assert.equal(bet.legCount, 1);
+      assert.equal(
+        bet.potentialPayout.toNumber(),
+        payoutFor(amount.toNumber(), 50 * 50, 100 * 100, feeBps),
+        "Two even-money legs should pay 4x minus the edge",
+      );
+
+      const before = await program.account.playerStats.fetch(stats);
+      await resolveParlay(betPda, ed25519Ix);
+      const after = await program.account.playerStats.fetch(stats);
+      assert.equal(after.winCount.toNumber(), before.winCount.toNumber() + 1);
+    });
+
+    it("Loses when a single leg fails", async () => {
+      const { betPda, ed25519Ix } = await placeParlay(false);
+      const before = await program.account.playerStats.fetch(stats);
+      await resolveParlay(betPda, ed25519Ix);
+      const after = await program.account.playerStats.fetch(stats);
+      assert.equal(after.winCount.toNumber(), before.winCount.toNumber());
+      assert.equal(after.currentStreak, 0);
+    });
+
+    it("Rejects more legs than MAX_LEGS", async () => {
+      const seed = new anchor.BN(outcomeSeed++);
+      try {
+        await program.methods
+          .placeBet(
+            seed,
+            betArgs(50, amount, { legs: Array(4).fill(legs[0]) }),
+          )
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: findBetPda(seed),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Place bet should fail - too many legs");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("TooManyLegs");
+      }
+    });
+  });
+
+  describe("Precision Modes", () => {
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const basis = { basis: {} };
+    // 49.50%, which no 1..=100 threshold can express.
+    const rollBps = 4_950;
+
+    const placePrecisionBet = (seed: anchor.BN, args: any) =>
+      program.methods
+        .placeBet(seed, args)
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: findBetPda(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    // Resolves a bet once its reveal slot has passed and returns its
+    // `BetResolved` event.
+    const resolvePrecisionBet = async (seed: anchor.BN) => {
+      const bet = await program.account.bet.fetch(findBetPda(seed));
+      const slotHash = await revealedSlotHash(bet, config);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(bet),
+      });
+      const signature = await program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          jackpot,
+          jackpots,
+          feeVault,
+          treasury,
+          recentOutcomes,
+          ledger,
+          bet: findBetPda(seed),
+          stats,
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc({ commitment: "confirmed" });
+      const tx = await connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(tx.meta.logMessages)];
+      const resolved = events.find((e) => e.name === "betResolved");
+      assert.ok(resolved, "BetResolved event should be emitted");
+      return { sig: ed25519Ix.data.subarray(48, 112), slotHash, resolved };
+    };
+
+    it("Agrees with compute_roll on the last two digits", () => {
+      for (let i = 0; i < 200; i++) {
+        const sig = Keypair.generate().secretKey.subarray(0, 64);
+        const slotHash = Keypair.generate().publicKey.toBytes();
+        const value = computeRollU16(sig, slotHash);
+        assert.isTrue(value >= 0 && value <= 9_999);
+        assert.equal((value % 100) + 1, computeRoll(sig, slotHash));
+      }
+    });
+
+    it("Prices and judges a basis-point bet on the 0..=9999 outcome", async () => {
+      const seed = new anchor.BN(outcomeSeed++);
+      await placePrecisionBet(
+        seed,
+        betArgs(0, amount, { precision: basis, rollBps }),
+      );
+      const placed = await program.account.bet.fetch(findBetPda(seed));
+      assert.equal(placed.rollBps, rollBps);
+      assert.equal(
+        placed.potentialPayout.toNumber(),
+        payoutFor(amount.toNumber(), rollBps, 10_000, feeBps),
+      );
+
+      const { sig, slotHash, resolved } = await resolvePrecisionBet(seed);
+      const outcome = computeRollU16(sig, slotHash);
+      assert.equal(resolved.data.won, outcome < rollBps);
+      assert.equal(
+        resolved.data.result,
+        Math.floor(outcome / 100) + 1,
+        "The reported roll should be the outcome's percentile",
+      );
+    });
+
+    it("Keeps percent bets on the legacy 1..=100 roll", async () => {
+      const seed = new anchor.BN(outcomeSeed++);
+      await placePrecisionBet(
+        seed,
+        betArgs(50, amount, { precision: { percent: {} } }),
+      );
+
+      const { sig, slotHash, resolved } = await resolvePrecisionBet(seed);
+      const roll = computeRoll(sig, slotHash);
+      assert.equal(resolved.data.result, roll);
+      assert.equal(resolved.data.won, roll <= 50);
+    });
+
+    it("Rejects a basis-point bet that isn't Under or Over", async () => {
+      const seed = new anchor.BN(outcomeSeed++);
+      try {
+        await placePrecisionBet(
+          seed,
+          betArgs(50, amount, {
+            precision: basis,
+            rollBps,
+            direction: { exact: {} },
+          }),
+        );
+        assert.fail("Place bet should fail - exact basis bet");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidBasisBet");
+      }
+    });
+
+    it("Rejects a basis-point threshold outside the d100 bounds", async () => {
+      const seed = new anchor.BN(outcomeSeed++);
+      try {
+        await placePrecisionBet(
+          seed,
+          betArgs(0, amount, { precision: basis, rollBps: 100 }),
+        );
+        assert.fail("Place bet should fail - threshold below 2%");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RollOutOfRange");
+      }
+    });
+  });
+
+  describe("Custom Dice", () => {
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    const placeFacedBet = (seed: anchor.BN, args: any) =>
+      program.methods
+        .placeBet(seed, args)
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: findBetPda(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    // Resolves a bet once its reveal slot has passed and returns its
+    // `BetResolved` event.
+    const resolveFacedBet = async (seed: anchor.BN) => {
+      const bet = await program.account.bet.fetch(findBetPda(seed));
+      const slotHash = await revealedSlotHash(bet, config);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(bet),
+      });
+      const signature = await program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          jackpot,
+          jackpots,
+          feeVault,
+          treasury,
+          recentOutcomes,
+          ledger,
+          bet: findBetPda(seed),
+          stats,
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc({ commitment: "confirmed" });
+      const tx = await connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(tx.meta.logMessages)];
+      const resolved = events.find((e) => e.name === "betResolved");
+      assert.ok(resolved, "BetResolved event should be emitted");
+      return { sig: ed25519Ix.data.subarray(48, 112), slotHash, resolved };
+    };
+
+    it("Keeps every roll on the die and agrees with compute_roll on a d100", () => {
+      for (let i = 0; i < 200; i++) {
+        const sig = Keypair.generate().secretKey.subarray(0, 64);
+        const slotHash = Keypair.generate().publicKey.toBytes();
+        for (const faces of [2, 6, 20, 1_000]) {
+          const face = computeFaceRoll(sig, slotHash, faces);
+          assert.isTrue(face >= 1 && face <= faces, `d${faces} rolled ${face}`);
+        }
+        assert.equal(
+          computeFaceRoll(sig, slotHash, 100),
+          computeRoll(sig, slotHash),
+        );
+      }
+    });
+
+    it("Prices and judges a d6 Under bet", async () => {
+      const seed = new anchor.BN(outcomeSeed++);
+      await placeFacedBet(seed, betArgs(2, amount, { faces: 6 }));
+      const placed = await program.account.bet.fetch(findBetPda(seed));
+      assert.equal(placed.faces, 6);
+      assert.equal(
+        placed.potentialPayout.toNumber(),
+        payoutFor(amount.toNumber(), 2, 6, feeBps),
+        "Under 2 on a d6 wins on 2 of 6 faces",
+      );
+
+      const { sig, slotHash, resolved } = await resolveFacedBet(seed);
+      const face = computeFaceRoll(sig, slotHash, 6);
+      assert.equal(resolved.data.won, face <= 2);
+      assert.equal(
+        resolved.data.result,
+        computeRoll(sig, slotHash),
+        "The reported roll should stay the d100",
+      );
+    });
+
+    it("Prices and judges a d20 Over bet", async () => {
+      const seed = new anchor.BN(outcomeSeed++);
+      await placeFacedBet(
+        seed,
+        betArgs(15, amount, { faces: 20, direction: { over: {} } }),
+      );
+      const placed = await program.account.bet.fetch(findBetPda(seed));
+      assert.equal(
+        placed.potentialPayout.toNumber(),
+        payoutFor(amount.toNumber(), 5, 20, feeBps),
+        "Over 15 on a d20 wins on 5 of 20 faces",
+      );
+
+      const { sig, slotHash, resolved } = await resolveFacedBet(seed);
+      const face = computeFaceRoll(sig, slotHash, 20);
+      assert.equal(resolved.data.won, face > 15);
+    });
+
+    it("Rejects dice outside 2..=1000 faces", async () => {
+      for (const faces of [1, 1_001]) {
+        const seed = new anchor.BN(outcomeSeed++);
+        try {
+          await placeFacedBet(seed, betArgs(1, amount, { faces }));
+          assert.fail(`Place bet should fail - d${faces}`);
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal("InvalidFaces");
+        }
+      }
+    });
+
+    it("Rejects a threshold the die can't lose on", async () => {
+      const seed = new anchor.BN(outcomeSeed++);
+      try {
+        await placeFacedBet(seed, betArgs(6, amount, { faces: 6 }));
+        assert.fail("Place bet should fail - Under 6 on a d6 always wins");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RollOutOfRange");
+      }
+    });
+
+    it("Rejects custom faces on a dice-sum bet", async () => {
+      const seed = new anchor.BN(outcomeSeed++);
+      try {
+        await placeFacedBet(
+          seed,
+          betArgs(7, amount, { faces: 20, diceCount: 2 }),
+        );
+        assert.fail("Place bet should fail - faces only apply to one die");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidFaces");
+      }
+    });
+  });
+
+  describe("USD-Denominated Bets", () => {
+    it("Rejects a USD-denominated bet without the price feed", async () => {
+      const seed = new anchor.BN(5_201);
+      try {
+        await program.methods
+          .placeBet(
+            seed,
+            betArgs(50, minBet, { usdDenominated: true }),
+          )
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: findBetPda(seed),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Place bet should fail - no SOL/USD price");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PriceFeedRequired");
+      }
+    });
+
+    it("Records no entry price for a lamport bet", async () => {
+      const betAccount = await program.account.bet.fetch(
+        findBetPda(new anchor.BN(5_100)),
+      );
+      assert.isFalse(betAccount.usdDenominated);
+      assert.equal(betAccount.entryPrice.toNumber(), 0);
+    });
+  });
+
+  describe("Micro Bets", () => {
+    const microHouse = Keypair.generate();
+    const micro = housePdas(microHouse.publicKey);
+    // At a 90% edge on roll 95 a win pays 100 * 1_000 / (95 * 10_000) of the
+    // stake, so 10 lamports is the smallest bet that pays anything.
+    const microFeeBps = 9_000;
+
+    before(async () => {
+      await setupHouse(microHouse, {
+        feeBps: microFeeBps,
+        minBet: new anchor.BN(1),
+      });
+    });
+
+    const placeMicroBet = (seed: anchor.BN, lamports: number) =>
+      program.methods
+        .placeBet(seed, betArgs(95, new anchor.BN(lamports)))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: microHouse.publicKey,
+          vault: micro.vault,
+          config: micro.config,
+          stats: micro.stats(player.publicKey),
+          bet: micro.bet(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    it("Accepts the smallest bet with a non-zero payout", async () => {
+      const seed = new anchor.BN(1);
+      await placeMicroBet(seed, 10);
+
+      const betAccount = await program.account.bet.fetch(micro.bet(seed));
+      assert.equal(payoutFor(10, 95, 100, microFeeBps), 1);
+      assert.equal(betAccount.potentialPayout.toNumber(), 1);
+    });
+
+    it("Rejects a bet whose payout would round to zero", async () => {
+      assert.equal(payoutFor(9, 95, 100, microFeeBps), 0);
+      try {
+        await placeMicroBet(new anchor.BN(2), 9);
+        assert.fail("Place bet should fail - payout rounds to zero");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PayoutTooSmall");
+      }
+    });
+  });
+
+  describe("Fee Floor", () => {
+    const floorHouse = Keypair.generate();
+    const floor = housePdas(floorHouse.publicKey);
+    const minFee = 100_000;
+    let floorSeed = 1;
+
+    before(async () => {
+      await setupHouse(floorHouse, {
+        minBet: new anchor.BN(1),
+        minFeeLamports: new anchor.BN(minFee),
+      });
+    });
+
+    const placeFloorBet = async (roll: number, lamports: number) => {
+      const seed = new anchor.BN(floorSeed++);
+      await program.methods
+        .placeBet(seed, betArgs(roll, new anchor.BN(lamports)))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: floorHouse.publicKey,
+          vault: floor.vault,
+          config: floor.config,
+          stats: floor.stats(player.publicKey),
+          bet: floor.bet(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+      return program.account.bet.fetch(floor.bet(seed));
+    };
+
+    it("Trims the payout when the percentage fee is below the floor", async () => {
+      // 1.5% of a 2x payout on 1_000_000 lamports is only 30_000.
+      const amount = 1_000_000;
+      assert.isBelow(2 * amount - payoutFor(amount, 50, 100, feeBps), minFee);
+      const bet = await placeFloorBet(50, amount);
+      assert.equal(bet.potentialPayout.toNumber(), 2 * amount - minFee);
+    });
+
+    it("Leaves the payout alone when the percentage fee exceeds the floor", async () => {
+      const amount = 10_000_000;
+      const payout = payoutFor(amount, 50, 100, feeBps);
+      assert.isAbove(2 * amount - payout, minFee);
+      const bet = await placeFloorBet(50, amount);
+      assert.equal(bet.potentialPayout.toNumber(), payout);
+    });
+
+    it("Never trims a winning payout below the stake", async () => {
+      // A roll-95 win on 200_000 lamports is fairly worth only 210_526.
+      const amount = 200_000;
+      const bet = await placeFloorBet(95, amount);
+      assert.equal(bet.potentialPayout.toNumber(), amount);
+    });
+
+    it("Rejects a stake below the floor", async () => {
+      try {
+        await placeFloorBet(50, minFee - 1);
+        assert.fail("Place bet should fail - stake below the fee floor");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("FeeExceedsStake");
+      }
+    });
+  });
+
+  describe("Open Bet Limit", () => {
+    const limitHouse = Keypair.generate();
+    const limited = housePdas(limitHouse.publicKey);
+    const maxOpenBets = 2;
+    const placeLimited = (seed: anchor.BN) =>
+      program.methods
+        .placeBet(seed, betArgs(50, minBet))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: limitHouse.publicKey,
+          vault: limited.vault,
+          config: limited.config,
+          stats: limited.stats(player.publicKey),
+          bet: limited.bet(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    before(async () => {
+      await setupHouse(limitHouse, { maxOpenBetsPerPlayer: maxOpenBets });
+    });
+
+    it("Rejects a bet beyond the open-bet limit", async () => {
+      for (let i = 0; i < maxOpenBets; i++) {
+        await placeLimited(new anchor.BN(6_201 + i));
+      }
+      const statsAccount = await program.account.playerStats.fetch(
+        limited.stats(player.publicKey),
+      );
+      assert.equal(statsAccount.openBets, maxOpenBets);
+
+      try {
+        await placeLimited(new anchor.BN(6_201 + maxOpenBets));
+        assert.fail("Place bet should fail - too many open bets");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("TooManyOpenBets");
+      }
+    });
+
+    it("Frees a slot when an open bet is cancelled", async () => {
+      await cancelBetFor(
+        player,
+        limitHouse.publicKey,
+        limited.bet(new anchor.BN(6_201)),
+      );
+      const statsAccount = await program.account.playerStats.fetch(
+        limited.stats(player.publicKey),
+      );
+      assert.equal(statsAccount.openBets, maxOpenBets - 1);
+
+      await placeLimited(new anchor.BN(6_201 + maxOpenBets));
+    });
+  });
+
+  describe("Bet Cooldown", () => {
+    const coolHouse = Keypair.generate();
+    const coolPlayer = Keypair.generate();
+    const coolPdas = housePdas(coolHouse.publicKey);
+    const coolStats = coolPdas.stats(coolPlayer.publicKey);
+    const integrator = Keypair.generate();
+    const allowlistEntry = (playerKey: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("allowlist"),
+          coolHouse.publicKey.toBuffer(),
+          Buffer.from([0]),
+          playerKey.toBuffer(),
+        ],
+        program.programId,
+      )[0];
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    before(async () => {
+      for (const kp of [coolPlayer, integrator]) {
+        await connection.confirmTransaction(
+          await connection.requestAirdrop(kp.publicKey, 5 * LAMPORTS_PER_SOL),
+        );
+      }
+      await setupHouse(coolHouse, { betCooldownSlots: new anchor.BN(1_000) });
+    });
+
+    const placeCoolBet = (
+      seed: anchor.BN,
+      playerKp = coolPlayer,
+      allowlisted: PublicKey | null = null,
+    ) =>
+      program.methods
+        .placeBet(seed, betArgs(50, amount))
+        .accountsStrict({
+          player: playerKp.publicKey,
+          rentPayer: playerKp.publicKey,
+          house: coolHouse.publicKey,
+          vault: coolPdas.vault,
+          config: coolPdas.config,
+          stats: coolPdas.stats(playerKp.publicKey),
+          bet: coolPdas.bet(seed, playerKp.publicKey),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted,
+          selfExclusion: selfExclusionFor(playerKp.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([playerKp])
+        .rpc();
+
+    it("Rejects a second bet inside the cooldown window", async () => {
+      await placeCoolBet(new anchor.BN(1));
+
+      const statsAccount = await program.account.playerStats.fetch(coolStats);
+      const betAccount = await program.account.bet.fetch(
+        coolPdas.bet(new anchor.BN(1), coolPlayer.publicKey),
+      );
+      assert.equal(
+        statsAccount.lastBetSlot.toString(),
+        betAccount.slot.toString(),
+        "Stats should record the slot of the last bet",
+      );
+
+      try {
+        await placeCoolBet(new anchor.BN(2));
+        assert.fail("Place bet should fail - cooldown active");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("BetCooldownActive");
+      }
+    });
+
+    it("Rejects an allowlist entry added by a non-authority", async () => {
+      try {
+        await program.methods
+          .addToAllowlist()
+          .accountsStrict({
+            authority: integrator.publicKey,
+            house: coolHouse.publicKey,
+            config: coolPdas.config,
+            player: integrator.publicKey,
+            allowlisted: allowlistEntry(integrator.publicKey),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([integrator])
+          .rpc();
+        assert.fail("Add should fail - not the authority");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("Lets an allowlisted player bet inside the cooldown window", async () => {
+      const entry = allowlistEntry(integrator.publicKey);
+      await program.methods
+        .addToAllowlist()
+        .accountsStrict({
+          authority: coolHouse.publicKey,
+          house: coolHouse.publicKey,
+          config: coolPdas.config,
+          player: integrator.publicKey,
+          allowlisted: entry,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([coolHouse])
+        .rpc();
+
+      await placeCoolBet(new anchor.BN(1), integrator, entry);
+      await placeCoolBet(new anchor.BN(2), integrator, entry);
+      const statsAccount = await program.account.playerStats.fetch(
+        coolPdas.stats(integrator.publicKey),
+      );
+      assert.equal(statsAccount.totalBets.toNumber(), 2);
+
+      await program.methods
+        .removeFromAllowlist()
+        .accountsStrict({
+          authority: coolHouse.publicKey,
+          house: coolHouse.publicKey,
+          config: coolPdas.config,
+          allowlisted: entry,
+        })
+        .signers([coolHouse])
+        .rpc();
+      assert.isNull(await connection.getAccountInfo(entry));
+
+      try {
+        await placeCoolBet(new anchor.BN(3), integrator);
+        assert.fail("Place bet should fail - no longer allowlisted");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("BetCooldownActive");
+      }
+    });
+  });
+
+  describe("Self Exclusion", () => {
+    const exclusionHouse = Keypair.generate();
+    const excluded = housePdas(exclusionHouse.publicKey);
+    // A fresh player, since an exclusion applies to every house.
+    const excludedPlayer = Keypair.generate();
+    const exclusion = selfExclusionFor(excludedPlayer.publicKey);
+    let untilSlot: number;
+    let exclusionSeed = 1;
+
+    const selfExclude = (slot: number) =>
+      program.methods
+        .selfExclude(new anchor.BN(slot))
+        .accountsStrict({
+          player: excludedPlayer.publicKey,
+          selfExclusion: exclusion,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([excludedPlayer])
+        .rpc();
+
+    const placeExcludedBet = () => {
+      const seed = new anchor.BN(exclusionSeed++);
+      return program.methods
+        .placeBet(seed, betArgs(50, minBet))
+        .accountsStrict({
+          player: excludedPlayer.publicKey,
+          rentPayer: excludedPlayer.publicKey,
+          house: exclusionHouse.publicKey,
+          vault: excluded.vault,
+          config: excluded.config,
+          stats: excluded.stats(excludedPlayer.publicKey),
+          bet: excluded.bet(seed, excludedPlayer.publicKey),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: exclusion,
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([excludedPlayer])
+        .rpc();
+    };
+
+    before(async () => {
+      await setupHouse(exclusionHouse);
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(
+          excludedPlayer.publicKey,
+          LAMPORTS_PER_SOL,
+        ),
+      );
+      untilSlot = (await connection.getSlot()) + 20;
+      await selfExclude(untilSlot);
+    });
+
+    it("Rejects bets during the exclusion", async () => {
+      try {
+        await placeExcludedBet();
+        assert.fail("Place bet should fail - player is self-excluded");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PlayerSelfExcluded");
+      }
+    });
+
+    it("Refuses to shorten the exclusion", async () => {
+      try {
+        await selfExclude(untilSlot - 1);
+        assert.fail("Self exclude should fail - exclusion shortened");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("SelfExclusionShortened");
+      }
+    });
+
+    it("Extends the exclusion", async () => {
+      untilSlot += 10;
+      await selfExclude(untilSlot);
+      const account = await program.account.selfExclusion.fetch(exclusion);
+      expect(account.untilSlot.toNumber()).to.equal(untilSlot);
+    });
+
+    it("Accepts bets once the exclusion has ended", async () => {
+      while ((await connection.getSlot("processed")) < untilSlot) {
+        await new Promise((resolve) => setTimeout(resolve, 200));
+      }
+      await placeExcludedBet();
+      const bet = await program.account.bet.fetch(
+        excluded.bet(new anchor.BN(exclusionSeed - 1), excludedPlayer.publicKey),
+      );
+      assert.equal(bet.player.toString(), excludedPlayer.publicKey.toString());
+    });
+  });
+
+  describe("Seed Consumption", () => {
+    const consumingHouse = Keypair.generate();
+    const consuming = housePdas(consumingHouse.publicKey);
+    const seed = new anchor.BN(1);
+    const betPda = consuming.bet(seed);
+
+    const placeSeeded = (usedSeed: PublicKey | null) =>
+      program.methods
+        .placeBet(seed, betArgs(50, minBet))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: consumingHouse.publicKey,
+          vault: consuming.vault,
+          config: consuming.config,
+          stats: consuming.stats(player.publicKey),
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    before(async () => {
+      await setupHouse(consumingHouse, { consumeSeeds: true });
+    });
+
+    it("Rejects a placement without the used-seed marker", async () => {
+      try {
+        await placeSeeded(null);
+        assert.fail("Placement should fail - marker required");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("UsedSeedMismatch");
+      }
+    });
+
+    it("Records the seed when the bet is placed", async () => {
+      await placeSeeded(consuming.usedSeed(seed));
+      const marker = await program.account.usedSeed.fetch(
+        consuming.usedSeed(seed),
+      );
+      expect(marker.player.toString()).to.equal(player.publicKey.toString());
+      expect(marker.seed.toString()).to.equal(seed.toString());
+    });
+
+    it("Rejects reusing the seed after its bet is closed", async () => {
+      await cancelBetFor(player, consumingHouse.publicKey, betPda);
+      const closedBet = await connection.getAccountInfo(betPda);
+      expect(closedBet?.lamports ?? 0).to.equal(0, "Bet should be closed");
+      try {
+        await placeSeeded(consuming.usedSeed(seed));
+        assert.fail("Placement should fail - seed already consumed");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("SeedAlreadyConsumed");
+      }
+    });
+  });
+
+  describe("Total Exposure Cap", () => {
+    const capHouse = Keypair.generate();
+    const capPdas = housePdas(capHouse.publicKey);
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    // 10% of the vault across all open bets.
+    const maxTotalExposureBps = 1_000;
+
+    before(async () => {
+      await setupHouse(capHouse, { maxTotalExposureBps });
+    });
+
+    const placeCapBet = (seed: anchor.BN) =>
+      program.methods
+        .placeBet(seed, betArgs(50, amount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: capHouse.publicKey,
+          vault: capPdas.vault,
+          config: capPdas.config,
+          stats: capPdas.stats(player.publicKey),
+          bet: capPdas.bet(seed, player.publicKey),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    it("Rejects a bet that would push reserved payouts past the cap", async () => {
+      const payout = payoutFor(amount.toNumber(), 50, 100, feeBps);
+      let seed = 1;
+      // Fill the cap: each bet must fit within 10% of the vault as it stands
+      // before that bet's stake arrives.
+      for (;;) {
+        const config = await program.account.houseConfig.fetch(capPdas.config);
+        const vaultBalance = await connection.getBalance(capPdas.vault);
+        const cap = Math.floor((vaultBalance * maxTotalExposureBps) / 10_000);
+        if (config.reservedLamports.toNumber() + payout > cap) break;
+        await placeCapBet(new anchor.BN(seed++));
+      }
+      assert.isAbove(seed, 1, "At least one bet should fit under the cap");
+
+      try {
+        await placeCapBet(new anchor.BN(seed));
+        assert.fail("Place bet should fail - total exposure cap reached");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("GlobalExposureExceeded");
+      }
+    });
+  });
+
+  describe("Minimum Vault Balance", () => {
+    const floorHouse = Keypair.generate();
+    const floorPdas = housePdas(floorHouse.publicKey);
+    // setupHouse funds the vault with 2 SOL.
+    const minVaultBalance = new anchor.BN(3 * LAMPORTS_PER_SOL);
+
+    before(async () => {
+      await setupHouse(floorHouse, { minVaultBalance });
+    });
+
+    const placeFloorBet = (seed: anchor.BN) =>
+      program.methods
+        .placeBet(seed, betArgs(50, minBet))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: floorHouse.publicKey,
+          vault: floorPdas.vault,
+          config: floorPdas.config,
+          stats: floorPdas.stats(player.publicKey),
+          bet: floorPdas.bet(seed, player.publicKey),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    it("Rejects bets while the vault is below the floor", async () => {
+      try {
+        await placeFloorBet(new anchor.BN(1));
+        assert.fail("Place bet should fail - vault under-capitalized");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("VaultUnderCapitalized");
+      }
+    });
+
+    it("Accepts bets once the vault is topped up", async () => {
+      await program.methods
+        .fundVault(0, new anchor.BN(LAMPORTS_PER_SOL))
+        .accountsStrict({
+          funder: floorHouse.publicKey,
+          house: floorHouse.publicKey,
+          vault: floorPdas.vault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([floorHouse])
+        .rpc();
+
+      await placeFloorBet(new anchor.BN(1));
+      const bet = await program.account.bet.fetch(
+        floorPdas.bet(new anchor.BN(1), player.publicKey),
+      );
+      assert.ok(bet.amount.eq(minBet));
+    });
+  });
+
+  describe("Proof of Burn", () => {
+    const TOKEN_PROGRAM_ID = new PublicKey(
+      "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    );
+    const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey(
+      "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    );
+    const burnHouse = Keypair.generate();
+    const burnPdas = housePdas(burnHouse.publicKey);
+    const burner = Keypair.generate();
+    const mint = Keypair.generate();
+    const betBurnAmount = 2;
+    const burnerAta = PublicKey.findProgramAddressSync(
+      [
+        burner.publicKey.toBuffer(),
+        TOKEN_PROGRAM_ID.toBuffer(),
+        mint.publicKey.toBuffer(),
+      ],
+      ASSOCIATED_TOKEN_PROGRAM_ID,
+    )[0];
+
+    before(async () => {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(burner.publicKey, LAMPORTS_PER_SOL),
+      );
+      // A bare SPL mint controlled by the burner, who mints themselves
+      // enough for one bet's burn but not two.
+      const mintSpace = 82;
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: burner.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports:
+            await connection.getMinimumBalanceForRentExemption(mintSpace),
+          space: mintSpace,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        new TransactionInstruction({
+          programId: TOKEN_PROGRAM_ID,
+          keys: [{ pubkey: mint.publicKey, isSigner: false, isWritable: true }],
+          // InitializeMint2: decimals 0, burner as mint authority, no freeze.
+          data: Buffer.concat([
+            Buffer.from([20, 0]),
+            burner.publicKey.toBuffer(),
+            Buffer.from([0]),
+          ]),
+        }),
+        new TransactionInstruction({
+          programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+          keys: [
+            { pubkey: burner.publicKey, isSigner: true, isWritable: true },
+            { pubkey: burnerAta, isSigner: false, isWritable: true },
+            { pubkey: burner.publicKey, isSigner: false, isWritable: false },
+            { pubkey: mint.publicKey, isSigner: false, isWritable: false },
+            {
+              pubkey: SystemProgram.programId,
+              isSigner: false,
+              isWritable: false,
+            },
+            { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.from([1]),
+        }),
+        new TransactionInstruction({
+          programId: TOKEN_PROGRAM_ID,
+          keys: [
+            { pubkey: mint.publicKey, isSigner: false, isWritable: true },
+            { pubkey: burnerAta, isSigner: false, isWritable: true },
+            { pubkey: burner.publicKey, isSigner: true, isWritable: false },
+          ],
+          // MintTo: one and a half bets' worth.
+          data: Buffer.concat([
+            Buffer.from([7]),
+            new anchor.BN(betBurnAmount + 1).toArrayLike(Buffer, "le", 8),
+          ]),
+        }),
+      );
+      await sendAndConfirmTransaction(connection, tx, [burner, mint]);
+
+      await setupHouse(burnHouse, {
+        betBurnAmount: new anchor.BN(betBurnAmount),
+        burnMint: mint.publicKey,
+      });
+    });
+
+    const placeBurnBet = (seed: anchor.BN) =>
+      program.methods
+        .placeBet(seed, betArgs(50, minBet))
+        .accountsStrict({
+          player: burner.publicKey,
+          rentPayer: burner.publicKey,
+          house: burnHouse.publicKey,
+          vault: burnPdas.vault,
+          config: burnPdas.config,
+          stats: burnPdas.stats(burner.publicKey),
+          bet: burnPdas.bet(seed, burner.publicKey),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(burner.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: mint.publicKey,
+          playerBurnAta: burnerAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([burner])
+        .rpc();
+
+    it("Burns the configured amount with each bet", async () => {
+      await placeBurnBet(new anchor.BN(1));
+
+      const balance = await connection.getTokenAccountBalance(burnerAta);
+      assert.equal(balance.value.amount, "1");
+      const supply = await connection.getTokenSupply(mint.publicKey);
+      assert.equal(supply.value.amount, "1");
+    });
+
+    it("Rejects a bet the player can't burn for", async () => {
+      try {
+        await placeBurnBet(new anchor.BN(2));
+        assert.fail("Place bet should fail - too few burn tokens");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InsufficientBurnBalance");
+      }
+    });
+  });
+
+  describe("Wrapped SOL", () => {
+    const TOKEN_PROGRAM_ID = new PublicKey(
+      "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    );
+    const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey(
+      "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    );
+    const NATIVE_MINT = new PublicKey(
+      "So11111111111111111111111111111111111111112",
+    );
+    const wsolHouse = Keypair.generate();
+    const wsolPdas = housePdas(wsolHouse.publicKey);
+    const wsolPlayer = Keypair.generate();
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    let wsolSeed = 1;
+
+    const wsolAta = (owner: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [owner.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), NATIVE_MINT.toBuffer()],
+        ASSOCIATED_TOKEN_PROGRAM_ID,
+      )[0];
+    const playerAta = wsolAta(wsolPlayer.publicKey);
+    const vaultAta = wsolAta(wsolPdas.vault);
+    const wsolStats = wsolPdas.stats(wsolPlayer.publicKey);
+    const tokenReserve = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("token_reserve"),
+        wsolPdas.vault.toBuffer(),
+        NATIVE_MINT.toBuffer(),
+      ],
+      program.programId,
+    )[0];
+    const unwrapAccount = (betPda: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("unwrap"), betPda.toBuffer()],
+        program.programId,
+      )[0];
+
+    // Creates `owner`'s wSOL account, paid for by `payer`, and wraps
+    // `lamports` into it.
+    const wrapInstructions = (
+      payer: PublicKey,
+      owner: PublicKey,
+      lamports: number,
+    ) => [
+      new TransactionInstruction({
+        programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+        keys: [
+          { pubkey: payer, isSigner: true, isWritable: true },
+          { pubkey: wsolAta(owner), isSigner: false, isWritable: true },
+          { pubkey: owner, isSigner: false, isWritable: false },
+          { pubkey: NATIVE_MINT, isSigner: false, isWritable: false },
+          {
+            pubkey: SystemProgram.programId,
+            isSigner: false,
+            isWritable: false,
+          },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([1]),
+      }),
+      SystemProgram.transfer({
+        fromPubkey: payer,
+        toPubkey: wsolAta(owner),
+        lamports,
+      }),
+      new TransactionInstruction({
+        programId: TOKEN_PROGRAM_ID,
+        keys: [
+          { pubkey: wsolAta(owner), isSigner: false, isWritable: true },
+        ],
+        // SyncNative
+        data: Buffer.from([17]),
+      }),
+    ];
+
+    before(async () => {
+      // Each test keeps at most one bet open at a time.
+      await setupHouse(wsolHouse, { maxOpenBetsPerPlayer: 1 });
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(
+          wsolPlayer.publicKey,
+          2 * LAMPORTS_PER_SOL,
+        ),
+      );
+      await sendAndConfirmTransaction(
+        connection,
+        new Transaction().add(
+          ...wrapInstructions(
+            wsolHouse.publicKey,
+            wsolPdas.vault,
+            LAMPORTS_PER_SOL,
+          ),
+        ),
+        [wsolHouse],
+      );
+      await sendAndConfirmTransaction(
+        connection,
+        new Transaction().add(
+          ...wrapInstructions(
+            wsolPlayer.publicKey,
+            wsolPlayer.publicKey,
+            0.5 * LAMPORTS_PER_SOL,
+          ),
+        ),
+        [wsolPlayer],
+      );
+    });
+
+    const placeWsolBet = async () => {
+      const seed = new anchor.BN(wsolSeed++);
+      const betPda = wsolPdas.bet(seed, wsolPlayer.publicKey);
+      await program.methods
+        .placeBetSpl(seed, betArgs(50, amount))
+        .accountsStrict({
+          player: wsolPlayer.publicKey,
+          rentPayer: wsolPlayer.publicKey,
+          house: wsolHouse.publicKey,
+          vault: wsolPdas.vault,
+          config: wsolPdas.config,
+          stats: wsolStats,
+          mint: NATIVE_MINT,
+          playerAta,
+          vaultAta,
+          tokenReserve,
+          bet: betPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(wsolPlayer.publicKey),
+          usedSeed: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([wsolPlayer])
+        .rpc();
+
+      const bet = await program.account.bet.fetch(betPda);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: wsolPlayer.secretKey,
+        message: betMessage(bet),
+      });
+      const sig = ed25519Ix.data.subarray(48, 112);
+      return { bet, betPda, ed25519Ix, sig };
+    };
+
+    // Losing draws are resolved as losses, which releases their reserved
+    // payout, and a fresh seed is tried until one wins.
+    const placeWsolWin = async () => {
+      for (;;) {
+        const placed = await placeWsolBet();
+        const outcome = computeRoll(
+          placed.sig,
+          await revealedSlotHash(placed.bet, wsolPdas.config),
+        );
+        if (outcome !== 1 && outcome <= 50) {
+          return placed;
+        }
+        await resolveWsol(placed, false);
+      }
+    };
+
+    const resolveWsol = (
+      win: Awaited<ReturnType<typeof placeWsolBet>>,
+      unwrap: boolean,
+      accounts = {},
+    ) =>
+      program.methods
+        .resolveBetWsol(Buffer.from(win.sig), unwrap)
+        .accountsStrict({
+          resolver: resolver.publicKey,
+          house: wsolHouse.publicKey,
+          vault: wsolPdas.vault,
+          config: wsolPdas.config,
+          player: wsolPlayer.publicKey,
+          mint: NATIVE_MINT,
+          playerAta: unwrap ? null : playerAta,
+          vaultAta,
+          bet: win.betPda,
+          unwrapAccount: unwrap ? unwrapAccount(win.betPda) : null,
+          rentPayer: wsolPlayer.publicKey,
+          tokenReserve,
+          stats: wsolStats,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          ...accounts,
+        })
+        .preInstructions([win.ed25519Ix])
+        .signers([resolver])
+        .rpc();
+
+    const tokenBalance = async (account: PublicKey) =>
+      Number((await connection.getTokenAccountBalance(account)).value.amount);
+
+    const reserved = async () =>
+      (await program.account.tokenReserve.fetch(tokenReserve)).reserved;
+
+    // CloseAccount: unwraps the player's remaining wSOL back to them.
+    const closePlayerAta = () =>
+      sendAndConfirmTransaction(
+        connection,
+        new Transaction().add(
+          new TransactionInstruction({
+            programId: TOKEN_PROGRAM_ID,
+            keys: [
+              { pubkey: playerAta, isSigner: false, isWritable: true },
+              {
+                pubkey: wsolPlayer.publicKey,
+                isSigner: false,
+                isWritable: true,
+              },
+              {
+                pubkey: wsolPlayer.publicKey,
+                isSigner: true,
+                isWritable: false,
+              },
+            ],
+            data: Buffer.from([9]),
+          }),
+        ),
+        [wsolPlayer],
+      );
+
+    it("Pays a wSOL win into the player's token account", async () => {
+      const win = await placeWsolWin();
+      const tokensBefore = await tokenBalance(playerAta);
+      const vaultTokensBefore = await tokenBalance(vaultAta);
+
+      await resolveWsol(win, false);
+
+      const payout = win.bet.potentialPayout.toNumber();
+      assert.equal(await tokenBalance(playerAta), tokensBefore + payout);
+      assert.equal(await tokenBalance(vaultAta), vaultTokensBefore - payout);
+    });
+
+    it("Unwraps a wSOL win to native SOL", async () => {
+      const win = await placeWsolWin();
+      const reservedBefore = await reserved();
+      const tokensBefore = await tokenBalance(playerAta);
+      const lamportsBefore = await connection.getBalance(wsolPlayer.publicKey);
+      const resolverBefore = await connection.getBalance(resolver.publicKey);
+
+      await resolveWsol(win, true);
+
+      // The player also gets the bet account's rent back, as its rent payer.
+      const payout = win.bet.potentialPayout.toNumber();
+      const betRent = await connection.getMinimumBalanceForRentExemption(
+        program.account.bet.size,
+      );
+      assert.equal(
+        await connection.getBalance(wsolPlayer.publicKey),
+        lamportsBefore + payout + betRent,
+      );
+      assert.equal(await tokenBalance(playerAta), tokensBefore);
+      assert.isNull(
+        await connection.getAccountInfo(unwrapAccount(win.betPda)),
+        "The unwrap account should be closed",
+      );
+      // The resolver fronts the unwrap account's rent and gets it back.
+      assert.equal(
+        await connection.getBalance(resolver.publicKey),
+        resolverBefore,
+      );
+      assert.equal(
+        (await reserved()).toString(),
+        reservedBefore.sub(win.bet.potentialPayout).toString(),
+      );
+    });
+
+    it("Recreates a closed wSOL account to pay into", async () => {
+      const win = await placeWsolWin();
+      await closePlayerAta();
+      assert.isNull(await connection.getAccountInfo(playerAta));
+
+      await resolveWsol(win, false);
+
+      assert.equal(
+        await tokenBalance(playerAta),
+        win.bet.potentialPayout.toNumber(),
+      );
+    });
+
+    it("Rejects an unwrap without its unwrap account", async () => {
+      // Re-wrap some SOL so there's a balance to bet from again.
+      await sendAndConfirmTransaction(
+        connection,
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: wsolPlayer.publicKey,
+            toPubkey: playerAta,
+            lamports: 0.1 * LAMPORTS_PER_SOL,
+          }),
+          new TransactionInstruction({
+            programId: TOKEN_PROGRAM_ID,
+            keys: [{ pubkey: playerAta, isSigner: false, isWritable: true }],
+            data: Buffer.from([17]),
+          }),
+        ),
+        [wsolPlayer],
+      );
+      const win = await placeWsolWin();
+      try {
+        await resolveWsol(win, true, {
+          playerAta,
+          unwrapAccount: null,
+        });
+        assert.fail("Resolve should fail - unwrap account missing");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("WsolAccountsMismatch");
+      }
+      await resolveWsol(win, false);
+    });
+
+    const refundAccounts = (betPda: PublicKey) => ({
+      player: wsolPlayer.publicKey,
+      house: wsolHouse.publicKey,
+      vault: wsolPdas.vault,
+      config: wsolPdas.config,
+      mint: NATIVE_MINT,
+      playerAta,
+      vaultAta,
+      bet: betPda,
+      rentPayer: wsolPlayer.publicKey,
+      tokenReserve,
+      stats: wsolStats,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    });
+
+    it("Reserves a token bet's payout until it's resolved", async () => {
+      const reservedBefore = await reserved();
+      const win = await placeWsolWin();
+      const payout = win.bet.potentialPayout;
+      assert.equal(
+        (await reserved()).toString(),
+        reservedBefore.add(payout).toString(),
+      );
+
+      await resolveWsol(win, false);
+
+      assert.equal((await reserved()).toString(), reservedBefore.toString());
+    });
+
+    it("Cancels a wSOL bet back into the player's token account", async () => {
+      const reservedBefore = await reserved();
+      const tokensBefore = await tokenBalance(playerAta);
+      const placed = await placeWsolBet();
+      assert.equal(
+        await tokenBalance(playerAta),
+        tokensBefore - amount.toNumber(),
+      );
+
+      await program.methods
+        .cancelBetSpl()
+        .accountsStrict(refundAccounts(placed.betPda))
+        .signers([wsolPlayer])
+        .rpc();
+
+      assert.equal(await tokenBalance(playerAta), tokensBefore);
+      assert.equal((await reserved()).toString(), reservedBefore.toString());
+      assert.isNull(await connection.getAccountInfo(placed.betPda));
+    });
+
+    it("Rejects a token refund before the bet expires", async () => {
+      const placed = await placeWsolBet();
+      try {
+        await program.methods
+          .refundBetSpl()
+          .accountsStrict(refundAccounts(placed.betPda))
+          .signers([wsolPlayer])
+          .rpc();
+        assert.fail("Refund should fail - timeout not reached");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RefundNotYetAvailable");
+      }
+      await program.methods
+        .cancelBetSpl()
+        .accountsStrict(refundAccounts(placed.betPda))
+        .signers([wsolPlayer])
+        .rpc();
+    });
+
+    it("Recreates a closed wSOL account to cancel into", async () => {
+      const placed = await placeWsolBet();
+      await closePlayerAta();
+
+      await program.methods
+        .cancelBetSpl()
+        .accountsStrict(refundAccounts(placed.betPda))
+        .signers([wsolPlayer])
+        .rpc();
+
+      assert.equal(await tokenBalance(playerAta), amount.toNumber());
+    });
+
+    it("Counts token bets towards the open-bet cap", async () => {
+      const placed = await placeWsolBet();
+      const stats = await program.account.playerStats.fetch(wsolStats);
+      assert.equal(stats.openBets, 1);
+      try {
+        await placeWsolBet();
+        assert.fail("Place bet should fail - the player is at the cap");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("TooManyOpenBets");
+      }
+
+      await program.methods
+        .cancelBetSpl()
+        .accountsStrict(refundAccounts(placed.betPda))
+        .signers([wsolPlayer])
+        .rpc();
+      assert.equal(
+        (await program.account.playerStats.fetch(wsolStats)).openBets,
+        0,
+      );
+    });
+
+    it("Refuses to close the game while a token bet is open", async () => {
+      const placed = await placeWsolBet();
+      const configAccount = await program.account.houseConfig.fetch(
+        wsolPdas.config,
+      );
+      assert.equal(configAccount.reservedLamports.toNumber(), 0);
+      assert.equal(configAccount.openTokenBets.toNumber(), 1);
+      try {
+        await program.methods
+          .closeGame()
+          .accountsStrict({
+            authority: wsolHouse.publicKey,
+            house: wsolHouse.publicKey,
+            vault: wsolPdas.vault,
+            config: wsolPdas.config,
+            jackpot: wsolPdas.jackpot,
+            jackpots: wsolPdas.jackpots,
+            feeVault: wsolPdas.feeVault,
+            treasury: wsolPdas.treasury,
+            recentOutcomes: wsolPdas.recentOutcomes,
+            ledger: wsolPdas.ledger,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([wsolHouse])
+          .rpc();
+        assert.fail("Close game should fail - a token bet is open");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("OpenBetsRemain");
+      }
+
+      await program.methods
+        .cancelBetSpl()
+        .accountsStrict(refundAccounts(placed.betPda))
+        .signers([wsolPlayer])
+        .rpc();
+      assert.equal(
+        (
+          await program.account.houseConfig.fetch(wsolPdas.config)
+        ).openTokenBets.toNumber(),
+        0,
+      );
+    });
+
+    it("Applies the house's bet limits to token bets", async () => {
+      const seed = new anchor.BN(wsolSeed++);
+      try {
+        await program.methods
+          .placeBetSpl(seed, betArgs(50, minBet.subn(1)))
+          .accountsStrict({
+            player: wsolPlayer.publicKey,
+            rentPayer: wsolPlayer.publicKey,
+            house: wsolHouse.publicKey,
+            vault: wsolPdas.vault,
+            config: wsolPdas.config,
+            stats: wsolStats,
+            mint: NATIVE_MINT,
+            playerAta,
+            vaultAta,
+            tokenReserve,
+            bet: wsolPdas.bet(seed, wsolPlayer.publicKey),
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(wsolPlayer.publicKey),
+            usedSeed: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([wsolPlayer])
+          .rpc();
+        assert.fail("Place bet should fail - below the minimum bet");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("MinimumBet");
+      }
+    });
+  });
+
+  describe("Multiple Houses", () => {
+    const otherHouse = Keypair.generate();
+    const other = housePdas(otherHouse.publicKey);
+    const seed = new anchor.BN(4_242);
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+
+    before(async () => {
+      await setupHouse(otherHouse);
+    });
+
+    it("Keeps stats, config and vault isolated per house", async () => {
+      const statsBefore = await program.account.playerStats.fetch(stats);
+      const configBefore = await program.account.houseConfig.fetch(config);
+      const vaultBefore = await connection.getBalance(vault);
+      const otherVaultBefore = await connection.getBalance(other.vault);
+      const otherStats = other.stats(player.publicKey);
+      assert.notEqual(
+        otherStats.toBase58(),
+        stats.toBase58(),
+        "The same player should get separate stats per house",
+      );
+
+      await program.methods
+        .placeBet(seed, betArgs(50, amount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: otherHouse.publicKey,
+          vault: other.vault,
+          config: other.config,
+          stats: otherStats,
+          bet: other.bet(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+      const otherStatsAccount = await program.account.playerStats.fetch(
+        otherStats,
+      );
+      assert.equal(otherStatsAccount.totalBets.toNumber(), 1);
+      const otherConfig = await program.account.houseConfig.fetch(
+        other.config,
+      );
+      assert.equal(otherConfig.betCount.toNumber(), 1);
+      assert.equal(
+        (await connection.getBalance(other.vault)) - otherVaultBefore,
+        amount.toNumber(),
+        "Only the other house's vault should take the stake",
+      );
+
+      const statsAfter = await program.account.playerStats.fetch(stats);
+      const configAfter = await program.account.houseConfig.fetch(config);
+      assert.equal(
+        statsAfter.totalBets.toString(),
+        statsBefore.totalBets.toString(),
+        "The first house's stats should be untouched",
+      );
+      assert.equal(
+        configAfter.betCount.toString(),
+        configBefore.betCount.toString(),
+        "The first house's bet count should be untouched",
+      );
+      assert.equal(
+        configAfter.reservedLamports.toString(),
+        configBefore.reservedLamports.toString(),
+      );
+      assert.equal(await connection.getBalance(vault), vaultBefore);
+    });
+
+    it("Rejects a bet pointing at another house's vault", async () => {
+      try {
+        await program.methods
+          .placeBet(new anchor.BN(4_243), betArgs(50, amount))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: otherHouse.publicKey,
+            vault,
+            config: other.config,
+            stats: other.stats(player.publicKey),
+            bet: findBetPda(new anchor.BN(4_243)),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Place bet should fail - vault belongs to another house");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintSeeds");
+      }
+    });
+
+    it("Rejects resolving one house's bet against another house's accounts", async () => {
+      const bet = await program.account.bet.fetch(other.bet(seed));
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(bet),
+      });
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: other.bet(seed),
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: new PublicKey(
+              "Sysvar1nstructions1111111111111111111111111",
+            ),
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - bet belongs to another house");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("AccountHouseMismatch");
+      }
+    });
+  });
+
+  describe("Multiple Games", () => {
+    // A second game run by the main house, under its own rules.
+    const gameId = 1;
+    const game = housePdas(house.publicKey, gameId);
+    const gameFeeBps = 300;
+    const seed = new anchor.BN(4_300);
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+
+    const placeGameBet = (betSeed: anchor.BN, gameVault = game.vault) =>
+      program.methods
+        .placeBet(betSeed, betArgs(50, amount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault: gameVault,
+          config: game.config,
+          stats: game.stats(player.publicKey),
+          bet: game.bet(betSeed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    before(async () => {
+      await setupHouse(house, { feeBps: gameFeeBps }, gameId);
+    });
+
+    it("Runs a second game with its own config and bankroll", async () => {
+      const gameConfig = await program.account.houseConfig.fetch(game.config);
+      assert.equal(gameConfig.gameId, gameId);
+      assert.equal(gameConfig.houseId.toString(), house.publicKey.toString());
+      assert.equal(gameConfig.feeBps, gameFeeBps);
+      assert.notEqual(game.vault.toBase58(), vault.toBase58());
+
+      const vaultBefore = await connection.getBalance(vault);
+      const gameVaultBefore = await connection.getBalance(game.vault);
+      await placeGameBet(seed);
+
+      const bet = await program.account.bet.fetch(game.bet(seed));
+      assert.equal(
+        bet.potentialPayout.toNumber(),
+        payoutFor(amount.toNumber(), 50, 100, gameFeeBps),
+        "The bet should be priced by the second game's config",
+      );
+      assert.equal(
+        (await connection.getBalance(game.vault)) - gameVaultBefore,
+        amount.toNumber(),
+      );
+      assert.equal(
+        await connection.getBalance(vault),
+        vaultBefore,
+        "The first game's bankroll should be untouched",
+      );
+    });
+
+    it("Rejects mixing one game's config with another's vault", async () => {
+      try {
+        await placeGameBet(new anchor.BN(4_301), vault);
+        assert.fail("Place bet should fail - vault belongs to another game");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintSeeds");
+      }
+    });
+  });
+
+  describe("Refund Bet", () => {
+    const seed = new anchor.BN(99);
+    const roll = 50;
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    let betPda: PublicKey;
+
+    before(async () => {
+      [betPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("bet"),
+          vault.toBytes(),
+          player.publicKey.toBytes(),
+          seed.toArrayLike(Buffer, "le", 16),
+        ],
+        program.programId,
+      );
+
+      await program.methods
+        .placeBet(seed, betArgs(roll, amount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("Fails to refund before timeout is reached", async () => {
+      try {
+        await program.methods
+          .refundBet()
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            bet: betPda,
+            stats,
+            freeCredits: null,
+            playerBalance: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Refund should fail - timeout not reached");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RefundNotYetAvailable");
+        console.log("Refund correctly rejected before timeout");
+      }
+    });
+  });
+
+  describe("Sweep Expired", () => {
+    const seed = new anchor.BN(5_201);
+    const betPda = findBetPda(seed);
+
+    const sweep = (signer: Keypair, remaining: PublicKey[]) =>
+      program.methods
+        .sweepExpired()
+        .accountsStrict({
+          authority: signer.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          remaining.map((pubkey) => ({
+            pubkey,
+            isSigner: false,
+            isWritable: true,
+          })),
+        )
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .placeBet(seed, betArgs(50, new anchor.BN(0.01 * LAMPORTS_PER_SOL)))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("Rejects sweeping a bet that has not expired", async () => {
+      try {
+        await sweep(house, [
+          betPda,
+          player.publicKey,
+          stats,
+          player.publicKey,
+        ]);
+        assert.fail("Sweep should fail - bet still live");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("BetNotExpired");
+      }
+    });
+
+    it("Rejects a sweep by anyone but the authority", async () => {
+      try {
+        await sweep(player, [
+          betPda,
+          player.publicKey,
+          stats,
+          player.publicKey,
+        ]);
+        assert.fail("Sweep should fail - not the authority");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("Rejects accounts that are not whole groups of four", async () => {
+      try {
+        await sweep(house, [betPda, player.publicKey, stats]);
+        assert.fail("Sweep should fail - missing rent payer account");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("BatchAccountsMismatch");
+      }
+    });
+  });
+
+  describe("Cancel Bet", () => {
+    const seed = new anchor.BN(7_000);
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+    it("Cancels a bet within the cancel window", async () => {
+      await program.methods
+        .placeBet(seed, betArgs(50, amount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: findBetPda(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+      const vaultBalanceBefore = await connection.getBalance(vault);
+
+      await program.methods
+        .cancelBet()
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          bet: findBetPda(seed),
+          stats,
+          freeCredits: null,
+          playerBalance: null,
+          feeVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+      const vaultBalanceAfter = await connection.getBalance(vault);
+      assert.equal(
+        vaultBalanceBefore - vaultBalanceAfter,
+        amount.toNumber(),
+        "Vault should refund the stake",
+      );
+      const closedBet = await connection.getAccountInfo(findBetPda(seed));
+      expect(closedBet?.lamports ?? 0).to.equal(0, "Bet should be closed");
+    });
+
+    it("Rejects a signature replayed against an identical later bet", async () => {
+      const seed = new anchor.BN(7_001);
+      const accounts = {
+        player: player.publicKey,
+        rentPayer: player.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet: findBetPda(seed),
+        stats,
+        freeCredits: null,
+        playerBalance: null,
+        feeVault,
+        systemProgram: SystemProgram.programId,
+      };
+      const placeBet = () =>
+        program.methods
+          .placeBet(seed, betArgs(50, amount))
+          .accountsStrict({
+            ...accounts,
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+          })
+          .signers([player])
+          .rpc();
+
+      await placeBet();
+      const betA = await program.account.bet.fetch(findBetPda(seed));
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(betA),
+      });
+      await program.methods
+        .cancelBet()
+        .accountsStrict(accounts)
+        .signers([player])
+        .rpc();
+
+      await placeBet();
+      const betB = await program.account.bet.fetch(findBetPda(seed));
+      assert.notEqual(betA.nonce.toString(), betB.nonce.toString());
+
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: findBetPda(seed),
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: new PublicKey(
+              "Sysvar1nstructions1111111111111111111111111",
+            ),
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - replayed signature");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Ed25519Message");
+      }
+    });
+  });
+
+  describe("Close Authority", () => {
+    const seed = new anchor.BN(7_100);
+    const betPda = findBetPda(seed);
+    const stranger = Keypair.generate();
+
+    const betAccounts = (signer: Keypair) => ({
+      player: signer.publicKey,
+      rentPayer: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: betPda,
+      stats,
+      freeCredits: null,
+      playerBalance: null,
+      feeVault,
+      systemProgram: SystemProgram.programId,
+    });
+
+    before(async () => {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(stranger.publicKey, LAMPORTS_PER_SOL),
+      );
+      await program.methods
+        .placeBet(seed, betArgs(50, new anchor.BN(0.01 * LAMPORTS_PER_SOL)))
+        .accountsStrict({
+          ...betAccounts(player),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("Rejects a cancel signed by someone other than the player", async () => {
+      try {
+        await program.methods
+          .cancelBet()
+          .accountsStrict(betAccounts(stranger))
+          .signers([stranger])
+          .rpc();
+        assert.fail("Cancel should fail - not the player");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("Rejects a refund signed by someone other than the player", async () => {
+      try {
+        await program.methods
+          .refundBet()
+          .accountsStrict(betAccounts(stranger))
+          .signers([stranger])
+          .rpc();
+        assert.fail("Refund should fail - not the player");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("Rejects an emergency refund by anyone but the authority", async () => {
+      try {
+        await program.methods
+          .emergencyRefund()
+          .accountsStrict({
+            ...betAccounts(player),
+            authority: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        assert.fail("Emergency refund should fail - not the authority");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("Leaves the bet open after every attempt", async () => {
+      const bet = await program.account.bet.fetch(betPda);
+      assert.ok(bet.player.equals(player.publicKey));
+      assert.ok(bet.amount.eq(new anchor.BN(0.01 * LAMPORTS_PER_SOL)));
+    });
+  });
+
+  describe("Sponsored Rent", () => {
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const sponsor = Keypair.generate();
+
+    before(async () => {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(sponsor.publicKey, LAMPORTS_PER_SOL),
+      );
+    });
+
+    const place = (seed: anchor.BN, rentPayer: Keypair) =>
+      program.methods
+        .placeBet(seed, betArgs(50, amount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: rentPayer.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: findBetPda(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers(rentPayer === player ? [player] : [player, rentPayer])
+        .rpc();
+
+    const cancel = (seed: anchor.BN, rentPayer: PublicKey) =>
+      program.methods
+        .cancelBet()
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer,
+          house: house.publicKey,
+          vault,
+          config,
+          bet: findBetPda(seed),
+          stats,
+          freeCredits: null,
+          playerBalance: null,
+          feeVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    it("Returns the rent of a self-funded bet to the player", async () => {
+      const seed = new anchor.BN(7_100);
+      await place(seed, player);
+      const bet = await program.account.bet.fetch(findBetPda(seed));
+      assert.equal(bet.rentPayer.toString(), player.publicKey.toString());
+
+      const betRent = (await connection.getAccountInfo(findBetPda(seed)))
+        .lamports;
+      const playerBefore = await connection.getBalance(player.publicKey);
+      await cancel(seed, player.publicKey);
+      const playerAfter = await connection.getBalance(player.publicKey);
+      assert.equal(playerAfter - playerBefore, amount.toNumber() + betRent);
+    });
+
+    it("Returns the rent of a sponsored bet to the sponsor", async () => {
+      const seed = new anchor.BN(7_101);
+      const sponsorBefore = await connection.getBalance(sponsor.publicKey);
+      await place(seed, sponsor);
+      const bet = await program.account.bet.fetch(findBetPda(seed));
+      assert.equal(bet.rentPayer.toString(), sponsor.publicKey.toString());
+      const betRent = (await connection.getAccountInfo(findBetPda(seed)))
+        .lamports;
+      assert.equal(
+        sponsorBefore - (await connection.getBalance(sponsor.publicKey)),
+        betRent,
+        "Sponsor should fund the bet account",
+      );
+
+      try {
+        await cancel(seed, player.publicKey);
+        assert.fail("Cancel should fail - rent belongs to the sponsor");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RentPayerMismatch");
+      }
+
+      const playerBefore = await connection.getBalance(player.publicKey);
+      await cancel(seed, sponsor.publicKey);
+      const playerAfter = await connection.getBalance(player.publicKey);
+      assert.equal(playerAfter - playerBefore, amount.toNumber());
+      assert.equal(
+        await connection.getBalance(sponsor.publicKey),
+        sponsorBefore,
+        "Sponsor should get the rent back",
+      );
+    });
+  });
+
+  describe("Risk Free", () => {
+    it("Rejects a risk-free flag on a returning player's bet", async () => {
+      const seed = new anchor.BN(10_000);
+      try {
+        await program.methods
+          .placeBet(seed, betArgs(50, minBet, { riskFree: true }))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: findBetPda(seed),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Place bet should fail - not the first bet");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RiskFreeAlreadyUsed");
+      }
+    });
+
+    it("Marks a new player's first bet as risk-free", async () => {
+      const newPlayer = Keypair.generate();
+      const airdrop = await connection.requestAirdrop(
+        newPlayer.publicKey,
+        LAMPORTS_PER_SOL,
+      );
+      await connection.confirmTransaction(airdrop);
+      const [newStats] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("stats"),
+          house.publicKey.toBytes(),
+          Buffer.from([0]),
+          newPlayer.publicKey.toBytes(),
+        ],
+        program.programId,
+      );
+      const seed = new anchor.BN(10_001);
+
+      await program.methods
+        .placeBet(seed, betArgs(50, minBet, { riskFree: true }))
+        .accountsStrict({
+          player: newPlayer.publicKey,
+          rentPayer: newPlayer.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats: newStats,
+          bet: findBetPda(seed, newPlayer.publicKey),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(newPlayer.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([newPlayer])
+        .rpc();
+
+      const betAccount = await program.account.bet.fetch(findBetPda(seed, newPlayer.publicKey));
+      assert.isTrue(betAccount.riskFree, "First bet should be risk-free");
+    });
+  });
+
+  describe("Resolve Bet", () => {
+    const seed = new anchor.BN(100);
+    const roll = 50;
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    const memo = [...Buffer.from("order-1234".padEnd(32, "\0"))];
+    let betPda: PublicKey;
+    const instructionSysvar = new PublicKey(
+      "Sysvar1nstructions1111111111111111111111111",
+    );
+
+    before(async () => {
+      [betPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("bet"),
+          vault.toBytes(),
+          player.publicKey.toBytes(),
+          seed.toArrayLike(Buffer, "le", 16),
+        ],
+        program.programId,
+      );
+
+      await program.methods
+        .placeBet(seed, betArgs(roll, amount, { memo }))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("Stores the memo on the bet", async () => {
+      const betAccount = await program.account.bet.fetch(betPda);
+      assert.deepEqual(betAccount.memo, memo);
+    });
+
+    it("Rejects a signature that is not 64 bytes", async () => {
+      const betAccount = await program.account.bet.fetch(betPda);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(betAccount),
+      });
+
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 80))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: betPda,
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: instructionSysvar,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - truncated signature");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Ed25519SignatureLength");
+      }
+    });
+
+    it("Rejects an instructions account that isn't the sysvar", async () => {
+      const betAccount = await program.account.bet.fetch(betPda);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(betAccount),
+      });
+
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: betPda,
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: SYSVAR_SLOT_HASHES_PUBKEY,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - not the instructions sysvar");
+      } catch (err: any) {
+        // The account constraint catches it before the signature check's own
+        // `InvalidInstructionsSysvar` guard is reached.
+        expect(err.error.errorCode.code).to.equal("ConstraintAddress");
+      }
+    });
+
+    it("Rejects resolution signed by the house instead of the resolver", async () => {
+      try {
+        await program.methods
+          .resolveBet(Buffer.alloc(64))
+          .accountsStrict({
+            resolver: house.publicKey,
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: betPda,
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: instructionSysvar,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([house])
+          .rpc();
+        assert.fail("Resolve should fail - house is not the resolver");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("Rejects resolution without an ed25519 instruction", async () => {
+      try {
+        await program.methods
+          .resolveBet(Buffer.alloc(64))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: betPda,
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: instructionSysvar,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - no ed25519 instruction");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "Ed25519InstructionNotFound",
+        );
+      }
+    });
+
+    it("Rejects a signature no ed25519 instruction carries", async () => {
+      const betAccount = await program.account.bet.fetch(betPda);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(betAccount),
+      });
+      try {
+        await program.methods
+          .resolveBet(Buffer.alloc(64, 1))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: betPda,
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: instructionSysvar,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - signature not in the instruction");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Ed25519SignatureValue");
+      }
+    });
+
+    it("Rejects a signed message with trailing bytes", async () => {
+      const betAccount = await program.account.bet.fetch(betPda);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: Buffer.concat([betMessage(betAccount), Buffer.from([0])]),
+      });
+
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: betPda,
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: instructionSysvar,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - message length mismatch");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Ed25519MessageLength");
+      }
+    });
+
+    it("Rejects a truncated signed message", async () => {
+      const betAccount = await program.account.bet.fetch(betPda);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(betAccount).subarray(0, -1),
+      });
+
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: betPda,
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: instructionSysvar,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - message is a prefix of the bet's");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Ed25519MessageLength");
+      }
+    });
+
+    it("Rejects a message with an unsupported version", async () => {
+      const betAccount = await program.account.bet.fetch(betPda);
+      const message = betMessage(betAccount);
+      message[0] = BET_MESSAGE_VERSION + 1;
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message,
+      });
+
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: betPda,
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: instructionSysvar,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - unsupported version");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("UnsupportedBetVersion");
+      }
+    });
+
+    it("Resolves a bet correctly", async () => {
+      const betAccountInfo = await connection.getAccountInfo(betPda);
+      const betAccountBefore = await program.account.bet.fetch(betPda);
+      const slotHash = await revealedSlotHash(betAccountBefore, config);
+
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(betAccountBefore),
+      });
+
+      const resolveIx = await program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          jackpot,
+          jackpots,
+          feeVault,
+          treasury,
+          recentOutcomes,
+          ledger,
+          bet: betPda,
+          stats,
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: instructionSysvar,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .instruction();
+
+      const vaultBalanceBefore = await connection.getBalance(vault);
+      const feeVaultBalanceBefore = await connection.getBalance(feeVault);
+      const jackpotBefore = await program.account.jackpot.fetch(jackpot);
+      const playerBalanceBefore = await connection.getBalance(player.publicKey);
+      const betRentLamports = betAccountInfo.lamports;
+
+      const tx = new Transaction().add(ed25519Ix).add(resolveIx);
+      const sig = await sendAndConfirmTransaction(connection, tx, [resolver]);
+      console.log("Resolve Bet tx:", sig);
+
+      const txDetails = await connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(txDetails.meta.logMessages)];
+      const betResolved = events.find((e) => e.name === "betResolved");
+      assert.ok(betResolved, "BetResolved event should be emitted");
+      assert.equal(
+        betResolved.data.seed.toString(),
+        seed.toString(),
+        "Event seed should match",
+      );
+      assert.deepEqual(
+        betResolved.data.memo,
+        memo,
+        "Event should carry the bet's memo",
+      );
+      const expectedRoll = computeRoll(
+        ed25519Ix.data.subarray(48, 112),
+        slotHash,
+      );
+      assert.equal(
+        betResolved.data.result,
+        expectedRoll,
+        "Event roll should match compute_roll",
+      );
+      assert.isTrue(expectedRoll >= 1 && expectedRoll <= 100);
+
+      // The proof alone is enough to reproduce the roll offline.
+      const proof = events.find((e) => e.name === "outcomeProof");
+      assert.ok(proof, "OutcomeProof event should be emitted");
+      assert.deepEqual(Buffer.from(proof.data.slotHash), slotHash);
+      assert.deepEqual(
+        Buffer.from(proof.data.inputHash),
+        outcomeHash(ed25519Ix.data.subarray(48, 112), proof.data.slotHash),
+      );
+      assert.equal(proof.data.roll, expectedRoll);
+      assert.equal(
+        rollFromHash(proof.data.inputHash),
+        proof.data.roll,
+        "Roll should follow from the published hash",
+      );
+
+      // `ResolveOutcome { roll: u8, won: bool, payout: u64 }` as return data.
+      const [returnData] = txDetails.meta.returnData.data;
+      const outcome = Buffer.from(returnData, "base64");
+      assert.equal(outcome.readUInt8(0), expectedRoll, "Return data roll");
+      assert.equal(
+        outcome.readUInt8(1) === 1,
+        betResolved.data.won,
+        "Return data should agree with the event on the result",
+      );
+      assert.equal(
+        outcome.readBigUInt64LE(2).toString(),
+        betResolved.data.payout.toString(),
+        "Return data payout should match the event",
+      );
+
+      const houseFee = Math.floor((amount.toNumber() * feeBps) / 10_000);
+      const jackpotShare = Math.floor(
+        (houseFee * jackpotContributionBps) / 10_000,
+      );
+      const houseProfit = houseFee - jackpotShare;
+      const jackpotAfter = await program.account.jackpot.fetch(jackpot);
+      let jackpotPaid = 0;
+      if (expectedRoll !== 1) {
+        assert.equal(
+          jackpotAfter.balance.sub(jackpotBefore.balance).toNumber(),
+          jackpotShare,
+          "Jackpot should grow by its share of the house fee",
+        );
+      } else {
+        assert.equal(jackpotAfter.balance.toNumber(), 0, "Jackpot resets");
+        jackpotPaid = jackpotBefore.balance.toNumber() + jackpotShare;
+      }
+
+      const feeVaultBalanceAfter = await connection.getBalance(feeVault);
+      assert.equal(
+        feeVaultBalanceAfter - feeVaultBalanceBefore,
+        houseProfit,
+        "Fee vault should receive the house profit",
+      );
+
+      const closedBetAccount = await connection.getAccountInfo(betPda);
+
+      expect(closedBetAccount.data.length).to.equal(
+        0,
+        "Bet account should be closed after resolution(data === 0)",
+      );
+      expect(closedBetAccount.lamports).to.equal(
+        0,
+        "Bet account should be closed after resolution(lamporsts === 0)",
+      );
+
+      const vaultBalanceAfter = await connection.getBalance(vault);
+      const playerBalanceAfter = await connection.getBalance(player.publicKey);
+
+      const playerBalanceChange = playerBalanceAfter - playerBalanceBefore;
+      const vaultBalanceChange = vaultBalanceBefore - vaultBalanceAfter;
+
+      if (betResolved.data.won) {
+        const expectedPayout = payoutFor(
+          amount.toNumber(),
+          roll,
+          100,
+          feeBps,
+        );
+        assert.equal(
+          vaultBalanceChange,
+          expectedPayout + houseProfit + jackpotPaid,
+          "Vault should pay out the winnings and the house profit on win",
+        );
+        assert.equal(
+          playerBalanceChange,
+          betRentLamports + expectedPayout + jackpotPaid,
+          "Player should receive rent + payout on win",
+        );
+        console.log(
+          `Player won! Roll: ${betAccountBefore.roll}, Payout: ${
+            expectedPayout / LAMPORTS_PER_SOL
+          } SOL`,
+        );
+      } else {
+        assert.equal(
+          vaultBalanceChange,
+          houseProfit,
+          "Vault should only move the house profit on loss",
+        );
+        assert.equal(
+          playerBalanceChange,
+          betRentLamports,
+          "Player should only receive rent back on loss",
+        );
+        console.log(
+          `Player lost. Roll: ${betAccountBefore.roll}, no payout from vault`,
+        );
+      }
+    });
+
+    it("Does not release the reservation twice for a resolved bet", async () => {
+      const configBefore = await program.account.houseConfig.fetch(config);
+
+      try {
+        await program.methods
+          .resolveBet(Buffer.alloc(64))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: betPda,
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: instructionSysvar,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - bet already settled");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("AccountNotInitialized");
+      }
+
+      const configAfter = await program.account.houseConfig.fetch(config);
+      assert.equal(
+        configAfter.reservedLamports.toString(),
+        configBefore.reservedLamports.toString(),
+        "Reserved lamports should be unchanged",
+      );
+    });
+
+    it("Fails a retried resolve on the closed bet account", async () => {
+      const { betPda: retryPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        house.publicKey,
+        50,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+        false,
+      );
+      const resolveAgain = (extra: TransactionInstruction[] = []) =>
+        program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            bet: retryPda,
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: instructionSysvar,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([...extra, ed25519Ix])
+          .signers([resolver])
+          .rpc();
+      await resolveAgain();
+
+      try {
+        // A distinct transaction, so the cluster doesn't drop it as a
+        // duplicate of the first.
+        await resolveAgain([
+          ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+        ]);
+        assert.fail("Resolve should fail - bet already settled");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("AccountNotInitialized");
+        expect(err.error.origin).to.equal("bet");
+      }
+    });
+  });
+
+  describe("Player Account", () => {
+    const ownerHouse = Keypair.generate();
+    const owned = housePdas(ownerHouse.publicKey);
+    const assignedPlayer = Keypair.generate();
+    const seed = new anchor.BN(8_001);
+
+    before(async () => {
+      await setupHouse(ownerHouse);
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(
+          assignedPlayer.publicKey,
+          LAMPORTS_PER_SOL,
+        ),
+      );
+    });
+
+    it("Rejects a payout to a player no longer owned by the system program", async () => {
+      const betPda = owned.bet(seed, assignedPlayer.publicKey);
+      await program.methods
+        .placeBet(seed, betArgs(50, minBet))
+        .accountsStrict({
+          player: assignedPlayer.publicKey,
+          rentPayer: assignedPlayer.publicKey,
+          house: ownerHouse.publicKey,
+          vault: owned.vault,
+          config: owned.config,
+          stats: owned.stats(assignedPlayer.publicKey),
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(assignedPlayer.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([assignedPlayer])
+        .rpc();
+      const bet = await program.account.bet.fetch(betPda);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: assignedPlayer.secretKey,
+        message: betMessage(bet),
+      });
+
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.assign({
+            accountPubkey: assignedPlayer.publicKey,
+            programId: program.programId,
+          }),
+        ),
+        [assignedPlayer],
+      );
+      await revealedSlotHash(bet, owned.config);
+
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: assignedPlayer.publicKey,
+            rentPayer: assignedPlayer.publicKey,
+            resolver: resolver.publicKey,
+            house: ownerHouse.publicKey,
+            vault: owned.vault,
+            config: owned.config,
+            jackpot: owned.jackpot,
+            jackpots: owned.jackpots,
+            feeVault: owned.feeVault,
+            treasury: owned.treasury,
+            recentOutcomes: owned.recentOutcomes,
+            ledger: owned.ledger,
+            bet: betPda,
+            stats: owned.stats(assignedPlayer.publicKey),
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - player is program-owned");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidPlayerAccount");
+      }
+    });
+
+    it("Rejects a bet placed by the house itself", async () => {
+      const houseSeed = new anchor.BN(8_002);
+      try {
+        await program.methods
+          .placeBet(houseSeed, betArgs(50, minBet))
+          .accountsStrict({
+            player: ownerHouse.publicKey,
+            rentPayer: ownerHouse.publicKey,
+            house: ownerHouse.publicKey,
+            vault: owned.vault,
+            config: owned.config,
+            stats: owned.stats(ownerHouse.publicKey),
+            bet: owned.bet(houseSeed, ownerHouse.publicKey),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(ownerHouse.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([ownerHouse])
+          .rpc();
+        assert.fail("Place bet should fail - player is the house");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("PlayerIsHouseAccount");
+      }
+    });
+  });
+
+  describe("Cancellation Fee", () => {
+    const feeHouse = Keypair.generate();
+    const charging = housePdas(feeHouse.publicKey);
+    const cancelFeeBps = 500;
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    const fee = Math.floor((amount.toNumber() * cancelFeeBps) / 10_000);
+
+    const placeAnyBet = () =>
+      placeSignedBet(player, feeHouse.publicKey, 50, amount);
+
+    const setPaused = (paused: boolean) =>
+      program.methods
+        .setPaused(paused)
+        .accountsStrict({
+          authority: feeHouse.publicKey,
+          house: feeHouse.publicKey,
+          config: charging.config,
+        })
+        .signers([feeHouse])
+        .rpc();
+
+    before(async () => {
+      await setupHouse(feeHouse, { cancelFeeBps });
+    });
+
+    it("Rejects a cancellation fee above the whole stake", async () => {
+      try {
+        await setupHouse(Keypair.generate(), { cancelFeeBps: 10_001 });
+        assert.fail("Config should fail - fee above the stake");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("CancelFeeExceedsStake");
+      }
+    });
+
+    it("Keeps the fee when the player cancels", async () => {
+      const { betPda } = await placeAnyBet();
+      const vaultBefore = await connection.getBalance(charging.vault);
+      const feeVaultBefore = await connection.getBalance(charging.feeVault);
+      await cancelBetFor(player, feeHouse.publicKey, betPda);
+      const vaultAfter = await connection.getBalance(charging.vault);
+      const feeVaultAfter = await connection.getBalance(charging.feeVault);
+      assert.equal(
+        vaultBefore - vaultAfter,
+        amount.toNumber(),
+        "The whole stake should leave the vault",
+      );
+      assert.equal(
+        feeVaultAfter - feeVaultBefore,
+        fee,
+        "The fee vault should take cancel_fee_bps of the stake",
+      );
+    });
+
+    it("Refunds the whole stake when the house is at fault", async () => {
+      const { betPda } = await placeAnyBet();
+      const rent = await connection.getBalance(betPda);
+      await setPaused(true);
+      const playerBefore = await connection.getBalance(player.publicKey);
+      const feeVaultBefore = await connection.getBalance(charging.feeVault);
+      await program.methods
+        .emergencyRefund()
+        .accountsStrict({
+          authority: feeHouse.publicKey,
+          house: feeHouse.publicKey,
+          vault: charging.vault,
+          config: charging.config,
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          bet: betPda,
+          stats: charging.stats(player.publicKey),
+          freeCredits: null,
+          playerBalance: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([feeHouse])
+        .rpc();
+      await setPaused(false);
+      const playerAfter = await connection.getBalance(player.publicKey);
+      const feeVaultAfter = await connection.getBalance(charging.feeVault);
+      assert.equal(
+        playerAfter - playerBefore,
+        amount.toNumber() + rent,
+        "The player should get the full stake, plus the bet's rent",
+      );
+      assert.equal(feeVaultAfter, feeVaultBefore, "No fee should be taken");
+    });
+  });
+
+  describe("Free Credits", () => {
+    const creditHouse = Keypair.generate();
+    const credit = housePdas(creditHouse.publicKey);
+    const creditPlayer = Keypair.generate();
+    const freeCredits = credit.freeCredits(creditPlayer.publicKey);
+    const grant = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    const availableCredit = async () =>
+      (await connection.getBalance(freeCredits)) -
+      (await connection.getMinimumBalanceForRentExemption(8 + 32 + 1));
+
+    const resolveCreditBet = (
+      betPda: PublicKey,
+      ed25519Ix: TransactionInstruction,
+    ) =>
+      program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: creditPlayer.publicKey,
+          rentPayer: creditPlayer.publicKey,
+          resolver: resolver.publicKey,
+          house: creditHouse.publicKey,
+          vault: credit.vault,
+          config: credit.config,
+          jackpot: credit.jackpot,
+          jackpots: credit.jackpots,
+          feeVault: credit.feeVault,
+          treasury: credit.treasury,
+          recentOutcomes: credit.recentOutcomes,
+          ledger: credit.ledger,
+          bet: betPda,
+          stats: credit.stats(creditPlayer.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+
+    before(async () => {
+      await setupHouse(creditHouse);
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(creditPlayer.publicKey, LAMPORTS_PER_SOL),
+      );
+    });
+
+    it("Grants credit funded by the authority", async () => {
+      await program.methods
+        .grantFreeCredit(grant)
+        .accountsStrict({
+          authority: creditHouse.publicKey,
+          house: creditHouse.publicKey,
+          config: credit.config,
+          player: creditPlayer.publicKey,
+          freeCredits,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creditHouse])
+        .rpc();
+      expect(await availableCredit()).to.equal(grant.toNumber());
+    });
+
+    it("Rejects a bet larger than the remaining credit", async () => {
+      try {
+        await placeBetWithOutcome(
+          creditPlayer,
+          creditHouse.publicKey,
+          50,
+          grant.addn(1),
+          false,
+          () => true,
+          freeCredits,
+        );
+        assert.fail("Place bet should fail - not enough credit");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InsufficientFreeCredits");
+      }
+    });
+
+    it("Consumes the credit on a loss", async () => {
+      // Bets cancelled while looking for a loss return their stake to the
+      // credits, so only the kept bet is deducted.
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        creditPlayer,
+        creditHouse.publicKey,
+        50,
+        amount,
+        false,
+        undefined,
+        freeCredits,
+      );
+      assert.isTrue(bet.freeCredit);
+      expect(await availableCredit()).to.equal(
+        grant.sub(amount).toNumber(),
+      );
+
+      const betRent = await connection.getBalance(betPda);
+      const walletBefore = await connection.getBalance(creditPlayer.publicKey);
+      await resolveCreditBet(betPda, ed25519Ix);
+      const walletAfter = await connection.getBalance(creditPlayer.publicKey);
+      expect(walletAfter - walletBefore).to.equal(
+        betRent,
+        "A lost free bet pays nothing beyond the bet's rent",
+      );
+      expect(await availableCredit()).to.equal(
+        grant.sub(amount).toNumber(),
+      );
+    });
+
+    it("Pays a win to the wallet like any other bet", async () => {
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        creditPlayer,
+        creditHouse.publicKey,
+        50,
+        amount,
+        true,
+        undefined,
+        freeCredits,
+      );
+      const betRent = await connection.getBalance(betPda);
+      const walletBefore = await connection.getBalance(creditPlayer.publicKey);
+      await resolveCreditBet(betPda, ed25519Ix);
+      const walletAfter = await connection.getBalance(creditPlayer.publicKey);
+      expect(walletAfter - walletBefore).to.equal(
+        bet.potentialPayout.toNumber() + betRent,
+      );
+      expect(await availableCredit()).to.equal(
+        grant.sub(amount).sub(amount).toNumber(),
+      );
+    });
+
+    it("Refuses to cash out a free-credit bet", async () => {
+      const seed = new anchor.BN(113);
+      const betPda = credit.bet(seed, creditPlayer.publicKey);
+      const stats = credit.stats(creditPlayer.publicKey);
+      const playerCommit = [
+        ...createHash("sha256").update(Buffer.alloc(32, 5)).digest(),
+      ];
+      await program.methods
+        .placeBet(seed, betArgs(50, amount, { playerCommit }))
+        .accountsStrict({
+          player: creditPlayer.publicKey,
+          rentPayer: creditPlayer.publicKey,
+          house: creditHouse.publicKey,
+          vault: credit.vault,
+          config: credit.config,
+          stats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(creditPlayer.publicKey),
+          usedSeed: null,
+          freeCredits,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creditPlayer])
+        .rpc();
+
+      const bet = await program.account.bet.fetch(betPda);
+      assert.isTrue(bet.freeCredit);
+      const offerAmount = amount.divn(2);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: resolver.secretKey,
+        message: Buffer.concat([
+          betMessage(bet),
+          offerAmount.toArrayLike(Buffer, "le", 8),
+        ]),
+      });
+      try {
+        await program.methods
+          .cashout(offerAmount, ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: creditPlayer.publicKey,
+            rentPayer: creditPlayer.publicKey,
+            house: creditHouse.publicKey,
+            vault: credit.vault,
+            config: credit.config,
+            bet: betPda,
+            stats,
+            playerBalance: null,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([creditPlayer])
+          .rpc();
+        assert.fail("Cashout should fail - a grant can't be cashed out");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("FreeCreditCashout");
+      }
+    });
+  });
+
+  describe("Signature Window", () => {
+    const windowHouse = Keypair.generate();
+    const windowed = housePdas(windowHouse.publicKey);
+    const windowSlots = 2;
+
+    before(async () => {
+      await setupHouse(windowHouse, {
+        signatureWindowSlots: new anchor.BN(windowSlots),
+      });
+    });
+
+    it("Rejects a signature submitted after the bet's window", async () => {
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        windowHouse.publicKey,
+        50,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+        true,
+        () => true,
+      );
+      assert.equal(bet.signatureWindowSlots.toNumber(), windowSlots);
+
+      const deadline = bet.slot.toNumber() + windowSlots;
+      while ((await connection.getSlot("processed")) <= deadline) {
+        await new Promise((resolve) => setTimeout(resolve, 200));
+      }
+
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: windowHouse.publicKey,
+            vault: windowed.vault,
+            config: windowed.config,
+            jackpot: windowed.jackpot,
+            jackpots: windowed.jackpots,
+            feeVault: windowed.feeVault,
+            treasury: windowed.treasury,
+            recentOutcomes: windowed.recentOutcomes,
+            ledger: windowed.ledger,
+            bet: betPda,
+            stats: windowed.stats(player.publicKey),
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - signature window passed");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("SignatureExpired");
+      }
+    });
+  });
+
+  describe("Reveal Delay", () => {
+    const delayHouse = Keypair.generate();
+    const delayed = housePdas(delayHouse.publicKey);
+    const revealDelaySlots = 20;
+    let bet: any;
+    let betPda: PublicKey;
+    let ed25519Ix: TransactionInstruction;
+
+    before(async () => {
+      await setupHouse(delayHouse, {
+        revealDelaySlots: new anchor.BN(revealDelaySlots),
+      });
+      ({ bet, betPda, ed25519Ix } = await placeSignedBet(
+        player,
+        delayHouse.publicKey,
+        50,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+      ));
+    });
+
+    it("Rejects a reveal delay inside the cancel window", async () => {
+      try {
+        await setupHouse(Keypair.generate(), {
+          revealDelaySlots: new anchor.BN(minRevealDelaySlots - 1),
+        });
+        assert.fail("Config init should fail - delay inside cancel window");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidRevealDelay");
+      }
+    });
+
+    const resolveDelayed = (slotHashes: PublicKey | null) =>
+      program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: delayHouse.publicKey,
+          vault: delayed.vault,
+          config: delayed.config,
+          jackpot: delayed.jackpot,
+          jackpots: delayed.jackpots,
+          feeVault: delayed.feeVault,
+          treasury: delayed.treasury,
+          recentOutcomes: delayed.recentOutcomes,
+          ledger: delayed.ledger,
+          bet: betPda,
+          stats: delayed.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc({ commitment: "confirmed" });
+
+    it("Rejects a resolve before the reveal slot", async () => {
+      try {
+        await resolveDelayed(SYSVAR_SLOT_HASHES_PUBKEY);
+        assert.fail("Resolve should fail - reveal slot not produced yet");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ResolutionTooEarly");
+      }
+    });
+
+    it("Rejects a resolve without the SlotHashes sysvar", async () => {
+      try {
+        await resolveDelayed(null);
+        assert.fail("Resolve should fail - SlotHashes missing");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("SlotHashesRequired");
+      }
+    });
+
+    it("Rejects a cancel once the outcome is computable", async () => {
+      await revealedSlotHash(bet, delayed.config);
+      try {
+        await cancelBetFor(player, delayHouse.publicKey, betPda);
+        assert.fail("Cancel should fail - the outcome is already known");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("OutcomeRevealed");
+      }
+    });
+
+    it("Resolves from the reveal slot's hash once it has passed", async () => {
+      const revealHash = await revealedSlotHash(bet, delayed.config);
+      const signature = await resolveDelayed(SYSVAR_SLOT_HASHES_PUBKEY);
+      const tx = await connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(tx.meta.logMessages)];
+      const proof = events.find((e) => e.name === "outcomeProof");
+      assert.ok(proof, "OutcomeProof event should be emitted");
+      assert.deepEqual(Buffer.from(proof.data.slotHash), revealHash);
+      assert.notDeepEqual(
+        proof.data.slotHash,
+        bet.slotHash,
+        "Outcome should use the reveal slot's hash, not the placement one",
+      );
+      assert.deepEqual(
+        Buffer.from(proof.data.inputHash),
+        outcomeHash(ed25519Ix.data.subarray(48, 112), proof.data.slotHash),
+      );
+      const closedBet = await connection.getAccountInfo(betPda);
+      expect(closedBet?.lamports ?? 0).to.equal(0, "Bet should be closed");
+    });
+  });
+
+  describe("Randomness Backends", () => {
+    const sourceHouse = Keypair.generate();
+    const sourced = housePdas(sourceHouse.publicKey);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    let sourceSeed = 40_000;
+
+    before(async () => {
+      await setupHouse(sourceHouse);
+    });
+
+    const placeWithSource = async (randomnessSource: any, overrides = {}) => {
+      const seed = new anchor.BN(sourceSeed++);
+      const betPda = sourced.bet(seed);
+      await program.methods
+        .placeBet(
+          seed,
+          betArgs(50, amount, { randomnessSource, ...overrides }),
+        )
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: sourceHouse.publicKey,
+          vault: sourced.vault,
+          config: sourced.config,
+          stats: sourced.stats(player.publicKey),
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+      return betPda;
+    };
+
+    const resolveFromSource = (
+      betPda: PublicKey,
+      sig: Buffer,
+      preInstructions: TransactionInstruction[] = [],
+    ) =>
+      program.methods
+        .resolveBetFromSource(sig)
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: sourceHouse.publicKey,
+          vault: sourced.vault,
+          config: sourced.config,
+          jackpot: sourced.jackpot,
+          jackpots: sourced.jackpots,
+          feeVault: sourced.feeVault,
+          treasury: sourced.treasury,
+          recentOutcomes: sourced.recentOutcomes,
+          ledger: sourced.ledger,
+          bet: betPda,
+          stats: sourced.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions(preInstructions)
+        .signers([resolver])
+        .rpc({ commitment: "confirmed" });
+
+    const outcomeProof = async (signature: string) => {
+      const tx = await connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(tx.meta.logMessages)];
+      return events.find((e) => e.name === "outcomeProof").data;
+    };
+
+    it("Resolves a signature bet through the Ed25519 backend", async () => {
+      const betPda = await placeWithSource({ signature: {} });
+      const bet = await program.account.bet.fetch(betPda);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(bet),
+      });
+      const sig = Buffer.from(ed25519Ix.data.subarray(48, 112));
+      const slotHash = await revealedSlotHash(bet, sourced.config);
+
+      const proof = await outcomeProof(
+        await resolveFromSource(betPda, sig, [ed25519Ix]),
+      );
+      assert.deepEqual(Buffer.from(proof.slotHash), slotHash);
+      assert.equal(proof.roll, computeRoll(sig, slotHash));
+    });
+
+    it("Rejects a signature bet without the player's signature", async () => {
+      const betPda = await placeWithSource({ signature: {} });
+      try {
+        await resolveFromSource(betPda, Buffer.alloc(0));
+        assert.fail("Resolve should fail - no signature");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Ed25519SignatureLength");
+      }
+      await cancelBetFor(player, sourceHouse.publicKey, betPda);
+    });
+
+    it("Rolls a slot-hash bet on a slot after its cancel window", async () => {
+      const betPda = await placeWithSource({ slotHash: {} });
+      const bet = await program.account.bet.fetch(betPda);
+      assert.deepEqual(bet.randomnessSource, { slotHash: {} });
+      try {
+        await resolveFromSource(betPda, Buffer.alloc(0));
+        assert.fail("Resolve should fail - entropy slot not produced yet");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ResolutionTooEarly");
+      }
+
+      const slotHash = await revealedSlotHash(bet, sourced.config);
+      const proof = await outcomeProof(
+        await resolveFromSource(betPda, Buffer.alloc(0)),
+      );
+      assert.deepEqual(Buffer.from(proof.slotHash), slotHash);
+      assert.notDeepEqual(proof.slotHash, bet.slotHash);
+      assert.deepEqual(
+        Buffer.from(proof.inputHash),
+        outcomeHash(betPda.toBuffer(), proof.slotHash),
+      );
+      assert.equal(proof.roll, computeRoll(betPda.toBuffer(), proof.slotHash));
+    });
+
+    it("Rejects placing a bet on a beacon", async () => {
+      try {
+        await placeWithSource({ beacon: {} });
+        assert.fail("Place should fail - no beacon backend");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "RandomnessBackendUnsupported",
+        );
+      }
+    });
+
+    it("Rejects a slot-hash bet that also commits to a secret", async () => {
+      try {
+        await placeWithSource(
+          { slotHash: {} },
+          { playerCommit: Array(32).fill(7) },
+        );
+        assert.fail("Place should fail - a commit needs commit-reveal");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RandomnessSourceMismatch");
+      }
+    });
+  });
+
+  describe("House Ledger", () => {
+    const ledgerHouse = Keypair.generate();
+    const books = housePdas(ledgerHouse.publicKey);
+    const roll = 50;
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+
+    before(async () => {
+      await setupHouse(ledgerHouse);
+    });
+
+    const placeAndResolve = async (wantWin: boolean) => {
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        ledgerHouse.publicKey,
+        roll,
+        amount,
+        wantWin,
+      );
+      await program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: ledgerHouse.publicKey,
+          vault: books.vault,
+          config: books.config,
+          jackpot: books.jackpot,
+          jackpots: books.jackpots,
+          feeVault: books.feeVault,
+          treasury: books.treasury,
+          recentOutcomes: books.recentOutcomes,
+          ledger: books.ledger,
+          bet: betPda,
+          stats: books.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+    };
+
+    it("Starts at zero", async () => {
+      const ledger = await program.account.houseLedger.fetch(books.ledger);
+      assert.equal(ledger.lifetimeBets.toNumber(), 0);
+      assert.equal(ledger.lifetimeVolume.toNumber(), 0);
+      assert.equal(ledger.lifetimePayouts.toNumber(), 0);
+      assert.equal(ledger.lifetimeFees.toNumber(), 0);
+    });
+
+    it("Reconciles after a series of wins and losses", async () => {
+      // Every win follows a loss, so no streak bonus is ever paid.
+      const results = [true, false, true, false, false];
+      for (const won of results) {
+        await placeAndResolve(won);
+      }
+
+      const wins = results.filter((won) => won).length;
+      const fee = Math.floor((amount.toNumber() * feeBps) / 10_000);
+      const ledger = await program.account.houseLedger.fetch(books.ledger);
+      assert.equal(ledger.lifetimeBets.toNumber(), results.length);
+      assert.equal(
+        ledger.lifetimeVolume.toNumber(),
+        amount.toNumber() * results.length,
+      );
+      assert.equal(
+        ledger.lifetimePayouts.toNumber(),
+        wins * payoutFor(amount.toNumber(), roll, 100, feeBps),
+      );
+      assert.equal(ledger.lifetimeFees.toNumber(), fee * results.length);
+    });
+  });
+
+  describe("Recent Outcomes", () => {
+    const feedHouse = Keypair.generate();
+    const feed = housePdas(feedHouse.publicKey);
+    // RECENT_OUTCOMES_LEN in `state/recent_outcomes.rs`.
+    const capacity = 16;
+
+    before(async () => {
+      await setupHouse(feedHouse);
+    });
+
+    const placeAndResolve = async () => {
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        feedHouse.publicKey,
+        50,
+        minBet,
+        true,
+        () => true,
+      );
+      await program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: feedHouse.publicKey,
+          vault: feed.vault,
+          config: feed.config,
+          jackpot: feed.jackpot,
+          jackpots: feed.jackpots,
+          feeVault: feed.feeVault,
+          treasury: feed.treasury,
+          recentOutcomes: feed.recentOutcomes,
+          ledger: feed.ledger,
+          bet: betPda,
+          stats: feed.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+      return computeRoll(
+        ed25519Ix.data.subarray(48, 112),
+        await revealedSlotHash(bet, feed.config),
+      );
+    };
+
+    it("Starts empty", async () => {
+      const recent = await program.account.recentOutcomes.fetch(
+        feed.recentOutcomes,
+      );
+      assert.equal(recent.position, 0);
+      assert.equal(recent.outcomes.length, capacity);
+      assert.isTrue(
+        recent.outcomes.every((outcome) => outcome.roll === 0),
+        "Unwritten entries should be zeroed",
+      );
+    });
+
+    it("Wraps around once more than the capacity has been resolved", async () => {
+      const rolls: number[] = [];
+      for (let i = 0; i < capacity + 2; i++) {
+        rolls.push(await placeAndResolve());
+      }
+
+      const recent = await program.account.recentOutcomes.fetch(
+        feed.recentOutcomes,
+      );
+      assert.equal(recent.position, 2, "Position should wrap past the end");
+      // The two newest overwrote the two oldest; the rest are in order.
+      const expected = [...rolls.slice(capacity), ...rolls.slice(2, capacity)];
+      assert.deepEqual(
+        recent.outcomes.map((outcome) => outcome.roll),
+        expected,
+      );
+      for (const outcome of recent.outcomes) {
+        assert.ok(outcome.player.equals(player.publicKey));
+        assert.equal(outcome.won, outcome.payout.gtn(0));
+      }
+    });
+  });
+
+  describe("Reward Token", () => {
+    const TOKEN_PROGRAM_ID = new PublicKey(
+      "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    );
+    const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey(
+      "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    );
+    const rewardHouse = Keypair.generate();
+    const rewarded = housePdas(rewardHouse.publicKey);
+    const mint = Keypair.generate();
+    const rewardRate = 3;
+    const playerAta = PublicKey.findProgramAddressSync(
+      [
+        player.publicKey.toBuffer(),
+        TOKEN_PROGRAM_ID.toBuffer(),
+        mint.publicKey.toBuffer(),
+      ],
+      ASSOCIATED_TOKEN_PROGRAM_ID,
+    )[0];
+
+    before(async () => {
+      // A bare SPL mint whose authority is the house vault, plus the
+      // player's associated token account for it.
+      const mintSpace = 82;
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: player.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports:
+            await connection.getMinimumBalanceForRentExemption(mintSpace),
+          space: mintSpace,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        new TransactionInstruction({
+          programId: TOKEN_PROGRAM_ID,
+          keys: [{ pubkey: mint.publicKey, isSigner: false, isWritable: true }],
+          // InitializeMint2: decimals 0, vault as mint authority, no freeze.
+          data: Buffer.concat([
+            Buffer.from([20, 0]),
+            rewarded.vault.toBuffer(),
+            Buffer.from([0]),
+          ]),
+        }),
+        new TransactionInstruction({
+          programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+          keys: [
+            { pubkey: player.publicKey, isSigner: true, isWritable: true },
+            { pubkey: playerAta, isSigner: false, isWritable: true },
+            { pubkey: player.publicKey, isSigner: false, isWritable: false },
+            { pubkey: mint.publicKey, isSigner: false, isWritable: false },
+            {
+              pubkey: SystemProgram.programId,
+              isSigner: false,
+              isWritable: false,
+            },
+            { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.from([1]),
+        }),
+      );
+      await sendAndConfirmTransaction(connection, tx, [player, mint]);
+
+      await setupHouse(rewardHouse, {
+        rewardsEnabled: true,
+        rewardMint: mint.publicKey,
+        rewardRate: new anchor.BN(rewardRate),
+      });
+    });
+
+    const resolveAccounts = (betPda: PublicKey) => ({
+      player: player.publicKey,
+      rentPayer: player.publicKey,
+      resolver: resolver.publicKey,
+      house: rewardHouse.publicKey,
+      vault: rewarded.vault,
+      config: rewarded.config,
+      jackpot: rewarded.jackpot,
+      jackpots: rewarded.jackpots,
+      feeVault: rewarded.feeVault,
+      treasury: rewarded.treasury,
+      recentOutcomes: rewarded.recentOutcomes,
+      ledger: rewarded.ledger,
+      bet: betPda,
+      stats: rewarded.stats(player.publicKey),
+      playerBalance: null,
+      pendingPayout: null,
+      slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      priceUpdate: null,
+      rewardMint: mint.publicKey,
+      playerRewardAta: playerAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    });
+
+    it("Rejects a resolve without the reward accounts", async () => {
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        rewardHouse.publicKey,
+        50,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+        false,
+      );
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            ...resolveAccounts(betPda),
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - reward accounts missing");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RewardAccountsRequired");
+      }
+      await discardBet(rewardHouse.publicKey, betPda);
+    });
+
+    it("Mints amount * reward_rate to the player on resolution", async () => {
+      const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        rewardHouse.publicKey,
+        50,
+        amount,
+        false,
+      );
+      await program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict(resolveAccounts(betPda))
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+
+      const balance = await connection.getTokenAccountBalance(playerAta);
+      assert.equal(balance.value.amount, amount.muln(rewardRate).toString());
+    });
+  });
+
+  describe("Keeper Resolution", () => {
+    const keeperHouse = Keypair.generate();
+    const keeperPdas = housePdas(keeperHouse.publicKey);
+    const keeper = Keypair.generate();
+
+    before(async () => {
+      await setupHouse(keeperHouse, { keeperFeeBps: 2000 });
+      const airdrop = await connection.requestAirdrop(
+        keeper.publicKey,
+        LAMPORTS_PER_SOL,
+      );
+      await connection.confirmTransaction(airdrop);
+    });
+
+    const keeperAccounts = (betPda: PublicKey) => ({
+      keeper: keeper.publicKey,
+      house: keeperHouse.publicKey,
+      vault: keeperPdas.vault,
+      config: keeperPdas.config,
+      jackpot: keeperPdas.jackpot,
+      jackpots: keeperPdas.jackpots,
+      feeVault: keeperPdas.feeVault,
+      treasury: keeperPdas.treasury,
+      player: player.publicKey,
+      rentPayer: player.publicKey,
+      bet: betPda,
+      stats: keeperPdas.stats(player.publicKey),
+      playerBalance: null,
+      pendingPayout: null,
+      priceUpdate: null,
+      slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      systemProgram: SystemProgram.programId,
+    });
+
+    it("Rejects a resolver signature over the plain bet message", async () => {
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        keeperHouse.publicKey,
+        50,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+        false,
+      );
+      const resolverIx = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: resolver.secretKey,
+        message: betMessage(bet),
+      });
+      try {
+        await program.methods
+          .keeperResolveBet(
+            ed25519Ix.data.subarray(48, 112),
+            resolverIx.data.subarray(48, 112),
+          )
+          .accountsStrict(keeperAccounts(betPda))
+          .preInstructions([ed25519Ix, resolverIx])
+          .signers([keeper])
+          .rpc();
+        assert.fail("Resolve should fail - not a keeper authorization");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "KeeperAuthorizationMismatch",
+        );
+      }
+      await discardBet(keeperHouse.publicKey, betPda);
+    });
+
+    it("Pays the keeper keeper_fee_bps of the house fee", async () => {
+      const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        keeperHouse.publicKey,
+        50,
+        amount,
+        false,
+      );
+      const resolverIx = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: resolver.secretKey,
+        message: Buffer.concat([betMessage(bet), Buffer.from("keeper")]),
+      });
+      const keeperBefore = await connection.getBalance(keeper.publicKey);
+      const sig = await program.methods
+        .keeperResolveBet(
+          ed25519Ix.data.subarray(48, 112),
+          resolverIx.data.subarray(48, 112),
+        )
+        .accountsStrict(keeperAccounts(betPda))
+        .preInstructions([ed25519Ix, resolverIx])
+        .signers([keeper])
+        .rpc({ commitment: "confirmed" });
+
+      // The keeper earns 20% of the house fee.
+      const expectedFee = (amount.toNumber() * feeBps) / 10_000 / 5;
+      const txDetails = await connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(txDetails.meta.logMessages)];
+      const paid = events.find((e) => e.name === "keeperPaid");
+      assert.ok(paid, "KeeperPaid event should be emitted");
+      assert.equal(paid.data.keeper.toString(), keeper.publicKey.toString());
+      assert.equal(paid.data.amount.toNumber(), expectedFee);
+
+      const keeperAfter = await connection.getBalance(
+        keeper.publicKey,
+        "confirmed",
+      );
+      assert.equal(
+        keeperAfter - keeperBefore + txDetails.meta.fee,
+        expectedFee,
+      );
+    });
+  });
+
+  describe("Auto Resolution", () => {
+    const autoHouse = Keypair.generate();
+    const auto = housePdas(autoHouse.publicKey);
+    const thread = Keypair.generate();
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    before(async () => {
+      await setupHouse(autoHouse, { autoResolve: true });
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(thread.publicKey, LAMPORTS_PER_SOL),
+      );
+    });
+
+    const presign = (
+      houseKey: PublicKey,
+      betPda: PublicKey,
+      ed25519Ix: TransactionInstruction,
+    ) => {
+      const pdas = housePdas(houseKey);
+      return program.methods
+        .presignBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          house: houseKey,
+          vault: pdas.vault,
+          config: pdas.config,
+          bet: betPda,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([player])
+        .rpc();
+    };
+
+    // Everything but the signer follows from the config and the bet.
+    const resolveAuto = (betPda: PublicKey) =>
+      program.methods
+        .resolveBetAuto()
+        .accountsStrict({
+          keeper: thread.publicKey,
+          house: autoHouse.publicKey,
+          vault: auto.vault,
+          config: auto.config,
+          jackpot: auto.jackpot,
+          jackpots: auto.jackpots,
+          feeVault: auto.feeVault,
+          treasury: auto.treasury,
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          bet: betPda,
+          stats: auto.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([thread])
+        .rpc({ commitment: "confirmed" });
+
+    it("Rejects presigning while the house hasn't enabled it", async () => {
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        house.publicKey,
+        50,
+        amount,
+        false,
+      );
+      try {
+        await presign(house.publicKey, betPda, ed25519Ix);
+        assert.fail("Presign should fail - auto-resolution disabled");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("AutoResolveDisabled");
+      }
+      await discardBet(house.publicKey, betPda);
+    });
+
+    it("Rejects auto-resolving a bet that wasn't presigned", async () => {
+      const { betPda } = await placeBetWithOutcome(
+        player,
+        autoHouse.publicKey,
+        50,
+        amount,
+        false,
+      );
+      try {
+        await resolveAuto(betPda);
+        assert.fail("Resolve should fail - no presigned signature");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("BetNotPresigned");
+      }
+      await discardBet(autoHouse.publicKey, betPda);
+    });
+
+    it("Settles a presigned bet with only the keeper signing", async () => {
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        autoHouse.publicKey,
+        50,
+        amount,
+        false,
+      );
+      await presign(autoHouse.publicKey, betPda, ed25519Ix);
+      const stored = await program.account.bet.fetch(betPda);
+      assert.deepEqual(
+        Buffer.from(stored.presignedSig),
+        Buffer.from(ed25519Ix.data.subarray(48, 112)),
+      );
+      try {
+        await presign(autoHouse.publicKey, betPda, ed25519Ix);
+        assert.fail("Presign should fail - already presigned");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("BetAlreadyPresigned");
+      }
+
+      const sig = await resolveAuto(betPda);
+      const txDetails = await connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(txDetails.meta.logMessages)];
+      const proof = events.find((e) => e.name === "outcomeProof");
+      assert.equal(
+        proof.data.roll,
+        computeRoll(
+          ed25519Ix.data.subarray(48, 112),
+          await revealedSlotHash(bet, auto.config),
+        ),
+        "The roll should come from the presigned signature",
+      );
+      const resolved = events.find((e) => e.name === "betResolved");
+      assert.equal(resolved.data.won, false);
+      assert.isNull(
+        await connection.getAccountInfo(betPda, "confirmed"),
+        "Bet should be closed",
+      );
+    });
+  });
+
+  describe("Resolver Quorum", () => {
+    const quorumHouse = Keypair.generate();
+    const quorum = housePdas(quorumHouse.publicKey);
+    const members = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    const resolverSet = (keys: PublicKey[]) => [
+      ...keys,
+      ...Array(5 - keys.length).fill(PublicKey.default),
+    ];
+
+    const setResolverSet = (keys: PublicKey[], threshold: number) =>
+      program.methods
+        .setResolverSet(resolverSet(keys), threshold)
+        .accountsStrict({
+          authority: quorumHouse.publicKey,
+          house: quorumHouse.publicKey,
+          config: quorum.config,
+        })
+        .signers([quorumHouse])
+        .rpc();
+
+    const approval = (member: Keypair, bet: any) =>
+      Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: member.secretKey,
+        message: Buffer.concat([betMessage(bet), Buffer.from("quorum")]),
+      });
+
+    const resolveWithQuorum = (
+      betPda: PublicKey,
+      ed25519Ix: TransactionInstruction,
+      approvals: TransactionInstruction[],
+    ) =>
+      program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: quorumHouse.publicKey,
+          vault: quorum.vault,
+          config: quorum.config,
+          jackpot: quorum.jackpot,
+          jackpots: quorum.jackpots,
+          feeVault: quorum.feeVault,
+          treasury: quorum.treasury,
+          recentOutcomes: quorum.recentOutcomes,
+          ledger: quorum.ledger,
+          bet: betPda,
+          stats: quorum.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix, ...approvals])
+        .signers([resolver])
+        .rpc();
+
+    before(async () => {
+      await setupHouse(quorumHouse);
+    });
+
+    it("Rejects a threshold larger than the set", async () => {
+      try {
+        await setResolverSet([members[0].publicKey], 2);
+        assert.fail("Update should fail - threshold can't be met");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidResolverSet");
+      }
+    });
+
+    it("Rejects a set listing the same key twice", async () => {
+      try {
+        await setResolverSet(
+          [members[0].publicKey, members[0].publicKey],
+          2,
+        );
+        assert.fail("Update should fail - duplicate resolver");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidResolverSet");
+      }
+    });
+
+    it("Stores a 2-of-3 resolver set", async () => {
+      await setResolverSet(
+        members.map((member) => member.publicKey),
+        2,
+      );
+      const config = await program.account.houseConfig.fetch(quorum.config);
+      expect(config.resolverThreshold).to.equal(2);
+      expect(config.resolverSet[2].toString()).to.equal(
+        members[2].publicKey.toString(),
+      );
+      expect(config.resolverSet[3].toString()).to.equal(
+        PublicKey.default.toString(),
+      );
+    });
+
+    it("Rejects a resolve approved by a single member", async () => {
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        quorumHouse.publicKey,
+        50,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+        false,
+      );
+      // The same member approving twice still counts once.
+      const approvals = [approval(members[0], bet), approval(members[0], bet)];
+      try {
+        await resolveWithQuorum(betPda, ed25519Ix, approvals);
+        assert.fail("Resolve should fail - quorum not reached");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "InsufficientResolverSignatures",
+        );
+      }
+      await discardBet(quorumHouse.publicKey, betPda);
+    });
+
+    it("Rejects approvals from keys outside the set", async () => {
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        quorumHouse.publicKey,
+        50,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+        false,
+      );
+      const approvals = [approval(members[0], bet), approval(resolver, bet)];
+      try {
+        await resolveWithQuorum(betPda, ed25519Ix, approvals);
+        assert.fail("Resolve should fail - outsider approvals don't count");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "InsufficientResolverSignatures",
+        );
+      }
+      await discardBet(quorumHouse.publicKey, betPda);
+    });
+
+    it("Resolves once two members approve", async () => {
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        quorumHouse.publicKey,
+        50,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+        false,
+      );
+      await resolveWithQuorum(betPda, ed25519Ix, [
+        approval(members[2], bet),
+        approval(members[0], bet),
+      ]);
+      const closedBet = await connection.getAccountInfo(betPda);
+      expect(closedBet?.lamports ?? 0).to.equal(0, "Bet should be closed");
+    });
+
+    it("Turns the quorum off with a threshold of 0", async () => {
+      await setResolverSet([], 0);
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        quorumHouse.publicKey,
+        50,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+        false,
+      );
+      await resolveWithQuorum(betPda, ed25519Ix, []);
+      const closedBet = await connection.getAccountInfo(betPda);
+      expect(closedBet?.lamports ?? 0).to.equal(0, "Bet should be closed");
+    });
+  });
+
+  describe("Resolution Window", () => {
+    const windowHouse = Keypair.generate();
+    const windowed = housePdas(windowHouse.publicKey);
+    const windowSlots = 50;
+
+    const windowSignature = (bet: any, validFrom: number, validUntil: number) =>
+      Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: resolver.secretKey,
+        message: Buffer.concat([
+          betMessage(bet),
+          Buffer.from("window"),
+          new anchor.BN(validFrom).toArrayLike(Buffer, "le", 8),
+          new anchor.BN(validUntil).toArrayLike(Buffer, "le", 8),
+        ]),
+      });
+
+    const resolveInWindow = (
+      betPda: PublicKey,
+      ed25519Ix: TransactionInstruction,
+      windowIxs: TransactionInstruction[],
+    ) =>
+      program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: windowHouse.publicKey,
+          vault: windowed.vault,
+          config: windowed.config,
+          jackpot: windowed.jackpot,
+          jackpots: windowed.jackpots,
+          feeVault: windowed.feeVault,
+          treasury: windowed.treasury,
+          recentOutcomes: windowed.recentOutcomes,
+          ledger: windowed.ledger,
+          bet: betPda,
+          stats: windowed.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix, ...windowIxs])
+        .signers([resolver])
+        .rpc();
+
+    const expectStale = async (
+      betPda: PublicKey,
+      ed25519Ix: TransactionInstruction,
+      windowIxs: TransactionInstruction[],
+    ) => {
+      try {
+        await resolveInWindow(betPda, ed25519Ix, windowIxs);
+        assert.fail("Resolve should fail - no fresh window");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ResolutionStale");
+      }
+      await discardBet(windowHouse.publicKey, betPda);
+    };
+
+    const placeWindowedBet = () =>
+      placeBetWithOutcome(
+        player,
+        windowHouse.publicKey,
+        50,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+        false,
+      );
+
+    before(async () => {
+      await setupHouse(windowHouse, {
+        resolutionWindowSlots: new anchor.BN(windowSlots),
+      });
+    });
+
+    it("Rejects a resolve without a window signature", async () => {
+      const { betPda, ed25519Ix } = await placeWindowedBet();
+      await expectStale(betPda, ed25519Ix, []);
+    });
+
+    it("Rejects a window that has already closed", async () => {
+      const { bet, betPda, ed25519Ix } = await placeWindowedBet();
+      const slot = await connection.getSlot();
+      await expectStale(betPda, ed25519Ix, [
+        windowSignature(bet, Math.max(slot - 100, 0), Math.max(slot - 60, 0)),
+      ]);
+    });
+
+    it("Rejects a window wider than the house allows", async () => {
+      const { bet, betPda, ed25519Ix } = await placeWindowedBet();
+      const slot = await connection.getSlot();
+      await expectStale(betPda, ed25519Ix, [
+        windowSignature(bet, slot, slot + windowSlots + 1),
+      ]);
+    });
+
+    it("Rejects a window signed by someone other than the resolver", async () => {
+      const { bet, betPda, ed25519Ix } = await placeWindowedBet();
+      const slot = await connection.getSlot();
+      const forged = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: Buffer.concat([
+          betMessage(bet),
+          Buffer.from("window"),
+          new anchor.BN(slot).toArrayLike(Buffer, "le", 8),
+          new anchor.BN(slot + 40).toArrayLike(Buffer, "le", 8),
+        ]),
+      });
+      await expectStale(betPda, ed25519Ix, [forged]);
+    });
+
+    it("Resolves within a window covering the current slot", async () => {
+      const { bet, betPda, ed25519Ix } = await placeWindowedBet();
+      const slot = await connection.getSlot();
+      await resolveInWindow(betPda, ed25519Ix, [
+        windowSignature(bet, slot, slot + 40),
+      ]);
+      const closedBet = await connection.getAccountInfo(betPda);
+      expect(closedBet?.lamports ?? 0).to.equal(0, "Bet should be closed");
+    });
+  });
+
+  describe("Full Cycle", () => {
+    // A fresh player keeps the streak bonus and risk-free state out of the
+    // payout math.
+    const cyclePlayer = Keypair.generate();
+    const cycleStats = housePdas(house.publicKey).stats(cyclePlayer.publicKey);
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    const roll = 50;
+
+    before(async () => {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(
+          cyclePlayer.publicKey,
+          2 * LAMPORTS_PER_SOL,
+        ),
+      );
+    });
+
+    const placeWithOutcome = (wantWin: boolean) =>
+      placeBetWithOutcome(cyclePlayer, house.publicKey, roll, amount, wantWin);
+
+    const resolve = (betPda: PublicKey, ed25519Ix: TransactionInstruction) =>
+      program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: cyclePlayer.publicKey,
+          rentPayer: cyclePlayer.publicKey,
+          resolver: resolver.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          jackpot,
+          jackpots,
+          feeVault,
+          treasury,
+          recentOutcomes,
+          ledger,
+          bet: betPda,
+          stats: cycleStats,
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+
+    it("Pays the winnings when the player's signature wins", async () => {
+      const { betPda, ed25519Ix } = await placeWithOutcome(true);
+      const betRent = (await connection.getAccountInfo(betPda)).lamports;
+      const balanceBefore = await connection.getBalance(cyclePlayer.publicKey);
+
+      await resolve(betPda, ed25519Ix);
+
+      const balanceAfter = await connection.getBalance(cyclePlayer.publicKey);
+      assert.equal(
+        balanceAfter - balanceBefore,
+        betRent + payoutFor(amount.toNumber(), roll, 100, feeBps),
+        "Player should receive the payout and the bet rent",
+      );
+      const statsAccount = await program.account.playerStats.fetch(cycleStats);
+      assert.equal(statsAccount.winCount.toNumber(), 1);
+    });
+
+    it("Pays nothing beyond the rent when the player's signature loses", async () => {
+      const { betPda, ed25519Ix } = await placeWithOutcome(false);
+      const betRent = (await connection.getAccountInfo(betPda)).lamports;
+      const balanceBefore = await connection.getBalance(cyclePlayer.publicKey);
+
+      await resolve(betPda, ed25519Ix);
+
+      const balanceAfter = await connection.getBalance(cyclePlayer.publicKey);
+      assert.equal(
+        balanceAfter - balanceBefore,
+        betRent,
+        "Player should only get the bet rent back",
+      );
+      const statsAccount = await program.account.playerStats.fetch(cycleStats);
+      assert.equal(statsAccount.winCount.toNumber(), 1, "Loss adds no win");
+      assert.equal(statsAccount.currentStreak, 0, "Loss resets the streak");
+    });
+
+    // deposit transfers the recorded bet.amount, so a bet can't be placed
+    // whose amount differs from the lamports it locked; the payout follows
+    // from what the vault actually received.
+    it("Pays a win from the lamports the bet locked", async () => {
+      const vaultBefore = await connection.getBalance(vault);
+      const { bet, betPda, ed25519Ix } = await placeWithOutcome(true);
+      const deposited = (await connection.getBalance(vault)) - vaultBefore;
+      assert.equal(deposited, bet.amount.toNumber());
+
+      const betRent = (await connection.getAccountInfo(betPda)).lamports;
+      const balanceBefore = await connection.getBalance(cyclePlayer.publicKey);
+      await resolve(betPda, ed25519Ix);
+      const balanceAfter = await connection.getBalance(cyclePlayer.publicKey);
+
+      const statsAccount = await program.account.playerStats.fetch(cycleStats);
+      assert.equal(statsAccount.currentStreak, 1, "No streak bonus applies");
+      assert.equal(
+        balanceAfter - balanceBefore - betRent,
+        payoutFor(deposited, roll, 100, feeBps),
+        "Payout should follow from the deposited lamports",
+      );
+    });
+
+    it("Rejects a genuine signature over a different bet", async () => {
+      const { bet, betPda } = await placeWithOutcome(true);
+      const forged = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: cyclePlayer.secretKey,
+        message: betMessage({ ...bet, amount: bet.amount.muln(2) }),
+      });
+
+      try {
+        await resolve(betPda, forged);
+        assert.fail("Resolve should fail - signature is over another bet");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Ed25519Message");
+      }
+      await discardBet(house.publicKey, betPda);
+    });
+  });
+
+  describe("Bonus Roll Offset", () => {
+    const promoHouse = Keypair.generate();
+    const promo = housePdas(promoHouse.publicKey);
+    const promoPlayer = Keypair.generate();
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    const roll = 50;
+    const offset = 10;
+
+    before(async () => {
+      await setupHouse(promoHouse);
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(
+          promoPlayer.publicKey,
+          2 * LAMPORTS_PER_SOL,
+        ),
+      );
+    });
+
+    it("Rejects an offset change from a non-authority signer", async () => {
+      try {
+        await program.methods
+          .setBonusRollOffset(offset)
+          .accountsStrict({
+            authority: player.publicKey,
+            house: promoHouse.publicKey,
+            config: promo.config,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Update should fail - wrong authority");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("Wins an Under bet the offset lifts over the line", async () => {
+      await program.methods
+        .setBonusRollOffset(offset)
+        .accountsStrict({
+          authority: promoHouse.publicKey,
+          house: promoHouse.publicKey,
+          config: promo.config,
+        })
+        .signers([promoHouse])
+        .rpc();
+
+      // Loses at the placed threshold, wins once it is shifted.
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        promoPlayer,
+        promoHouse.publicKey,
+        roll,
+        amount,
+        true,
+        (outcome) => outcome > roll && outcome <= roll + offset,
+      );
+      const sig = await program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: promoPlayer.publicKey,
+          rentPayer: promoPlayer.publicKey,
+          resolver: resolver.publicKey,
+          house: promoHouse.publicKey,
+          vault: promo.vault,
+          config: promo.config,
+          jackpot: promo.jackpot,
+          jackpots: promo.jackpots,
+          feeVault: promo.feeVault,
+          treasury: promo.treasury,
+          recentOutcomes: promo.recentOutcomes,
+          ledger: promo.ledger,
+          bet: betPda,
+          stats: promo.stats(promoPlayer.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc({ commitment: "confirmed" });
+
+      const txDetails = await connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(txDetails.meta.logMessages)];
+      const betResolved = events.find((e) => e.name === "betResolved");
+      assert.isTrue(betResolved.data.won, "The offset should turn it into a win");
+      assert.equal(betResolved.data.rollOffset, offset);
+      assert.equal(
+        betResolved.data.payout.toNumber(),
+        payoutFor(amount.toNumber(), roll, 100, feeBps),
+        "The payout should keep the odds the bet was placed at",
+      );
+    });
+  });
+
+  describe("Bet Terms", () => {
+    const termsHouse = Keypair.generate();
+    const termed = housePdas(termsHouse.publicKey);
+    const roll = 50;
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    const raisedFeeBps = 1_000;
+
+    const updateFee = (bps: number) =>
+      program.methods
+        .updateFee(bps)
+        .accountsStrict({
+          authority: termsHouse.publicKey,
+          house: termsHouse.publicKey,
+          config: termed.config,
+        })
+        .signers([termsHouse])
+        .rpc();
+
+    before(async () => {
+      await setupHouse(termsHouse);
+    });
+
+    it("Settles a bet at the fee it was placed under", async () => {
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        termsHouse.publicKey,
+        roll,
+        amount,
+        true,
+      );
+      assert.equal(bet.terms.feeBps, feeBps);
+      assert.equal(bet.terms.playerWinsTies, true);
+
+      await updateFee(raisedFeeBps);
+      const playerBefore = await connection.getBalance(player.publicKey);
+      await program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: termsHouse.publicKey,
+          vault: termed.vault,
+          config: termed.config,
+          jackpot: termed.jackpot,
+          jackpots: termed.jackpots,
+          feeVault: termed.feeVault,
+          treasury: termed.treasury,
+          recentOutcomes: termed.recentOutcomes,
+          ledger: termed.ledger,
+          bet: betPda,
+          stats: termed.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+      const playerAfter = await connection.getBalance(player.publicKey);
+      const rent = await connection.getMinimumBalanceForRentExemption(
+        program.account.bet.size,
+      );
+      assert.equal(
+        playerAfter - playerBefore,
+        payoutFor(amount.toNumber(), roll, 100, feeBps) + rent,
+        "The payout should use the fee in force at placement",
+      );
+    });
+
+    it("Prices bets placed after the change at the new fee", async () => {
+      const { bet, betPda } = await placeBetWithOutcome(
+        player,
+        termsHouse.publicKey,
+        roll,
+        amount,
+        true,
+      );
+      assert.equal(bet.terms.feeBps, raisedFeeBps);
+      assert.equal(
+        bet.potentialPayout.toNumber(),
+        payoutFor(amount.toNumber(), roll, 100, raisedFeeBps),
+      );
+      await discardBet(termsHouse.publicKey, betPda);
+    });
+  });
+
+  describe("Client Seed", () => {
+    const seedHouse = Keypair.generate();
+    const pdas = housePdas(seedHouse.publicKey);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const clientSeed = Array.from(
+      createHash("sha256").update("a phrase only the player knows").digest(),
+    );
+    let seedCounter = 1;
+
+    before(async () => {
+      await setupHouse(seedHouse);
+    });
+
+    const placeSeededBet = async () => {
+      const seed = new anchor.BN(seedCounter++);
+      const betPda = pdas.bet(seed, player.publicKey);
+      await program.methods
+        .placeBet(seed, betArgs(50, amount, { clientSeed }))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: seedHouse.publicKey,
+          vault: pdas.vault,
+          config: pdas.config,
+          stats: pdas.stats(player.publicKey),
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+      return {
+        bet: await program.account.bet.fetch(betPda),
+        betPda,
+      };
+    };
+
+    const signWith = (bet: any, seed: number[]) =>
+      Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage({ ...bet, clientSeed: seed }),
+      });
+
+    it("Gives the same bet a different roll input per client seed", async () => {
+      const { bet } = await placeSeededBet();
+      assert.deepEqual(bet.clientSeed, clientSeed);
+      assert.deepEqual(
+        betMessage(bet).subarray(-32),
+        Buffer.from(clientSeed),
+        "The client seed should be signed over",
+      );
+
+      const chosen = signWith(bet, clientSeed).data.subarray(48, 112);
+      const other = signWith(bet, Array(32).fill(7)).data.subarray(48, 112);
+      assert.notDeepEqual(
+        outcomeHash(chosen, bet.slotHash),
+        outcomeHash(other, bet.slotHash),
+        "Each client seed should lead to its own outcome",
+      );
+    });
+
+    it("Rejects a signature over a different client seed", async () => {
+      const { bet, betPda } = await placeSeededBet();
+      const ed25519Ix = signWith(bet, Array(32).fill(0));
+      try {
+        await program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            resolver: resolver.publicKey,
+            house: seedHouse.publicKey,
+            vault: pdas.vault,
+            config: pdas.config,
+            jackpot: pdas.jackpot,
+            jackpots: pdas.jackpots,
+            feeVault: pdas.feeVault,
+            treasury: pdas.treasury,
+            recentOutcomes: pdas.recentOutcomes,
+            ledger: pdas.ledger,
+            bet: betPda,
+            stats: pdas.stats(player.publicKey),
+            playerBalance: null,
+            pendingPayout: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+        assert.fail("Resolve should fail - client seed not signed over");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Ed25519Message");
+      }
+    });
+  });
+
+  describe("Large Win Escrow", () => {
+    const escrowPlayer = Keypair.generate();
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    const roll = 50;
+    const largeWinThreshold = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    before(async () => {
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(
+          escrowPlayer.publicKey,
+          2 * LAMPORTS_PER_SOL,
+        ),
+      );
+    });
+
+    const pendingPayoutPda = (houseKey: PublicKey, seed: anchor.BN) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("pending_payout"),
+          housePdas(houseKey).vault.toBuffer(),
+          escrowPlayer.publicKey.toBuffer(),
+          seed.toArrayLike(Buffer, "le", 16),
+        ],
+        program.programId,
+      )[0];
+
+    const resolveWin = async (houseKey: PublicKey, withPending = true) => {
+      const pdas = housePdas(houseKey);
+      const { bet, betPda, ed25519Ix } = await placeBetWithOutcome(
+        escrowPlayer,
+        houseKey,
+        roll,
+        amount,
+        true,
+      );
+      const pendingPayout = pendingPayoutPda(houseKey, bet.seed);
+      const resolve = (pending: PublicKey | null) =>
+        program.methods
+          .resolveBet(ed25519Ix.data.subarray(48, 112))
+          .accountsStrict({
+            player: escrowPlayer.publicKey,
+            rentPayer: escrowPlayer.publicKey,
+            resolver: resolver.publicKey,
+            house: houseKey,
+            vault: pdas.vault,
+            config: pdas.config,
+            jackpot: pdas.jackpot,
+            jackpots: pdas.jackpots,
+            feeVault: pdas.feeVault,
+            treasury: pdas.treasury,
+            recentOutcomes: pdas.recentOutcomes,
+            ledger: pdas.ledger,
+            bet: betPda,
+            stats: pdas.stats(escrowPlayer.publicKey),
+            playerBalance: null,
+            pendingPayout: pending,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            priceUpdate: null,
+            rewardMint: null,
+            playerRewardAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ed25519Ix])
+          .signers([resolver])
+          .rpc();
+
+      if (!withPending) {
+        try {
+          await resolve(null);
+          assert.fail("Resolve should fail - large win needs an escrow");
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal("PendingPayoutRequired");
+        }
+      }
+      await resolve(pendingPayout);
+      return { bet, pendingPayout };
+    };
+
+    describe("after the challenge window", () => {
+      const claimHouse = Keypair.generate();
+      const claim = housePdas(claimHouse.publicKey);
+
+      before(async () => {
+        await setupHouse(claimHouse, {
+          largeWinThreshold,
+          challengeSlots: new anchor.BN(0),
+        });
+      });
+
+      it("Escrows a large win and pays it on claim", async () => {
+        const { bet, pendingPayout } = await resolveWin(
+          claimHouse.publicKey,
+          false,
+        );
+        const expectedPayout = payoutFor(amount.toNumber(), roll, 100, feeBps);
+
+        const pending = await program.account.pendingPayout.fetch(
+          pendingPayout,
+        );
+        assert.equal(pending.amount.toNumber(), expectedPayout);
+        assert.equal(pending.seed.toString(), bet.seed.toString());
+        const configEscrowed = await program.account.houseConfig.fetch(
+          claim.config,
+        );
+        assert.equal(
+          configEscrowed.reservedLamports.toNumber(),
+          expectedPayout,
+          "The escrowed payout should stay reserved",
+        );
+
+        const vaultBefore = await connection.getBalance(claim.vault);
+        const resolverBefore = await connection.getBalance(resolver.publicKey);
+        const pendingRent = (await connection.getAccountInfo(pendingPayout))
+          .lamports;
+        assert.equal(
+          pending.rentPayer.toBase58(),
+          resolver.publicKey.toBase58(),
+          "The resolver paid for the escrow",
+        );
+        await program.methods
+          .claimPayout()
+          .accountsStrict({
+            player: escrowPlayer.publicKey,
+            house: claimHouse.publicKey,
+            vault: claim.vault,
+            config: claim.config,
+            pendingPayout,
+            rentPayer: resolver.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([escrowPlayer])
+          .rpc();
+
+        assert.equal(
+          vaultBefore - (await connection.getBalance(claim.vault)),
+          expectedPayout,
+          "Claiming should pay the escrowed amount out of the vault",
+        );
+        assert.equal(
+          (await connection.getBalance(resolver.publicKey)) - resolverBefore,
+          pendingRent,
+          "The escrow's rent should go back to the resolver that paid it",
+        );
+        assert.isNull(await connection.getAccountInfo(pendingPayout));
+        const configClaimed = await program.account.houseConfig.fetch(
+          claim.config,
+        );
+        assert.equal(configClaimed.reservedLamports.toNumber(), 0);
+      });
+    });
+
+    describe("during the challenge window", () => {
+      const voidHouse = Keypair.generate();
+      const voided = housePdas(voidHouse.publicKey);
+      let pendingPayout: PublicKey;
+
+      before(async () => {
+        await setupHouse(voidHouse, {
+          largeWinThreshold,
+          challengeSlots: new anchor.BN(1_000),
+        });
+        ({ pendingPayout } = await resolveWin(voidHouse.publicKey));
+      });
+
+      it("Rejects a claim before the window elapses", async () => {
+        try {
+          await program.methods
+            .claimPayout()
+            .accountsStrict({
+              player: escrowPlayer.publicKey,
+              house: voidHouse.publicKey,
+              vault: voided.vault,
+              config: voided.config,
+              pendingPayout,
+              rentPayer: resolver.publicKey,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([escrowPlayer])
+            .rpc();
+          assert.fail("Claim should fail - challenge window open");
+        } catch (err: any) {
+          expect(err.error.errorCode.code).to.equal("ChallengeWindowOpen");
+        }
+      });
+
+      it("Counts an escrowed payout when reconciling reserved", async () => {
+        const setPaused = (paused: boolean) =>
+          program.methods
+            .setPaused(paused)
+            .accountsStrict({
+              authority: voidHouse.publicKey,
+              house: voidHouse.publicKey,
+              config: voided.config,
+            })
+            .signers([voidHouse])
+            .rpc();
+        const reconcile = (reserved: anchor.BN | null, accounts: PublicKey[]) =>
+          program.methods
+            .reconcileReserved(reserved)
+            .accountsStrict({
+              authority: voidHouse.publicKey,
+              house: voidHouse.publicKey,
+              vault: voided.vault,
+              config: voided.config,
+            })
+            .remainingAccounts(
+              accounts.map((pubkey) => ({
+                pubkey,
+                isSigner: false,
+                isWritable: false,
+              })),
+            )
+            .signers([voidHouse])
+            .rpc();
+        const pending = await program.account.pendingPayout.fetch(
+          pendingPayout,
+        );
+
+        await setPaused(true);
+        // Simulates a drifted counter, then rebuilds it from the escrow alone.
+        await reconcile(new anchor.BN(0), []);
+        await reconcile(null, [pendingPayout]);
+        await setPaused(false);
+
+        const configAfter = await program.account.houseConfig.fetch(
+          voided.config,
+        );
+        assert.equal(
+          configAfter.reservedLamports.toString(),
+          pending.amount.toString(),
+          "The escrowed payout should count as reserved",
+        );
+      });
+
+      it("Lets the authority void the payout back to the house", async () => {
+        const vaultBefore = await connection.getBalance(voided.vault);
+
+        await program.methods
+          .voidPayout()
+          .accountsStrict({
+            authority: voidHouse.publicKey,
+            house: voidHouse.publicKey,
+            vault: voided.vault,
+            config: voided.config,
+            pendingPayout,
+            rentPayer: resolver.publicKey,
+          })
+          .signers([voidHouse])
+          .rpc();
+
+        assert.isNull(await connection.getAccountInfo(pendingPayout));
+        assert.equal(
+          await connection.getBalance(voided.vault),
+          vaultBefore,
+          "The voided funds should never leave the vault",
+        );
+        const configAccount = await program.account.houseConfig.fetch(
+          voided.config,
+        );
+        assert.equal(
+          configAccount.reservedLamports.toNumber(),
+          0,
+          "Voiding should release the reservation",
+        );
+      });
+    });
+  });
+
+  describe("Forced Roll", () => {
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    const instructionSysvar = new PublicKey(
+      "Sysvar1nstructions1111111111111111111111111",
+    );
+
+    before(function () {
+      // Only present when built with `--features test-randomness`.
+      if (
+        !program.idl.instructions.some(
+          (ix) => ix.name === "resolve_bet_with_roll",
+        )
+      ) {
+        this.skip();
+      }
+    });
+
+    const placeAndForce = async (
+      seed: anchor.BN,
+      forcedRoll: number,
+      args = betArgs(50, amount),
+      houseKey = house.publicKey,
+    ) => {
+      const pdas = housePdas(houseKey);
+      const betPda = pdas.bet(seed);
+      await program.methods
+        .placeBet(seed, args)
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: houseKey,
+          vault: pdas.vault,
+          config: pdas.config,
+          stats: pdas.stats(player.publicKey),
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+      const rent = (await connection.getAccountInfo(betPda)).lamports;
+      const playerBalanceBefore = await connection.getBalance(player.publicKey);
+
+      await (program.methods as any)
+        .resolveBetWithRoll(forcedRoll)
+        .accountsStrict({
+          resolver: resolver.publicKey,
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: houseKey,
+          vault: pdas.vault,
+          config: pdas.config,
+          jackpot: pdas.jackpot,
+          jackpots: pdas.jackpots,
+          feeVault: pdas.feeVault,
+          treasury: pdas.treasury,
+          recentOutcomes: pdas.recentOutcomes,
+          ledger: pdas.ledger,
+          bet: betPda,
+          stats: pdas.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: instructionSysvar,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([resolver])
+        .rpc();
+
+      const playerBalanceAfter = await connection.getBalance(player.publicKey);
+      return playerBalanceAfter - playerBalanceBefore - rent;
+    };
+
+    it("Pays nothing when the forced roll loses", async () => {
+      const winnings = await placeAndForce(new anchor.BN(8_001), 99);
+      assert.equal(winnings, 0);
+    });
+
+    it("Pays out when the forced roll wins", async () => {
+      const winnings = await placeAndForce(new anchor.BN(8_000), 2);
+      assert.equal(winnings, payoutFor(amount.toNumber(), 50, 100, feeBps));
+    });
+
+    describe("on a tie", () => {
+      const tieHouse = Keypair.generate();
+
+      before(async () => {
+        await setupHouse(tieHouse, { playerWinsTies: false });
+      });
+
+      it("Pays the player when player_wins_ties is set", async () => {
+        const winnings = await placeAndForce(new anchor.BN(8_010), 50);
+        assert.isAbove(winnings, 0, "A roll equal to the threshold should win");
+      });
+
+      it("Keeps the stake when the house wins ties", async () => {
+        const tie = await placeAndForce(
+          new anchor.BN(8_011),
+          50,
+          betArgs(50, amount),
+          tieHouse.publicKey,
+        );
+        assert.equal(tie, 0, "A roll equal to the threshold should lose");
+
+        const below = await placeAndForce(
+          new anchor.BN(8_012),
+          49,
+          betArgs(50, amount),
+          tieHouse.publicKey,
+        );
+        assert.equal(below, payoutFor(amount.toNumber(), 50, 100, feeBps));
+      });
+    });
+
+    describe("with a guaranteed jackpot", () => {
+      const floorHouse = Keypair.generate();
+      const floorPdas = housePdas(floorHouse.publicKey);
+      const minJackpot = new anchor.BN(0.2 * LAMPORTS_PER_SOL);
+      const seeded = new anchor.BN(0.5 * LAMPORTS_PER_SOL);
+
+      before(async () => {
+        await setupHouse(floorHouse, { minJackpot });
+      });
+
+      it("Seeds the jackpot from the authority", async () => {
+        const vaultBefore = await connection.getBalance(floorPdas.vault);
+        await program.methods
+          .seedJackpot(seeded)
+          .accountsStrict({
+            authority: floorHouse.publicKey,
+            house: floorHouse.publicKey,
+            vault: floorPdas.vault,
+            config: floorPdas.config,
+            jackpot: floorPdas.jackpot,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([floorHouse])
+          .rpc();
+        const jackpot = await program.account.jackpot.fetch(floorPdas.jackpot);
+        assert.equal(jackpot.balance.toNumber(), seeded.toNumber());
+        assert.equal(
+          (await connection.getBalance(floorPdas.vault)) - vaultBefore,
+          seeded.toNumber(),
+        );
+      });
+
+      it("Resets to the guaranteed floor after a jackpot win", async () => {
+        // The winning bet's own contribution is added before the jackpot pays.
+        const contribution = Math.floor(
+          (Math.floor((amount.toNumber() * feeBps) / 10_000) *
+            jackpotContributionBps) /
+            10_000,
+        );
+        const winnings = await placeAndForce(
+          new anchor.BN(8_030),
+          1,
+          betArgs(50, amount),
+          floorHouse.publicKey,
+        );
+        assert.equal(
+          winnings,
+          payoutFor(amount.toNumber(), 50, 100, feeBps) +
+            seeded.toNumber() +
+            contribution,
+        );
+        const jackpot = await program.account.jackpot.fetch(floorPdas.jackpot);
+        assert.equal(jackpot.balance.toNumber(), minJackpot.toNumber());
+      });
+    });
+
+    describe("with jackpot tiers", () => {
+      const tierHouse = Keypair.generate();
+      const tierBps = 1_000;
+
+      before(async () => {
+        await setupHouse(tierHouse);
+        await setJackpotTiers(tierHouse, tierHouse.publicKey, [
+          [30, tierBps],
+          [10, tierBps],
+          [3, tierBps],
+        ]);
+      });
+
+      it("Pays only the rarest tier the roll triggers", async () => {
+        // 5 matches the mini and the major but not the grand.
+        const winnings = await placeAndForce(
+          new anchor.BN(8_030),
+          5,
+          betArgs(50, amount),
+          tierHouse.publicKey,
+        );
+        const share =
+          (((amount.toNumber() * feeBps) / 10_000) * tierBps) / 10_000;
+        assert.equal(
+          winnings,
+          payoutFor(amount.toNumber(), 50, 100, feeBps) + share,
+        );
+
+        const account = await program.account.jackpots.fetch(
+          housePdas(tierHouse.publicKey).jackpots,
+        );
+        assert.deepEqual(
+          account.tiers.map((tier) => tier.balance.toNumber()),
+          [share, 0, share],
+        );
+      });
+    });
+
+    describe("on a near miss", () => {
+      const nearMissHouse = Keypair.generate();
+      const nearMissBps = 500;
+
+      before(async () => {
+        await setupHouse(nearMissHouse, { nearMissWindow: 3, nearMissBps });
+      });
+
+      it("Returns a consolation when the roll lands within the window", async () => {
+        const near = await placeAndForce(
+          new anchor.BN(8_020),
+          53,
+          betArgs(50, amount),
+          nearMissHouse.publicKey,
+        );
+        assert.equal(near, (amount.toNumber() * nearMissBps) / 10_000);
+      });
+
+      it("Pays nothing when the roll misses by more than the window", async () => {
+        const far = await placeAndForce(
+          new anchor.BN(8_021),
+          54,
+          betArgs(50, amount),
+          nearMissHouse.publicKey,
+        );
+        assert.equal(far, 0);
+      });
+    });
+
+    it("Builds a streak bonus on consecutive wins and resets on a loss", async () => {
+      const payout = payoutFor(amount.toNumber(), 50, 100, feeBps);
+      const winnings = await placeAndForce(new anchor.BN(8_004), 2);
+      assert.equal(
+        winnings,
+        payout + Math.floor((payout * streakBonusBps) / 10_000),
+        "Second consecutive win should earn one step of streak bonus",
+      );
+      let statsAccount = await program.account.playerStats.fetch(stats);
+      assert.equal(statsAccount.currentStreak, 2);
+
+      await placeAndForce(new anchor.BN(8_005), 99);
+      statsAccount = await program.account.playerStats.fetch(stats);
+      assert.equal(statsAccount.currentStreak, 0, "A loss resets the streak");
+    });
+
+    it("Pays an exact bet only on a matching roll", async () => {
+      const exact = betArgs(42, minBet, { direction: { exact: {} } });
+      const hit = await placeAndForce(new anchor.BN(8_002), 42, exact);
+      assert.equal(hit, payoutFor(minBet.toNumber(), 1, 100, feeBps));
+
+      const miss = await placeAndForce(new anchor.BN(8_003), 41, exact);
+      assert.equal(miss, 0);
+    });
+  });
+
+  describe("Bundled Signatures", () => {
+    const seed = new anchor.BN(9_000);
+
+    before(async () => {
+      await program.methods
+        .placeBet(seed, betArgs(50, minBet))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: findBetPda(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("Resolves a bet whose signature shares an ed25519 instruction", async () => {
+      const betAccount = await program.account.bet.fetch(findBetPda(seed));
+      await revealedSlotHash(betAccount, config);
+      const unrelatedIx = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: Keypair.generate().secretKey,
+        message: Buffer.from("unrelated"),
+      });
+      const playerIx = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: player.secretKey,
+        message: betMessage(betAccount),
+      });
+
+      await program.methods
+        .resolveBet(playerIx.data.subarray(48, 112))
+        .accountsStrict({
+          resolver: resolver.publicKey,
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          jackpot,
+          jackpots,
+          feeVault,
+          treasury,
+          recentOutcomes,
+          ledger,
+          bet: findBetPda(seed),
+          stats,
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: new PublicKey(
+            "Sysvar1nstructions1111111111111111111111111",
+          ),
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([bundleEd25519([unrelatedIx, playerIx])])
+        .signers([resolver])
+        .rpc();
+
+      const closedBet = await connection.getAccountInfo(findBetPda(seed));
+      expect(closedBet?.lamports ?? 0).to.equal(0, "Bet should be resolved");
+    });
+  });
+
+  describe("Reveal And Resolve", () => {
+    const seed = new anchor.BN(101);
+    const roll = 50;
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    const reveal = Buffer.alloc(32, 7);
+    const houseSeed = Buffer.alloc(32, 9);
+    const instructionSysvar = new PublicKey(
+      "Sysvar1nstructions1111111111111111111111111",
+    );
+    let betPda: PublicKey;
+
+    const revealAndResolve = (secret: Buffer) =>
+      program.methods
+        .revealAndResolve([...secret], [...houseSeed])
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          jackpot,
+          jackpots,
+          feeVault,
+          treasury,
+          recentOutcomes,
+          ledger,
+          bet: betPda,
+          stats,
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: instructionSysvar,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([resolver])
+        .rpc();
+
+    before(async () => {
+      betPda = findBetPda(seed);
+      const playerCommit = [...createHash("sha256").update(reveal).digest()];
+
+      await program.methods
+        .placeBet(seed, betArgs(roll, amount, { playerCommit }))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("Rejects a reveal that does not match the commitment", async () => {
+      try {
+        await revealAndResolve(Buffer.alloc(32, 8));
+        assert.fail("Reveal should fail - commitment mismatch");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("CommitMismatch");
+      }
+    });
+
+    it("Resolves the bet from the revealed secret", async () => {
+      await revealedSlotHash(await program.account.bet.fetch(betPda), config);
+      await revealAndResolve(reveal);
+
+      const closedBetAccount = await connection.getAccountInfo(betPda);
+      assert.isNull(closedBetAccount, "Bet account should be closed");
+    });
+  });
+
+  describe("Cashout", () => {
+    const seed = new anchor.BN(111);
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    const instructionSysvar = new PublicKey(
+      "Sysvar1nstructions1111111111111111111111111",
+    );
+    const betPda = findBetPda(seed);
+
+    const offer = (bet: any, offerAmount: anchor.BN, signer = resolver) =>
+      Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: signer.secretKey,
+        message: Buffer.concat([
+          betMessage(bet),
+          offerAmount.toArrayLike(Buffer, "le", 8),
+        ]),
+      });
+
+    const cashout = (
+      pda: PublicKey,
+      cashoutAmount: anchor.BN,
+      ed25519Ix: TransactionInstruction,
+    ) =>
+      program.methods
+        .cashout(cashoutAmount, ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          bet: pda,
+          stats,
+          playerBalance: null,
+          instructions: instructionSysvar,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([player])
+        .rpc();
+
+    before(async () => {
+      const playerCommit = [
+        ...createHash("sha256").update(Buffer.alloc(32, 3)).digest(),
+      ];
+      await program.methods
+        .placeBet(seed, betArgs(50, amount, { playerCommit }))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("Rejects an offer not signed by the resolver", async () => {
+      const bet = await program.account.bet.fetch(betPda);
+      const offerAmount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+      try {
+        await cashout(betPda, offerAmount, offer(bet, offerAmount, player));
+        assert.fail("Cashout should fail - offer not from the resolver");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("Ed25519Pubkey");
+      }
+    });
+
+    it("Rejects an amount other than the one offered", async () => {
+      const bet = await program.account.bet.fetch(betPda);
+      const ed25519Ix = offer(bet, new anchor.BN(0.05 * LAMPORTS_PER_SOL));
+      try {
+        await cashout(betPda, new anchor.BN(0.06 * LAMPORTS_PER_SOL), ed25519Ix);
+        assert.fail("Cashout should fail - amount tampered with");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("CashoutOfferMismatch");
+      }
+    });
+
+    it("Rejects an offer above the potential payout", async () => {
+      const bet = await program.account.bet.fetch(betPda);
+      const offerAmount = bet.potentialPayout.addn(1);
+      try {
+        await cashout(betPda, offerAmount, offer(bet, offerAmount));
+        assert.fail("Cashout should fail - offer exceeds payout");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("CashoutExceedsPayout");
+      }
+    });
+
+    it("Rejects cashing out a signature-resolved bet", async () => {
+      const signatureSeed = new anchor.BN(112);
+      const signaturePda = findBetPda(signatureSeed);
+      await program.methods
+        .placeBet(signatureSeed, betArgs(50, amount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: signaturePda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+      const bet = await program.account.bet.fetch(signaturePda);
+      const offerAmount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+      try {
+        await cashout(signaturePda, offerAmount, offer(bet, offerAmount));
+        assert.fail("Cashout should fail - player knows the outcome");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RandomnessSourceMismatch");
+      }
+      await cancelBetFor(player, house.publicKey, signaturePda);
+    });
+
+    it("Pays the agreed amount and closes the bet", async () => {
+      const bet = await program.account.bet.fetch(betPda);
+      const offerAmount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+      const vaultBefore = await connection.getBalance(vault);
+      const configBefore = await program.account.houseConfig.fetch(config);
+
+      await cashout(betPda, offerAmount, offer(bet, offerAmount));
+
+      assert.equal(
+        vaultBefore - (await connection.getBalance(vault)),
+        offerAmount.toNumber(),
+        "Vault should pay exactly the offered amount",
+      );
+      const configAfter = await program.account.houseConfig.fetch(config);
+      assert.equal(
+        configBefore.reservedLamports.sub(configAfter.reservedLamports).toString(),
+        bet.potentialPayout.toString(),
+        "Cashout should release the bet's reservation",
+      );
+      assert.isNull(
+        await connection.getAccountInfo(betPda),
+        "Bet account should be closed",
+      );
+    });
+  });
+
+  describe("Referral", () => {
+    const seed = new anchor.BN(102);
+    const roll = 50;
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    const referrer = Keypair.generate();
+    const instructionSysvar = new PublicKey(
+      "Sysvar1nstructions1111111111111111111111111",
+    );
+    let betPda: PublicKey;
+
+    const resolveWithReferrer = async (remainingAccounts: any[]) => {
+      const betAccount = await program.account.bet.fetch(betPda);
+      await revealedSlotHash(betAccount, config);
       const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
         privateKey: player.secretKey,
-        message: betAccountInfo.data.subarray(8),
+        message: betMessage(betAccount),
+      });
+
+      return program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          jackpot,
+          jackpots,
+          feeVault,
+          treasury,
+          recentOutcomes,
+          ledger,
+          bet: betPda,
+          stats,
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: instructionSysvar,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+    };
+
+    before(async () => {
+      betPda = findBetPda(seed);
+
+      const rent = await connection.getMinimumBalanceForRentExemption(0);
+      await sendAndConfirmTransaction(
+        connection,
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: house.publicKey,
+            toPubkey: referrer.publicKey,
+            lamports: rent,
+          }),
+        ),
+        [house],
+      );
+
+      await program.methods
+        .placeBet(
+          seed,
+          betArgs(roll, amount, { referrer: referrer.publicKey }),
+        )
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("Rejects resolution without the referrer account", async () => {
+      try {
+        await resolveWithReferrer([]);
+        assert.fail("Resolve should fail - referrer account missing");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ReferrerAccountMissing");
+      }
+    });
+
+    it("Pays the referrer a cut of the house fee", async () => {
+      const referrerBalanceBefore = await connection.getBalance(
+        referrer.publicKey,
+      );
+
+      await resolveWithReferrer([
+        { pubkey: referrer.publicKey, isSigner: false, isWritable: true },
+      ]);
+
+      const referrerBalanceAfter = await connection.getBalance(
+        referrer.publicKey,
+      );
+      const houseFee = Math.floor((amount.toNumber() * feeBps) / 10_000);
+      assert.equal(
+        referrerBalanceAfter - referrerBalanceBefore,
+        Math.floor((houseFee * referralBps) / 10_000),
+        "Referrer should receive their share of the house fee",
+      );
+    });
+  });
+
+  describe("Charity", () => {
+    const charityHouse = Keypair.generate();
+    const giving = housePdas(charityHouse.publicKey);
+    const charity = Keypair.generate();
+    const charityBps = 1_000;
+    const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+
+    const resolveWithCharity = (
+      betPda: PublicKey,
+      ed25519Ix: TransactionInstruction,
+      remainingAccounts: any[],
+    ) =>
+      program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: charityHouse.publicKey,
+          vault: giving.vault,
+          config: giving.config,
+          jackpot: giving.jackpot,
+          jackpots: giving.jackpots,
+          feeVault: giving.feeVault,
+          treasury: giving.treasury,
+          recentOutcomes: giving.recentOutcomes,
+          ledger: giving.ledger,
+          bet: betPda,
+          stats: giving.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+
+    const charityAccount = () => [
+      { pubkey: charity.publicKey, isSigner: false, isWritable: true },
+    ];
+
+    before(async () => {
+      await setupHouse(charityHouse, {
+        charity: charity.publicKey,
+        charityBps,
+      });
+      const rent = await connection.getMinimumBalanceForRentExemption(0);
+      await sendAndConfirmTransaction(
+        connection,
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: charityHouse.publicKey,
+            toPubkey: charity.publicKey,
+            lamports: rent,
+          }),
+        ),
+        [charityHouse],
+      );
+    });
+
+    it("Rejects a losing resolve without the charity account", async () => {
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        charityHouse.publicKey,
+        50,
+        amount,
+        false,
+      );
+      try {
+        await resolveWithCharity(betPda, ed25519Ix, []);
+        assert.fail("Resolve should fail - charity account missing");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("CharityAccountMissing");
+      }
+      await discardBet(charityHouse.publicKey, betPda);
+    });
+
+    it("Gives the charity its share of a lost stake", async () => {
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        charityHouse.publicKey,
+        50,
+        amount,
+        false,
+      );
+      const before = await connection.getBalance(charity.publicKey);
+      await resolveWithCharity(betPda, ed25519Ix, charityAccount());
+      const after = await connection.getBalance(charity.publicKey);
+      assert.equal(
+        after - before,
+        Math.floor((amount.toNumber() * charityBps) / 10_000),
+        "Charity should receive charity_bps of the stake",
+      );
+    });
+
+    it("Gives the charity nothing when the player wins", async () => {
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        charityHouse.publicKey,
+        50,
+        amount,
+        true,
+      );
+      const before = await connection.getBalance(charity.publicKey);
+      await resolveWithCharity(betPda, ed25519Ix, []);
+      const after = await connection.getBalance(charity.publicKey);
+      assert.equal(after, before, "A house loss should not fund the charity");
+    });
+  });
+
+  describe("Resolve Bets Batch", () => {
+    const seeds = [new anchor.BN(4_000), new anchor.BN(4_001)];
+    const instructionSysvar = new PublicKey(
+      "Sysvar1nstructions1111111111111111111111111",
+    );
+
+    before(async () => {
+      for (const seed of seeds) {
+        await program.methods
+          .placeBet(seed, betArgs(50, minBet))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: findBetPda(seed),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+      }
+    });
+
+    it("Rejects a batch above the size cap", async () => {
+      try {
+        await program.methods
+          .resolveBetsBatch(Array(5).fill(Buffer.alloc(64)))
+          .accountsStrict({
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            instructions: instructionSysvar,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([resolver])
+          .rpc();
+        assert.fail("Batch should fail - too many bets");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("BatchTooLarge");
+      }
+    });
+
+    it("Resolves several bets in one transaction", async () => {
+      const ed25519Ixs = [];
+      for (const seed of seeds) {
+        const betAccount = await program.account.bet.fetch(findBetPda(seed));
+        await revealedSlotHash(betAccount, config);
+        ed25519Ixs.push(
+          Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: player.secretKey,
+            message: betMessage(betAccount),
+          }),
+        );
+      }
+
+      const resolveIx = await program.methods
+        .resolveBetsBatch(ed25519Ixs.map((ix) => ix.data.subarray(48, 112)))
+        .accountsStrict({
+          resolver: resolver.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          jackpot,
+          jackpots,
+          feeVault,
+          treasury,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: instructionSysvar,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          seeds.flatMap((seed) => [
+            { pubkey: findBetPda(seed), isSigner: false, isWritable: true },
+            { pubkey: player.publicKey, isSigner: false, isWritable: true },
+            { pubkey: stats, isSigner: false, isWritable: true },
+            { pubkey: player.publicKey, isSigner: false, isWritable: true },
+          ]),
+        )
+        .instruction();
+
+      // A leading compute-budget instruction must not hide the signatures.
+      const tx = new Transaction()
+        .add(ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }))
+        .add(...ed25519Ixs)
+        .add(resolveIx);
+      await sendAndConfirmTransaction(connection, tx, [resolver]);
+
+      for (const seed of seeds) {
+        const closedBet = await connection.getAccountInfo(findBetPda(seed));
+        expect(closedBet?.lamports ?? 0).to.equal(
+          0,
+          "Each bet should be closed after the batch",
+        );
+      }
+    });
+  });
+
+  describe("CPI Resolution", () => {
+    const cpiHouse = Keypair.generate();
+    const cpi = housePdas(cpiHouse.publicKey);
+    const cpiResolver = Keypair.generate();
+    const cpiPlayer = Keypair.generate();
+    const seed = new anchor.BN(9_301);
+    const randomness = Buffer.alloc(32, 42);
+
+    const setCpiResolver = (signer: Keypair, key: PublicKey | null) =>
+      program.methods
+        .setCpiResolver(key)
+        .accountsStrict({
+          authority: signer.publicKey,
+          house: cpiHouse.publicKey,
+          config: cpi.config,
+        })
+        .signers([signer])
+        .rpc();
+
+    const resolveCpi = () =>
+      program.methods
+        .resolveBetCpi([...randomness])
+        .accountsStrict({
+          cpiResolver: cpiResolver.publicKey,
+          player: cpiPlayer.publicKey,
+          rentPayer: cpiPlayer.publicKey,
+          house: cpiHouse.publicKey,
+          vault: cpi.vault,
+          config: cpi.config,
+          jackpot: cpi.jackpot,
+          jackpots: cpi.jackpots,
+          feeVault: cpi.feeVault,
+          treasury: cpi.treasury,
+          bet: cpi.bet(seed, cpiPlayer.publicKey),
+          stats: cpi.stats(cpiPlayer.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cpiResolver, cpiPlayer])
+        .rpc({ commitment: "confirmed" });
+
+    before(async () => {
+      await setupHouse(cpiHouse);
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(cpiPlayer.publicKey, 2 * LAMPORTS_PER_SOL),
+      );
+      await program.methods
+        .placeBet(seed, betArgs(50, new anchor.BN(0.05 * LAMPORTS_PER_SOL)))
+        .accountsStrict({
+          player: cpiPlayer.publicKey,
+          rentPayer: cpiPlayer.publicKey,
+          house: cpiHouse.publicKey,
+          vault: cpi.vault,
+          config: cpi.config,
+          stats: cpi.stats(cpiPlayer.publicKey),
+          bet: cpi.bet(seed, cpiPlayer.publicKey),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(cpiPlayer.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cpiPlayer])
+        .rpc();
+    });
+
+    it("Rejects CPI resolution until the house enables it", async () => {
+      try {
+        await resolveCpi();
+        assert.fail("Resolve should fail - no CPI resolver configured");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("UnauthorizedCpiResolver");
+      }
+    });
+
+    it("Rejects setting the CPI resolver as a non-authority", async () => {
+      try {
+        await setCpiResolver(player, cpiResolver.publicKey);
+        assert.fail("Update should fail - wrong authority");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("Resolves from the caller's randomness once enabled", async () => {
+      await setCpiResolver(cpiHouse, cpiResolver.publicKey);
+      await revealedSlotHash(
+        await program.account.bet.fetch(cpi.bet(seed, cpiPlayer.publicKey)),
+        cpi.config,
+      );
+      const sig = await resolveCpi();
+
+      assert.isNull(
+        await connection.getAccountInfo(cpi.bet(seed, cpiPlayer.publicKey)),
+        "Bet account should be closed",
+      );
+      const txDetails = await connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
+      );
+      const events = [...eventParser.parseLogs(txDetails.meta.logMessages)];
+      const proof = events.find((e) => e.name === "outcomeProof");
+      assert.deepEqual(
+        Buffer.from(proof.data.inputHash),
+        outcomeHash(randomness, proof.data.slotHash),
+      );
+      assert.equal(
+        proof.data.roll,
+        computeRoll(randomness, proof.data.slotHash),
+      );
+    });
+  });
+
+  describe("Outcome Caching", () => {
+    const cacheHouse = Keypair.generate();
+    const cached = housePdas(cacheHouse.publicKey);
+    const cpiResolver = Keypair.generate();
+    const cachePlayer = Keypair.generate();
+    const seed = new anchor.BN(1);
+    const betPda = cached.bet(seed, cachePlayer.publicKey);
+    const randomness = Buffer.alloc(32, 7);
+
+    const resolveCpi = (preInstructions: TransactionInstruction[] = []) =>
+      program.methods
+        .resolveBetCpi([...randomness])
+        .accountsStrict({
+          cpiResolver: cpiResolver.publicKey,
+          player: cachePlayer.publicKey,
+          rentPayer: cachePlayer.publicKey,
+          house: cacheHouse.publicKey,
+          vault: cached.vault,
+          config: cached.config,
+          jackpot: cached.jackpot,
+          jackpots: cached.jackpots,
+          feeVault: cached.feeVault,
+          treasury: cached.treasury,
+          bet: betPda,
+          stats: cached.stats(cachePlayer.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions(preInstructions)
+        .signers([cpiResolver, cachePlayer])
+        .rpc();
+
+    before(async () => {
+      await setupHouse(cacheHouse, { cacheOutcomes: true });
+      await program.methods
+        .setCpiResolver(cpiResolver.publicKey)
+        .accountsStrict({
+          authority: cacheHouse.publicKey,
+          house: cacheHouse.publicKey,
+          config: cached.config,
+        })
+        .signers([cacheHouse])
+        .rpc();
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(
+          cachePlayer.publicKey,
+          2 * LAMPORTS_PER_SOL,
+        ),
+      );
+      await program.methods
+        .placeBet(seed, betArgs(50, new anchor.BN(0.05 * LAMPORTS_PER_SOL)))
+        .accountsStrict({
+          player: cachePlayer.publicKey,
+          rentPayer: cachePlayer.publicKey,
+          house: cacheHouse.publicKey,
+          vault: cached.vault,
+          config: cached.config,
+          stats: cached.stats(cachePlayer.publicKey),
+          bet: betPda,
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(cachePlayer.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cachePlayer])
+        .rpc();
+    });
+
+    it("Settles a retried CPI resolution only once", async () => {
+      await revealedSlotHash(
+        await program.account.bet.fetch(betPda),
+        cached.config,
+      );
+      await resolveCpi();
+      const bet = await program.account.bet.fetch(betPda);
+      assert.isNotNull(bet.cachedOutcome, "The outcome should be cached");
+      const { roll, won, payout } = bet.cachedOutcome;
+      const playerAfterFirst = await connection.getBalance(
+        cachePlayer.publicKey,
+      );
+      const vaultAfterFirst = await connection.getBalance(cached.vault);
+      const configAfterFirst = await program.account.houseConfig.fetch(
+        cached.config,
+      );
+
+      // A distinct transaction carrying the same resolution, as a retrying
+      // caller would send.
+      await resolveCpi([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 }),
+      ]);
+
+      assert.equal(
+        await connection.getBalance(cachePlayer.publicKey),
+        playerAfterFirst,
+        "The retry should pay nothing",
+      );
+      assert.equal(await connection.getBalance(cached.vault), vaultAfterFirst);
+      const configAfter = await program.account.houseConfig.fetch(
+        cached.config,
+      );
+      assert.equal(
+        configAfter.reservedLamports.toString(),
+        configAfterFirst.reservedLamports.toString(),
+      );
+      const betAfter = await program.account.bet.fetch(betPda);
+      assert.equal(betAfter.cachedOutcome.roll, roll);
+      assert.equal(betAfter.cachedOutcome.won, won);
+      assert.ok(betAfter.cachedOutcome.payout.eq(payout));
+    });
+
+    it("Rejects cancelling a settled bet", async () => {
+      try {
+        await cancelBetFor(cachePlayer, cacheHouse.publicKey, betPda);
+        assert.fail("Cancel should fail - bet already settled");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("BetAlreadySettled");
+      }
+    });
+
+    it("Returns the rent when the settled bet is closed", async () => {
+      const playerBefore = await connection.getBalance(cachePlayer.publicKey);
+      const rent = await connection.getMinimumBalanceForRentExemption(
+        program.account.bet.size,
+      );
+      await program.methods
+        .closeSettledBet()
+        .accountsStrict({
+          closer: cachePlayer.publicKey,
+          house: cacheHouse.publicKey,
+          vault: cached.vault,
+          config: cached.config,
+          bet: betPda,
+          rentPayer: cachePlayer.publicKey,
+        })
+        .signers([cachePlayer])
+        .rpc();
+
+      assert.isNull(await connection.getAccountInfo(betPda));
+      assert.equal(
+        await connection.getBalance(cachePlayer.publicKey),
+        playerBefore + rent,
+      );
+    });
+  });
+
+  describe("Switchboard Randomness", () => {
+    const seed = new anchor.BN(5_000);
+
+    before(async () => {
+      await program.methods
+        .placeBet(seed, betArgs(50, minBet))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: findBetPda(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("Rejects consuming randomness for a signature bet", async () => {
+      const betAccount = await program.account.bet.fetch(findBetPda(seed));
+      assert.ok(betAccount.randomnessSource.signature);
+
+      try {
+        await program.methods
+          .consumeRandomness()
+          .accountsStrict({
+            resolver: resolver.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            bet: findBetPda(seed),
+            stats,
+            playerBalance: null,
+            pendingPayout: null,
+            randomnessAccountData: Keypair.generate().publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([resolver])
+          .rpc();
+        assert.fail("Consume should fail - bet uses signatures");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RandomnessSourceMismatch");
+      }
+    });
+  });
+
+  describe("Payout Cap", () => {
+    const placeMaxBet = (seed: anchor.BN, roll: number) =>
+      program.methods
+        .placeBet(seed, betArgs(roll, maxBet))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: findBetPda(seed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    it("Rejects a bet whose potential payout exceeds the cap", async () => {
+      try {
+        await placeMaxBet(new anchor.BN(6_000), 49);
+        assert.fail("Place bet should fail - payout above cap");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "PotentialPayoutExceedsCap",
+        );
+      }
+    });
+
+    it("Accepts a bet whose potential payout equals the cap", async () => {
+      const seed = new anchor.BN(6_001);
+      await placeMaxBet(seed, 50);
+
+      const betAccount = await program.account.bet.fetch(findBetPda(seed));
+      assert.equal(
+        betAccount.potentialPayout.toString(),
+        maxPayout.toString(),
+        "Potential payout should sit exactly on the cap",
+      );
+    });
+  });
+
+  describe("Jackpot Tiers", () => {
+    const tierHouse = Keypair.generate();
+
+    before(async () => {
+      await setupHouse(tierHouse);
+    });
+
+    it("Starts every tier disabled and empty", async () => {
+      const account = await program.account.jackpots.fetch(
+        housePdas(tierHouse.publicKey).jackpots,
+      );
+      for (const tier of account.tiers) {
+        assert.equal(tier.balance.toNumber(), 0);
+        assert.equal(tier.triggerRoll, 0);
+      }
+    });
+
+    it("Rejects tiers that don't get rarer", async () => {
+      try {
+        await setJackpotTiers(tierHouse, tierHouse.publicKey, [
+          [10, 100],
+          [30, 100],
+          [3, 100],
+        ]);
+        assert.fail("Update should fail - major triggers more often than mini");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidJackpotTiers");
+      }
+    });
+
+    it("Rejects contributions above the house fee", async () => {
+      try {
+        await setJackpotTiers(tierHouse, tierHouse.publicKey, [
+          [30, 5_000],
+          [10, 4_000],
+          [3, 1_000],
+        ]);
+        assert.fail("Update should fail - tiers and jackpot exceed the fee");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("FeeTooHigh");
+      }
+    });
+
+    it("Rejects an update from a non-authority", async () => {
+      try {
+        await setJackpotTiers(player, tierHouse.publicKey, [
+          [30, 100],
+          [10, 100],
+          [3, 100],
+        ]);
+        assert.fail("Update should fail - wrong authority");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+
+    it("Stores the triggers and contributions", async () => {
+      await setJackpotTiers(tierHouse, tierHouse.publicKey, [
+        [30, 100],
+        [0, 0],
+        [3, 200],
+      ]);
+      const account = await program.account.jackpots.fetch(
+        housePdas(tierHouse.publicKey).jackpots,
+      );
+      assert.deepEqual(
+        account.tiers.map((tier) => [tier.triggerRoll, tier.contributionBps]),
+        [
+          [30, 100],
+          [0, 0],
+          [3, 200],
+        ],
+      );
+    });
+  });
+
+  describe("Treasury", () => {
+    const insuredHouse = Keypair.generate();
+    const insured = housePdas(insuredHouse.publicKey);
+    const insuredPlayer = Keypair.generate();
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    const insuranceBps = 1_000;
+
+    const rebalance = (houseKp: Keypair, pdas = insured) =>
+      program.methods
+        .rebalanceFromTreasury()
+        .accountsStrict({
+          authority: houseKp.publicKey,
+          house: houseKp.publicKey,
+          vault: pdas.vault,
+          config: pdas.config,
+          treasury: pdas.treasury,
+        })
+        .signers([houseKp])
+        .rpc();
+
+    before(async () => {
+      await setupHouse(insuredHouse, {
+        insuranceBps,
+        rebalanceThreshold: new anchor.BN(3 * LAMPORTS_PER_SOL),
+      });
+      await connection.confirmTransaction(
+        await connection.requestAirdrop(
+          insuredPlayer.publicKey,
+          2 * LAMPORTS_PER_SOL,
+        ),
+      );
+    });
+
+    it("Moves a slice of a lost stake to the treasury", async () => {
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        insuredPlayer,
+        insuredHouse.publicKey,
+        50,
+        amount,
+        false,
+      );
+      const treasuryBefore = await connection.getBalance(insured.treasury);
+
+      await program.methods
+        .resolveBet(ed25519Ix.data.subarray(48, 112))
+        .accountsStrict({
+          player: insuredPlayer.publicKey,
+          rentPayer: insuredPlayer.publicKey,
+          resolver: resolver.publicKey,
+          house: insuredHouse.publicKey,
+          vault: insured.vault,
+          config: insured.config,
+          jackpot: insured.jackpot,
+          jackpots: insured.jackpots,
+          feeVault: insured.feeVault,
+          treasury: insured.treasury,
+          recentOutcomes: insured.recentOutcomes,
+          ledger: insured.ledger,
+          bet: betPda,
+          stats: insured.stats(insuredPlayer.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+
+      assert.equal(
+        (await connection.getBalance(insured.treasury)) - treasuryBefore,
+        (amount.toNumber() * insuranceBps) / 10_000,
+      );
+    });
+
+    it("Tops the vault up from the treasury below the threshold", async () => {
+      const treasuryInfo = await connection.getAccountInfo(insured.treasury);
+      const rent = await connection.getMinimumBalanceForRentExemption(
+        treasuryInfo.data.length,
+      );
+      const vaultBefore = await connection.getBalance(insured.vault);
+
+      await rebalance(insuredHouse);
+
+      assert.equal(
+        (await connection.getBalance(insured.vault)) - vaultBefore,
+        treasuryInfo.lamports - rent,
+        "The vault should receive everything in the treasury above rent",
+      );
+      assert.equal(await connection.getBalance(insured.treasury), rent);
+    });
+
+    it("Rejects a rebalance once the treasury is empty", async () => {
+      try {
+        await rebalance(insuredHouse);
+        assert.fail("Rebalance should fail - treasury empty");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "InsufficientTreasuryBalance",
+        );
+      }
+    });
+
+    it("Rejects a rebalance while the vault is above the threshold", async () => {
+      try {
+        await rebalance(house, housePdas(house.publicKey));
+        assert.fail("Rebalance should fail - vault above threshold");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "VaultAboveRebalanceThreshold",
+        );
+      }
+    });
+  });
+
+  describe("Vault Rent", () => {
+    it("Rejects funding that leaves a fresh vault below rent exemption", async () => {
+      const emptyHouse = Keypair.generate();
+      try {
+        await program.methods
+          .fundVault(0, new anchor.BN(1))
+          .accountsStrict({
+            funder: player.publicKey,
+            house: emptyHouse.publicKey,
+            vault: housePdas(emptyHouse.publicKey).vault,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Funding should fail - vault not rent-exempt");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "VaultWouldBecomeRentExempt",
+        );
+      }
+    });
+
+    it("Rejects a withdrawal that would drain the vault below rent exemption", async () => {
+      const drainHouse = Keypair.generate();
+      const drained = await setupHouse(drainHouse);
+      const vaultBalance = await connection.getBalance(drained.vault);
+      const rentMinimum =
+        await connection.getMinimumBalanceForRentExemption(0);
+      const withdraw = (amount: number) =>
+        program.methods
+          .withdrawHouse(new anchor.BN(amount))
+          .accountsStrict({
+            authority: drainHouse.publicKey,
+            house: drainHouse.publicKey,
+            vault: drained.vault,
+            config: drained.config,
+            jackpot: drained.jackpot,
+            jackpots: drained.jackpots,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([drainHouse])
+          .rpc();
+
+      try {
+        await withdraw(vaultBalance);
+        assert.fail("Withdraw should fail - vault would not be rent-exempt");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "VaultWouldBecomeRentExempt",
+        );
+      }
+      await withdraw(vaultBalance - rentMinimum);
+      assert.equal(await connection.getBalance(drained.vault), rentMinimum);
+    });
+  });
+
+  describe("Withdraw House", () => {
+    it("Rejects a withdrawal that dips into reserved funds", async () => {
+      const vaultBalance = await connection.getBalance(vault);
+      try {
+        await program.methods
+          .withdrawHouse(new anchor.BN(vaultBalance))
+          .accountsStrict({
+            authority: house.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([house])
+          .rpc();
+        assert.fail("Withdraw should fail - funds are reserved");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal(
+          "InsufficientUnreservedFunds",
+        );
+      }
+    });
+
+    it("Withdraws unreserved funds to the house", async () => {
+      const configAccount = await program.account.houseConfig.fetch(config);
+      const vaultBalanceBefore = await connection.getBalance(vault);
+      const unreserved =
+        vaultBalanceBefore - configAccount.reservedLamports.toNumber();
+      const withdrawAmount = new anchor.BN(Math.floor(unreserved / 2));
+
+      await program.methods
+        .withdrawHouse(withdrawAmount)
+        .accountsStrict({
+          authority: house.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          jackpot,
+          jackpots,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([house])
+        .rpc();
+
+      const vaultBalanceAfter = await connection.getBalance(vault);
+      assert.equal(
+        vaultBalanceBefore - vaultBalanceAfter,
+        withdrawAmount.toNumber(),
+        "Vault should decrease by the withdrawn amount",
+      );
+    });
+  });
+
+  describe("Withdrawal Timelock", () => {
+    const lockedHouse = Keypair.generate();
+    const locked = housePdas(lockedHouse.publicKey);
+    const timelockSlots = 20;
+    const amount = new anchor.BN(0.5 * LAMPORTS_PER_SOL);
+
+    const withdrawAccounts = {
+      authority: lockedHouse.publicKey,
+      house: lockedHouse.publicKey,
+      vault: locked.vault,
+      config: locked.config,
+      jackpot: locked.jackpot,
+      jackpots: locked.jackpots,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const claim = () =>
+      program.methods
+        .claimWithdrawal()
+        .accountsStrict(withdrawAccounts)
+        .signers([lockedHouse])
+        .rpc();
+
+    before(async () => {
+      await setupHouse(lockedHouse, {
+        withdrawalTimelockSlots: new anchor.BN(timelockSlots),
       });
+    });
+
+    it("Refuses an immediate withdraw_house", async () => {
+      try {
+        await program.methods
+          .withdrawHouse(amount)
+          .accountsStrict(withdrawAccounts)
+          .signers([lockedHouse])
+          .rpc();
+        assert.fail("Withdraw should fail - house has a timelock");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("WithdrawalStillLocked");
+      }
+    });
+
+    it("Rejects a claim with nothing requested", async () => {
+      try {
+        await claim();
+        assert.fail("Claim should fail - no request");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("NoPendingWithdrawal");
+      }
+    });
 
-      const resolveIx = await program.methods
+    it("Rejects a claim before the timelock passes", async () => {
+      await program.methods
+        .requestWithdrawal(amount)
+        .accountsStrict({
+          authority: lockedHouse.publicKey,
+          house: lockedHouse.publicKey,
+          config: locked.config,
+        })
+        .signers([lockedHouse])
+        .rpc();
+      const vaultBefore = await connection.getBalance(locked.vault);
+      try {
+        await claim();
+        assert.fail("Claim should fail - still locked");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("WithdrawalStillLocked");
+      }
+      expect(await connection.getBalance(locked.vault)).to.equal(
+        vaultBefore,
+        "Requested funds should stay in the vault",
+      );
+    });
+
+    it("Pays the request out once the timelock has passed", async () => {
+      const configBefore = await program.account.houseConfig.fetch(
+        locked.config,
+      );
+      const unlockSlot = configBefore.withdrawalUnlockSlot.toNumber();
+      while ((await connection.getSlot("processed")) < unlockSlot) {
+        await new Promise((resolve) => setTimeout(resolve, 200));
+      }
+
+      const vaultBefore = await connection.getBalance(locked.vault);
+      await claim();
+      expect(vaultBefore - (await connection.getBalance(locked.vault))).to.equal(
+        amount.toNumber(),
+      );
+      const configAfter = await program.account.houseConfig.fetch(
+        locked.config,
+      );
+      expect(configAfter.pendingWithdrawal.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("Fee Reinvestment", () => {
+    const amount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+
+    // Loses a bet against a fresh house, returning how much the resolution
+    // moved out of the vault and into the fee vault.
+    const loseBet = async (reinvestFees: boolean) => {
+      const houseKp = Keypair.generate();
+      const pdas = await setupHouse(houseKp, { reinvestFees });
+      const { betPda, ed25519Ix } = await placeBetWithOutcome(
+        player,
+        houseKp.publicKey,
+        50,
+        amount,
+        false,
+      );
+      const vaultBefore = await connection.getBalance(pdas.vault);
+      const feeVaultBefore = await connection.getBalance(pdas.feeVault);
+      await program.methods
         .resolveBet(ed25519Ix.data.subarray(48, 112))
         .accountsStrict({
           player: player.publicKey,
+          rentPayer: player.publicKey,
+          resolver: resolver.publicKey,
+          house: houseKp.publicKey,
+          vault: pdas.vault,
+          config: pdas.config,
+          jackpot: pdas.jackpot,
+          jackpots: pdas.jackpots,
+          feeVault: pdas.feeVault,
+          treasury: pdas.treasury,
+          recentOutcomes: pdas.recentOutcomes,
+          ledger: pdas.ledger,
+          bet: betPda,
+          stats: pdas.stats(player.publicKey),
+          playerBalance: null,
+          pendingPayout: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          priceUpdate: null,
+          rewardMint: null,
+          playerRewardAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([resolver])
+        .rpc();
+      return {
+        vaultOut: vaultBefore - (await connection.getBalance(pdas.vault)),
+        feeVaultIn:
+          (await connection.getBalance(pdas.feeVault)) - feeVaultBefore,
+      };
+    };
+
+    // The fee less the jackpot's cut; these houses pay no referral or keeper.
+    const houseFee = Math.floor((amount.toNumber() * feeBps) / 10_000);
+    const profit =
+      houseFee - Math.floor((houseFee * jackpotContributionBps) / 10_000);
+
+    it("Moves the house's fee share to the fee vault by default", async () => {
+      const { vaultOut, feeVaultIn } = await loseBet(false);
+      assert.equal(feeVaultIn, profit);
+      assert.equal(vaultOut, profit);
+    });
+
+    it("Keeps the fee share in the vault when reinvesting", async () => {
+      const { vaultOut, feeVaultIn } = await loseBet(true);
+      assert.equal(feeVaultIn, 0);
+      assert.equal(vaultOut, 0, "The whole lost stake should stay as bankroll");
+    });
+  });
+
+  describe("Withdraw Fees", () => {
+    const withdrawFees = (amount: anchor.BN) =>
+      program.methods
+        .withdrawFees(amount)
+        .accountsStrict({
+          authority: house.publicKey,
+          house: house.publicKey,
+          config,
+          feeVault,
+        })
+        .signers([house])
+        .rpc();
+
+    it("Rejects a withdrawal that dips into the fee vault rent", async () => {
+      const feeVaultBalance = await connection.getBalance(feeVault);
+      try {
+        await withdrawFees(new anchor.BN(feeVaultBalance));
+        assert.fail("Withdraw should fail - rent must stay in the fee vault");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InsufficientFeeBalance");
+      }
+    });
+
+    it("Withdraws accumulated fees to the house", async () => {
+      const feeVaultInfo = await connection.getAccountInfo(feeVault);
+      const rent = await connection.getMinimumBalanceForRentExemption(
+        feeVaultInfo.data.length,
+      );
+      const available = feeVaultInfo.lamports - rent;
+      assert.isAbove(available, 0, "Resolutions should have collected fees");
+
+      await withdrawFees(new anchor.BN(available));
+
+      assert.equal(
+        await connection.getBalance(feeVault),
+        rent,
+        "Only rent should remain in the fee vault",
+      );
+    });
+  });
+
+  describe("Pause", () => {
+    const setPaused = (paused: boolean) =>
+      program.methods
+        .setPaused(paused)
+        .accountsStrict({
+          authority: house.publicKey,
+          house: house.publicKey,
+          config,
+        })
+        .signers([house])
+        .rpc();
+
+    const refundSeed = new anchor.BN(3_001);
+    const refundAmount = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    const emergencyRefund = () =>
+      program.methods
+        .emergencyRefund()
+        .accountsStrict({
+          authority: house.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          bet: findBetPda(refundSeed),
+          stats,
+          freeCredits: null,
+          playerBalance: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([house])
+        .rpc({ commitment: "confirmed" });
+
+    after(async () => {
+      await setPaused(false);
+    });
+
+    it("Rejects an emergency refund outside an incident", async () => {
+      await program.methods
+        .placeBet(refundSeed, betArgs(50, refundAmount))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
           house: house.publicKey,
           vault,
-          bet: betPda,
-          instructions: instructionSysvar,
+          config,
+          stats,
+          bet: findBetPda(refundSeed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
           systemProgram: SystemProgram.programId,
         })
-        .instruction();
+        .signers([player])
+        .rpc();
 
-      const vaultBalanceBefore = await connection.getBalance(vault);
+      try {
+        await emergencyRefund();
+        assert.fail("Emergency refund should fail - game not paused");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("GameNotPaused");
+      }
+    });
+
+    it("Rejects new bets while paused", async () => {
+      await setPaused(true);
+
+      const seed = new anchor.BN(3_000);
+      try {
+        await program.methods
+          .placeBet(seed, betArgs(50, minBet))
+          .accountsStrict({
+            player: player.publicKey,
+            rentPayer: player.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            stats,
+            bet: findBetPda(seed),
+            priceUpdate: null,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            allowlisted: null,
+            selfExclusion: selfExclusionFor(player.publicKey),
+            usedSeed: null,
+            freeCredits: null,
+            playerBalance: null,
+            burnMint: null,
+            playerBurnAta: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+        assert.fail("Place bet should fail - game paused");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("GamePaused");
+      }
+    });
+
+    it("Refunds an open bet immediately while paused", async () => {
+      const betPda = findBetPda(refundSeed);
+      const betRent = (await connection.getAccountInfo(betPda)).lamports;
       const playerBalanceBefore = await connection.getBalance(player.publicKey);
-      const betRentLamports = betAccountInfo.lamports;
+      const configBefore = await program.account.houseConfig.fetch(config);
+      const betAccount = await program.account.bet.fetch(betPda);
 
-      const tx = new Transaction().add(ed25519Ix).add(resolveIx);
-      const sig = await sendAndConfirmTransaction(connection, tx, [house]);
-      console.log("Resolve Bet tx:", sig);
+      const sig = await emergencyRefund();
 
-      const closedBetAccount = await connection.getAccountInfo(betPda);
+      const playerBalanceAfter = await connection.getBalance(player.publicKey);
+      assert.equal(
+        playerBalanceAfter - playerBalanceBefore,
+        refundAmount.toNumber() + betRent,
+        "Player should get the stake and the bet rent back",
+      );
+      assert.isNull(
+        await connection.getAccountInfo(betPda),
+        "Bet should be closed",
+      );
+      const configAfter = await program.account.houseConfig.fetch(config);
+      assert.equal(
+        configBefore.reservedLamports
+          .sub(configAfter.reservedLamports)
+          .toString(),
+        betAccount.potentialPayout.toString(),
+        "The bet's reservation should be released",
+      );
 
-      expect(closedBetAccount.data.length).to.equal(
-        0,
-        "Bet account should be closed after resolution(data === 0)",
+      const txDetails = await connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl),
       );
-      expect(closedBetAccount.lamports).to.equal(
-        0,
-        "Bet account should be closed after resolution(lamporsts === 0)",
+      const events = [...eventParser.parseLogs(txDetails.meta.logMessages)];
+      const refund = events.find((e) => e.name === "emergencyRefund");
+      assert.ok(refund, "EmergencyRefund event should be emitted");
+      assert.equal(refund.data.seed.toString(), refundSeed.toString());
+      assert.equal(
+        refund.data.amount.toString(),
+        refundAmount.toString(),
+        "Event should report the refunded stake",
       );
+    });
+  });
 
-      const vaultBalanceAfter = await connection.getBalance(vault);
-      const playerBalanceAfter = await connection.getBalance(player.publicKey);
+  describe("Reconcile Reserved", () => {
+    const reconcileHouse = Keypair.generate();
+    const reconciled = housePdas(reconcileHouse.publicKey);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const openBets: PublicKey[] = [];
 
-      const playerBalanceChange = playerBalanceAfter - playerBalanceBefore;
-      const vaultBalanceChange = vaultBalanceBefore - vaultBalanceAfter;
+    const setPaused = (paused: boolean) =>
+      program.methods
+        .setPaused(paused)
+        .accountsStrict({
+          authority: reconcileHouse.publicKey,
+          house: reconcileHouse.publicKey,
+          config: reconciled.config,
+        })
+        .signers([reconcileHouse])
+        .rpc();
 
-      if (vaultBalanceChange > 0) {
-        // Player won
-        const expectedPayout = Math.floor(
-          (amount.toNumber() * (10_000 - 150)) / 10_000,
-        );
-        assert.equal(
-          vaultBalanceChange,
-          expectedPayout,
-          "Vault should decrease by payout amount on win",
-        );
-        assert.equal(
-          playerBalanceChange,
-          betRentLamports + expectedPayout,
-          "Player should receive rent + payout on win",
-        );
-        console.log(
-          `Player won! Roll: ${betAccountBefore.roll}, Payout: ${
-            expectedPayout / LAMPORTS_PER_SOL
-          } SOL`,
-        );
-      } else {
-        // Player lost
-        assert.equal(
-          vaultBalanceChange,
-          0,
-          "Vault balance should remain unchanged on loss",
-        );
-        assert.equal(
-          playerBalanceChange,
-          betRentLamports,
-          "Player should only receive rent back on loss",
-        );
-        console.log(
-          `Player lost. Roll: ${betAccountBefore.roll}, no payout from vault`,
+    const reconcile = (reserved: anchor.BN | null, bets: PublicKey[] = []) =>
+      program.methods
+        .reconcileReserved(reserved)
+        .accountsStrict({
+          authority: reconcileHouse.publicKey,
+          house: reconcileHouse.publicKey,
+          vault: reconciled.vault,
+          config: reconciled.config,
+        })
+        .remainingAccounts(
+          bets.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false })),
+        )
+        .signers([reconcileHouse])
+        .rpc();
+
+    const reserved = async () =>
+      (
+        await program.account.houseConfig.fetch(reconciled.config)
+      ).reservedLamports.toNumber();
+
+    before(async () => {
+      await setupHouse(reconcileHouse);
+      for (const wantWin of [true, false]) {
+        const { betPda } = await placeBetWithOutcome(
+          player,
+          reconcileHouse.publicKey,
+          50,
+          amount,
+          wantWin,
         );
+        openBets.push(betPda);
+      }
+      openBets.sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()));
+    });
+
+    it("Rejects reconciling while the game is live", async () => {
+      try {
+        await reconcile(new anchor.BN(0));
+        assert.fail("Reconcile should fail - game not paused");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("GameNotPaused");
+      }
+    });
+
+    it("Recomputes a drifted counter from the open bets", async () => {
+      const expected = await reserved();
+      await setPaused(true);
+
+      // Simulates the drift a manual intervention could leave behind.
+      await reconcile(new anchor.BN(expected + 12_345));
+      assert.equal(await reserved(), expected + 12_345);
+
+      await reconcile(null, openBets);
+      let sum = 0;
+      for (const bet of openBets) {
+        sum += (await program.account.bet.fetch(bet)).potentialPayout.toNumber();
+      }
+      assert.equal(sum, expected);
+      assert.equal(await reserved(), expected);
+    });
+
+    it("Rejects a bet listed twice", async () => {
+      try {
+        await reconcile(null, [openBets[0], openBets[0]]);
+        assert.fail("Reconcile should fail - bet counted twice");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("BetsNotAscending");
+      }
+    });
+  });
+
+  describe("Migrate Bet", () => {
+    const migrateSeed = new anchor.BN(3_101);
+    const migrate = (signer: Keypair) =>
+      program.methods
+        .migrateBet()
+        .accountsStrict({
+          authority: signer.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          bet: findBetPda(migrateSeed),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .placeBet(migrateSeed, betArgs(50, new anchor.BN(0.01 * LAMPORTS_PER_SOL)))
+        .accountsStrict({
+          player: player.publicKey,
+          rentPayer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          stats,
+          bet: findBetPda(migrateSeed),
+          priceUpdate: null,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          allowlisted: null,
+          selfExclusion: selfExclusionFor(player.publicKey),
+          usedSeed: null,
+          freeCredits: null,
+          playerBalance: null,
+          burnMint: null,
+          playerBurnAta: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("Stamps new bets with the current layout version", async () => {
+      const bet = await program.account.bet.fetch(findBetPda(migrateSeed));
+      expect(bet.layoutVersion).to.equal(13);
+    });
+
+    it("Sizes bets for their largest encoding", async () => {
+      // `Bet::LEN` must cover the bet with every optional field set, and
+      // nothing more.
+      const betPda = findBetPda(migrateSeed);
+      const bet = await program.account.bet.fetch(betPda);
+      const some = Keypair.generate().publicKey;
+      const encoded = await program.coder.accounts.encode("bet", {
+        ...bet,
+        mint: some,
+        referrer: some,
+        randomnessAccount: some,
+        cachedOutcome: { roll: 1, won: true, payout: new anchor.BN(0) },
+      });
+      const info = await connection.getAccountInfo(betPda);
+      assert.equal(info.data.length, encoded.length);
+    });
+
+    it("Rejects migrating a bet already on the current layout", async () => {
+      try {
+        await migrate(house);
+        assert.fail("Migration should fail - bet is current");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("BetAlreadyMigrated");
+      }
+    });
+
+    it("Migrates a legacy bet with its optional fields unset", async function () {
+      // Only present when built with `--features test-randomness`.
+      if (
+        !program.idl.instructions.some(
+          (ix) => ix.name === "create_legacy_bet",
+        )
+      ) {
+        this.skip();
+      }
+      const legacySeed = new anchor.BN(3_102);
+      const [legacyPda, legacyBump] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("bet"),
+          vault.toBytes(),
+          player.publicKey.toBytes(),
+          legacySeed.toArrayLike(Buffer, "le", 16),
+        ],
+        program.programId,
+      );
+      const current = await program.account.bet.fetch(findBetPda(migrateSeed));
+      const encoded = await program.coder.accounts.encode("bet", {
+        ...current,
+        seed: legacySeed,
+        bump: legacyBump,
+        mint: null,
+        referrer: null,
+        randomnessAccount: null,
+        cachedOutcome: null,
+      });
+      // Layout 9 ends just before `terms` (13 bytes), followed by
+      // `presigned_sig`, `client_seed`, `cached_outcome` and the version;
+      // its accounts were sized with every option set, so the unset ones
+      // leave zero padding after the version byte.
+      const termsOnward = 13 + 64 + 32 + 1 + 1;
+      const legacy = Buffer.alloc(program.account.bet.size - (13 + 64 + 32 + 12));
+      Buffer.concat([
+        encoded.subarray(0, encoded.length - termsOnward),
+        Buffer.from([9]),
+      ]).copy(legacy);
+
+      await program.methods
+        .createLegacyBet(legacySeed, legacy)
+        .accountsStrict({
+          payer: player.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          player: player.publicKey,
+          bet: legacyPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+      await program.methods
+        .migrateBet()
+        .accountsStrict({
+          authority: house.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          bet: legacyPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([house])
+        .rpc();
+
+      const migrated = await program.account.bet.fetch(legacyPda);
+      expect(migrated.layoutVersion).to.equal(13);
+      assert.ok(migrated.seed.eq(legacySeed));
+      assert.ok(migrated.amount.eq(current.amount));
+      assert.equal(migrated.roll, current.roll);
+      assert.isNull(migrated.mint);
+      assert.isNull(migrated.referrer);
+      assert.isNull(migrated.cachedOutcome);
+      assert.equal(migrated.faces, 100);
+      assert.deepEqual(migrated.clientSeed, Array(32).fill(0));
+      assert.ok(
+        migrated.rentPayer.equals(current.rentPayer),
+        "Fields the layout had should carry over",
+      );
+      const info = await connection.getAccountInfo(legacyPda);
+      assert.equal(info.data.length, program.account.bet.size);
+    });
+
+    it("Rejects migration by anyone but the authority", async () => {
+      try {
+        await migrate(player);
+        assert.fail("Migration should fail - not the authority");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+      }
+    });
+  });
+
+  describe("Close Game", () => {
+    it("Rejects closing while bets are still open", async () => {
+      try {
+        await program.methods
+          .closeGame()
+          .accountsStrict({
+            authority: house.publicKey,
+            house: house.publicKey,
+            vault,
+            config,
+            jackpot,
+            jackpots,
+            feeVault,
+            treasury,
+            recentOutcomes,
+            ledger,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([house])
+          .rpc();
+        assert.fail("Close game should fail - bets are open");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("OpenBetsRemain");
       }
     });
   });